@@ -253,6 +253,70 @@ fn get_int(fmt: &mut impl FormatString) -> Result<usize, Error> {
     Ok(i)
 }
 
+// Read an argument position like "2$" from a format string, as used by "%2$s" and "%*2$d".
+// If there is none, return None and leave the format string unchanged.
+// Otherwise adjust the format string to point to the char after the '$'.
+fn get_position(fmt: &mut impl FormatString) -> Result<Option<usize>, Error> {
+    let mut digits = 0;
+    while fmt.at(digits).is_some_and(|c| c.is_ascii_digit()) {
+        digits += 1;
+    }
+    if digits == 0 || fmt.at(digits) != Some('$') {
+        return Ok(None);
+    }
+    let position = get_int(fmt)?;
+    fmt.advance_by(1);
+    // Positions start at 1.
+    if position == 0 {
+        return Err(Error::BadFormatString);
+    }
+    Ok(Some(position))
+}
+
+// The arguments of a format string. These are either all taken in order, or all selected
+// by position with "%n$".
+struct Args<'a, 'b> {
+    args: &'a mut [Arg<'b>],
+    // The index of the next argument to take in order.
+    next: usize,
+    // Whether arguments are selected by position, or None if no argument was taken yet.
+    positional: Option<bool>,
+    // The number of arguments up to the last one that was taken.
+    used: usize,
+}
+
+impl<'a, 'b> Args<'a, 'b> {
+    fn new(args: &'a mut [Arg<'b>]) -> Self {
+        Args {
+            args,
+            next: 0,
+            positional: None,
+            used: 0,
+        }
+    }
+
+    // Return the argument at the given position (starting from 1), or the next argument if None.
+    // Mixing the two is an error.
+    fn get(&mut self, position: Option<usize>) -> Result<&mut Arg<'b>, Error> {
+        let positional = position.is_some();
+        if *self.positional.get_or_insert(positional) != positional {
+            return Err(Error::BadFormatString);
+        }
+        let index = position.map_or(self.next, |p| p - 1);
+        if !positional {
+            self.next += 1;
+        }
+        let arg = self.args.get_mut(index).ok_or(Error::MissingArg)?;
+        self.used = self.used.max(index + 1);
+        Ok(arg)
+    }
+
+    // Return true if any argument was never reached.
+    fn has_extra(&self) -> bool {
+        self.used < self.args.len()
+    }
+}
+
 // Read a conversion prefix from a format string, advancing it.
 fn get_prefix(fmt: &mut impl FormatString) -> ConversionPrefix {
     use ConversionPrefix as CP;
@@ -325,7 +389,8 @@ pub(super) fn pad(
 /// - `f`: The receiver of formatted output.
 /// - `fmt`: The format string being parsed.
 /// - `locale`: The locale to use for number formatting.
-/// - `args`: Iterator over the arguments to format.
+/// - `args`: The arguments to format. Conversions take these in order, unless they all select
+///   one by position, like `%2$s` or `%*1$d`.
 ///
 /// # Returns
 /// A `Result` which is `Ok` containing the number of bytes written on success, or an `Error`.
@@ -353,7 +418,7 @@ pub fn sprintf_locale(
 ) -> Result<usize, Error> {
     use ConversionSpec as CS;
     let mut s = fmt;
-    let mut args = args.iter_mut();
+    let mut args = Args::new(args);
     let mut out_len: usize = 0;
 
     // Shared storage for the output of the conversion specifier.
@@ -375,6 +440,9 @@ pub fn sprintf_locale(
         debug_assert_eq!(s.at(0), Some('%'));
         s.advance_by(1);
 
+        // Read the position of the argument, as in "%2$s".
+        let position = get_position(&mut s)?;
+
         // Read modifier flags. '-' and '0' flags are mutually exclusive.
         let mut flags = ModifierFlags::default();
         while flags.try_set(s.at(0).unwrap_or('\0')) {
@@ -384,10 +452,11 @@ pub fn sprintf_locale(
             flags.zero_pad = false;
         }
 
-        // Read field width. We do not support $.
+        // Read field width, which may be taken from an argument as in "%*d" or "%*1$d".
         let desired_width = if s.at(0) == Some('*') {
-            let arg_width = args.next().ok_or(Error::MissingArg)?.as_sint()?;
             s.advance_by(1);
+            let width_position = get_position(&mut s)?;
+            let arg_width = args.get(width_position)?.as_sint()?;
             if arg_width < 0 {
                 flags.left_adj = true;
             }
@@ -399,12 +468,13 @@ pub fn sprintf_locale(
             get_int(&mut s)?
         };
 
-        // Optionally read precision. We do not support $.
+        // Optionally read precision, which may also be taken from an argument.
         let mut desired_precision: Option<usize> = if s.at(0) == Some('.') && s.at(1) == Some('*') {
             // "A negative precision is treated as though it were missing."
             // Here we assume the precision is always signed.
             s.advance_by(2);
-            let p = args.next().ok_or(Error::MissingArg)?.as_sint()?;
+            let precision_position = get_position(&mut s)?;
+            let p = args.get(precision_position)?.as_sint()?;
             p.try_into().ok()
         } else if s.at(0) == Some('.') {
             s.advance_by(1);
@@ -419,12 +489,12 @@ pub fn sprintf_locale(
 
         // Read out the format specifier and arg.
         let conv_spec = get_specifier(&mut s)?;
-        let arg = args.next().ok_or(Error::MissingArg)?;
+        let arg = args.get(position)?;
         let mut prefix = "";
 
-        // Thousands grouping only works for d,u,i,f,F.
-        // 'i' is mapped to 'd'.
-        if flags.grouped && !matches!(conv_spec, CS::d | CS::u | CS::f | CS::F) {
+        // Thousands grouping only works for d,u,i,f,F,g,G.
+        // 'i' is mapped to 'd'. Note %g only groups if it does not use an exponent.
+        if flags.grouped && !matches!(conv_spec, CS::d | CS::u | CS::f | CS::F | CS::g | CS::G) {
             return Err(Error::BadFormatString);
         }
 
@@ -622,7 +692,7 @@ pub fn sprintf_locale(
     }
 
     // Too many args?
-    if args.next().is_some() {
+    if args.has_extra() {
         return Err(Error::ExtraArg);
     }
     Ok(out_len)
@@ -787,7 +787,7 @@ fn test_errors() {
     sprintf_err!("%c", 0xD800 => Overflow);
     sprintf_err!("%c", 0xD8FF => Overflow);
 
-    // Apostrophe only works for d,u,i,f,F
+    // Apostrophe only works for d,u,i,f,F,g,G
     sprintf_err!("%'c", 0 => BadFormatString);
     sprintf_err!("%'o", 0 => BadFormatString);
     sprintf_err!("%'x", 0 => BadFormatString);
@@ -797,8 +797,36 @@ fn test_errors() {
     sprintf_err!("%'A", 0 => BadFormatString);
     sprintf_err!("%'e", 0 => BadFormatString);
     sprintf_err!("%'E", 0 => BadFormatString);
-    sprintf_err!("%'g", 0 => BadFormatString);
-    sprintf_err!("%'G", 0 => BadFormatString);
+
+    // Positions start at 1, and can't be mixed with arguments taken in order.
+    sprintf_err!("%0$d", 1 => BadFormatString);
+    sprintf_err!("%1$d %d", 1, 2 => BadFormatString);
+    sprintf_err!("%d %1$d", 1 => BadFormatString);
+    sprintf_err!("%1$*d", 1, 2 => BadFormatString);
+    sprintf_err!("%*1$d", 1, 2 => BadFormatString);
+    sprintf_err!("%3$d", 1, 2 => MissingArg);
+    sprintf_err!("%1$d", 1, 2 => ExtraArg);
+    sprintf_err!("%2$*3$d", 1, 2 => MissingArg);
+}
+
+#[test]
+fn test_positional() {
+    assert_fmt!("%2$s %1$s", "world", "hello" => "hello world");
+    assert_fmt!("%1$s %1$s", "again" => "again again");
+    assert_fmt!("%2$s", "unused", "used" => "used");
+    assert_fmt!("%1$d%%%2$5.1f|", 10, 2.25 => "10%  2.2|");
+    assert_fmt!("%1$-4d|%1$04d", 7 => "7   |0007");
+    assert_fmt!("%10$c", 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j' => "j");
+
+    // Width and precision can be taken from an argument by position too.
+    assert_fmt!("%2$*1$d|", 5, 42 => "   42|");
+    assert_fmt!("%2$-*1$d|", 5, 42 => "42   |");
+    assert_fmt!("%3$*1$.*2$f|", 8, 2, 1.23456 => "    1.23|");
+    assert_fmt!("%1$.*2$s", "abcdef", 3 => "abc");
+
+    // Flags and zero-padding still work after a position.
+    assert_fmt!("%1$05d", 42 => "00042");
+    assert_fmt!("%1$+d %1$#x", 255 => "+255 0xff");
 }
 
 #[test]
@@ -835,6 +863,11 @@ fn test_printf_loc<'a>(expected: &str, locale: &Locale, format: &str, arg: impl
     test_printf_loc("1!2!345", &locale, "%'7d", 12345);
     test_printf_loc(" 1!2!345", &locale, "%'8d", 12345);
     test_printf_loc("+1!2!345", &locale, "%'+d", 12345);
+    test_printf_loc("12!3!456", &locale, "%'g", 123456.0);
+    test_printf_loc("123!4!567", &locale, "%'.7g", 1234567.0);
+    test_printf_loc("1,23457e+06", &locale, "%'g", 1234567.0);
+    test_printf_loc("123!4!567,89", &locale, "%'.9G", 1234567.89);
+    test_printf_loc("1!2!345 1!2!345", &locale, "%1$'d %1$'d", 12345);
 
     // Thousands seps count as width, and so remove some leading zeros.
     // Padding does NOT use thousands sep.
@@ -846,6 +879,7 @@ fn test_printf_loc<'a>(expected: &str, locale: &Locale, format: &str, arg: impl
         "%'0.30d",
         1222333444,
     );
+    test_printf_loc("1,234,567.5", &EN_US_LOCALE, "%'.8g", 1234567.5);
 }
 
 #[test]
@@ -1,2 +1,2 @@
-complete -c bg -x -a "(__fish_complete_job_pids)"
+complete -c bg -x -a "(__fish_complete_job_specs; __fish_complete_job_pids)"
 complete -c bg -s h -l help -d 'Display help and exit'
@@ -1,2 +1,2 @@
 complete -c disown -s h -l help -d "Display help and exit"
-complete -c disown -x -a "(__fish_complete_job_pids)"
+complete -c disown -x -a "(__fish_complete_job_specs; __fish_complete_job_pids)"
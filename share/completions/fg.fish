@@ -1,2 +1,2 @@
-complete -c fg -x -a "(__fish_complete_job_pids)"
+complete -c fg -x -a "(__fish_complete_job_specs; __fish_complete_job_pids)"
 complete -c fg -s h -l help -d 'Display help and exit'
@@ -1,3 +1,4 @@
 complete -c for -n 'test (count (commandline -xpc)) -eq 1' -s h -l help -d 'Display help and exit'
 complete -c for -n 'test (count (commandline -xpc)) -eq 1' -f
 complete -c for -n 'test (count (commandline -xpc)) -eq 2' -xa in
+complete -c for -n 'test (count (commandline -xpc)) -eq 1' -l parallel -x -d 'Run up to N iterations at once'
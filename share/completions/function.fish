@@ -31,10 +31,15 @@ complete -c function -s d -l description -d "Set function description" -x
 complete -c function -xa "(functions -n)" -d Function
 complete -c function -xa "(builtin -n)" -d Builtin
 complete -c function -s j -l on-job-exit -d "Make the function a job exit event handler" -x
+complete -c function -l on-job-start -d "Make the function a job start event handler"
+complete -c function -l on-job-stop -d "Make the function a job stop event handler"
+complete -c function -l on-job-continue -d "Make the function a job continue event handler"
 complete -c function -s p -l on-process-exit -d "Make the function a process exit event handler" -x
 complete -c function -s s -l on-signal -d "Make the function a signal event handler" -xka "(__fish_complete_signals)"
 complete -c function -s v -l on-variable -d "Make the function a variable update event handler" -xa "(__fish_complete_variables)"
 complete -c function -s e -l on-event -d "Make the function a generic event handler" -xa "(__fish_complete_function_event_handlers)"
+complete -c function -l on-timer -d "Run the function repeatedly, every DURATION" -x
+complete -c function -l on-timeout -d "Run the function once, after DURATION" -x
 complete -c function -s a -l argument-names -d "Specify named arguments" -x
 complete -c function -s S -l no-scope-shadowing -d "Do not shadow variable scope of calling function"
 complete -c function -s w -l wraps -d "Inherit completions from the given command" -xa "(__fish_complete_command)"
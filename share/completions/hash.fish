@@ -0,0 +1,4 @@
+complete -c hash -s h -l help -d 'Display help and exit'
+complete -c hash -s r -l reset -d 'Forget all remembered commands'
+complete -c hash -s d -l delete -d 'Forget the given commands' -xa '(builtin hash | string replace -r " .*" "")'
+complete -c hash -n 'not __fish_seen_argument -s d -l delete -s r -l reset' -xa '(__fish_complete_command)'
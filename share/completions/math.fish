@@ -1,3 +1,6 @@
 complete -f -c math -r
 complete -f -c math -s s -l scale -r
-complete -f -c math -s b -l base -r
+complete -f -c math -s b -l base -r -a "hex octal binary"
+complete -f -c math -s v -l var -r -d "Bind a variable for the expression"
+complete -f -c math -l define -r -d "Define a function for the session"
+complete -f -c math -s i -l integer -d "Compute with exact 128-bit integers"
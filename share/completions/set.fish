@@ -103,6 +103,9 @@ complete -c set -n "__fish_is_nth_token 1" -s S -l show -d "Show variable"
 complete -c set -n "__fish_is_nth_token 1" -l path -d "Make variable as a path variable"
 complete -c set -n "__fish_is_nth_token 1" -l unpath -d "Make variable not as a path variable"
 complete -c set -n "__fish_is_nth_token 1" -l no-event -d "Don't emit an event"
+complete -c set -n "__fish_is_nth_token 1" -l map -d "Make variable a map of keys to values"
+complete -c set -n "__fish_is_nth_token 1" -l keys -d "List the keys of a map"
+complete -c set -n "__fish_is_nth_token 1" -l secret -d "Hide the value in listings and traces"
 
 #TODO: add CPP code to generate list of read-only variables and exclude them from the following completions
 
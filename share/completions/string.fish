@@ -17,6 +17,8 @@ complete -x -c string -n 'test (count (commandline -xpc)) -ge 2' -n 'string matc
 complete -x -c string -n 'test (count (commandline -xpc)) -ge 2' -n 'string match -qr split0\?\$ -- (commandline -xpc)[2]' -s f -l fields -a "(seq 1 10)" -d "Specify fields"
 complete -f -c string -n 'test (count (commandline -xpc)) -ge 2' -n 'string match -qr split0\?\$ -- (commandline -xpc)[2]' -s r -l right -d "Split right-to-left"
 complete -f -c string -n 'test (count (commandline -xpc)) -ge 2' -n 'string match -qr split0\?\$ -- (commandline -xpc)[2]' -s n -l no-empty -d "Empty results excluded"
+complete -f -c string -n 'test (count (commandline -xpc)) -ge 2' -n 'contains -- (commandline -xpc)[2] split' -l csv -d "Parse comma-separated values"
+complete -x -c string -n 'test (count (commandline -xpc)) -ge 2' -n 'contains -- (commandline -xpc)[2] split join' -l quote -d "Quote character for --csv"
 complete -f -c string -n "test (count (commandline -xpc)) -lt 2" -a collect
 complete -f -c string -n 'test (count (commandline -xpc)) -ge 2' -n 'string match -qr collect\$ -- (commandline -xpc)[2]' -s N -l no-trim-newlines -d "Don't trim trailing newlines"
 complete -f -c string -n 'test (count (commandline -xpc)) -ge 2' -n 'string match -qr collect\$ -- (commandline -xpc)[2]' -s a -l allow-empty -d "Always print empty argument"
@@ -24,6 +26,7 @@ complete -f -c string -n 'test (count (commandline -xpc)) -ge 2' -n 'string matc
 complete -f -c string -n "test (count (commandline -xpc)) -lt 2" -a join
 complete -f -c string -n "test (count (commandline -xpc)) -lt 2" -a join0
 complete -f -c string -n 'test (count (commandline -xpc)) -ge 2' -n 'contains -- (commandline -xpc)[2] join' -s n -l no-empty -d "Empty strings excluded"
+complete -f -c string -n 'test (count (commandline -xpc)) -ge 2' -n 'contains -- (commandline -xpc)[2] join' -l csv -d "Quote fields as comma-separated values"
 complete -f -c string -n "test (count (commandline -xpc)) -lt 2" -a trim
 complete -f -c string -n "test (count (commandline -xpc)) -ge 2" -n "contains -- (commandline -xpc)[2] trim" -s l -l left -d "Trim only leading chars"
 complete -f -c string -n "test (count (commandline -xpc)) -ge 2" -n "contains -- (commandline -xpc)[2] trim" -s r -l right -d "Trim only trailing chars"
@@ -57,6 +60,9 @@ complete -x -c string -n "test (count (commandline -xpc)) -ge 2" -n "contains --
 complete -x -c string -n "test (count (commandline -xpc)) -ge 2" -n "contains -- (commandline -xpc)[2] pad" -s r -l center -d "Pad both left and right"
 complete -x -c string -n "test (count (commandline -xpc)) -ge 2" -n "contains -- (commandline -xpc)[2] pad" -s c -l char -x -d "Character to use for padding"
 complete -x -c string -n "test (count (commandline -xpc)) -ge 2" -n "contains -- (commandline -xpc)[2] pad" -s w -l width -x -d "Integer width of the result, default is maximum width of inputs"
+complete -f -c string -n "test (count (commandline -xpc)) -lt 2" -a parse-time
+complete -x -c string -n "test (count (commandline -xpc)) -ge 2" -n "contains -- (commandline -xpc)[2] parse-time" -s f -l format -d "Parse with this strptime format instead of ISO-8601"
+complete -f -c string -n "test (count (commandline -xpc)) -ge 2" -n "contains -- (commandline -xpc)[2] parse-time" -s u -l utc -d "Times without an offset are in UTC"
 complete -f -c string -n "test (count (commandline -xpc)) -lt 2" -a shorten
 complete -x -c string -n "test (count (commandline -xpc)) -ge 2" -n "contains -- (commandline -xpc)[2] shorten" -s l -l left -d "Remove from the left on"
 complete -x -c string -n "test (count (commandline -xpc)) -ge 2" -n "contains -- (commandline -xpc)[2] shorten" -s c -l char -x -d "Characters to use as ellipsis"
@@ -1,9 +1,13 @@
+complete -c time -s r -l repeat -n __fish_no_arguments -d "Run the command N times and summarize" -x
+complete -c time -s w -l warmup -n __fish_no_arguments -d "Run the command M times before measuring" -x
+complete -c time -l json -n __fish_no_arguments -d "Print the results as JSON"
+
 if not command -q time
-    complete -c time -a "(__fish_complete_subcommand)" -d Command
+    complete -c time -a "(__fish_complete_subcommand -- -r --repeat -w --warmup)" -d Command
     exit
 end
 
-complete -c time -a "(__fish_complete_subcommand -- -o --output -f --format)" -d Command
+complete -c time -a "(__fish_complete_subcommand -- -o --output -f --format -r --repeat -w --warmup)" -d Command
 
 complete -c time -s f -l format -n __fish_no_arguments -d "Specify output format" -x
 complete -c time -s p -l portable -n __fish_no_arguments -d "Use the portable output format"
@@ -6,6 +6,7 @@ complete -c type -s p -l path -d "Print path to command, or nothing if name is n
 complete -c type -s P -l force-path -d "Print path to command"
 complete -c type -s q -l query -l quiet -d "Check if something exists without output"
 complete -c type -s s -l short -d "Don't print function definition"
+complete -c type -l suggest -d "Print names the name might be a misspelling of"
 complete -c type -l color -d "When to colorize output" -xa "always never auto"
 
 complete -c type -a "(builtin -n)" -d Builtin
@@ -1,3 +1,6 @@
-complete -c wait -xa '(__fish_complete_job_pids)'
+complete -c wait -xa '(__fish_complete_job_specs; __fish_complete_job_pids)'
 complete -c wait -s n -l any -d 'Return as soon as the first job completes'
+complete -c wait -s t -l timeout -x -d 'Give up after the given duration'
+complete -c wait -s s -l signal -xa '(__fish_make_completion_signals; string split -f2 " " -- $__kill_signals)' -d 'Signal to send to the jobs on timeout'
+complete -c wait -s k -l kill-after -x -d 'Send SIGKILL if jobs survive this long after the signal'
 complete -c wait -s h -l help -d 'Display help and exit'
@@ -170,11 +170,11 @@ end
 # PATH is possibly set above.
 __fish_reconstruct_path
 
-# Allow %n job expansion to be used with fg/bg/wait
-# `jobs` is the only one that natively supports job expansion
+# Allow job specs like %1 or %?name to be used with kill.
+# The job control builtins support them natively.
 function __fish_expand_pid_args
     for arg in $argv
-        if string match -qr '^%\d+$' -- $arg
+        if string match -q '%*' -- $arg
             if not jobs -p $arg
                 return 1
             end
@@ -184,17 +184,6 @@ function __fish_expand_pid_args
     end
 end
 
-for jobbltn in bg wait disown
-    function $jobbltn -V jobbltn
-        set -l args (__fish_expand_pid_args $argv)
-        and builtin $jobbltn $args
-    end
-end
-function fg
-    set -l args (__fish_expand_pid_args $argv)
-    and builtin fg $args[-1]
-end
-
 if command -q kill
     # Only define this if something to wrap exists
     # this allows a nice "command not found" error to be triggered.
@@ -0,0 +1,8 @@
+# localization: skip(private)
+function __fish_complete_job_specs --description "Print a list of job specs and their commands"
+    # Without a terminal, jobs prints no header, and the command is the last field.
+    for job in (jobs)
+        set -l fields (string split \t -- $job)
+        printf "%%%s\tJob: %s\n" $fields[1] $fields[-1]
+    end
+end
@@ -5,7 +5,8 @@
 function cd --description "Change directory"
     set -l MAX_DIR_HIST 25
 
-    if set -q argv[2]; and begin
+    # cd --jump takes any number of search terms.
+    if set -q argv[2]; and not test "$argv[1]" = --jump; and begin
             set -q argv[3]
             or not test "$argv[1]" = --
         end
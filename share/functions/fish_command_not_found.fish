@@ -13,6 +13,24 @@ end
 
 function __fish_default_command_not_found_handler
     printf (_ "fish: Unknown command: %s\n") (string escape -- $argv[1]) >&2
+
+    set -l suggestions (type --suggest -- $argv[1])[1..3]
+    set -q suggestions[1]
+    or return
+    printf (_ "Did you mean: %s?\n") (string join ', ' -- (string escape -- $suggestions)) >&2
+
+    # Offering to run the closest match is opt-in, and needs someone to answer.
+    # Abbreviations only expand in the commandline, so they cannot be run from here.
+    if test "$fish_offer_correction" = 1
+        and isatty stdin
+        and not abbr -q -- $suggestions[1]
+        set -l corrected (string join ' ' -- (string escape -- $suggestions[1] $argv[2..]))
+        read -l -n 1 -P (printf (_ "Run '%s' instead? [y/N] ") $corrected) answer
+        or return
+        if string match -qi y -- $answer
+            $suggestions[1] $argv[2..]
+        end
+    end
 end
 
 # If an old handler already exists, defer to that.
@@ -277,6 +277,7 @@ pub enum Kind<'a> {
     JobConjunction(&'a JobConjunction),
     BlockStatementHeader(&'a BlockStatementHeader),
     ForHeader(&'a ForHeader),
+    ForParallel(&'a ForParallel),
     WhileHeader(&'a WhileHeader),
     FunctionHeader(&'a FunctionHeader),
     BeginHeader(&'a BeginHeader),
@@ -318,6 +319,7 @@ pub enum KindMut<'a> {
     JobConjunction(&'a mut JobConjunction),
     BlockStatementHeader(&'a mut BlockStatementHeader),
     ForHeader(&'a mut ForHeader),
+    ForParallel(&'a mut ForParallel),
     WhileHeader(&'a mut WhileHeader),
     FunctionHeader(&'a mut FunctionHeader),
     BeginHeader(&'a mut BeginHeader),
@@ -806,6 +808,8 @@ fn can_be_parsed(pop: &mut Populator<'_>) -> bool {
 pub struct ForHeader {
     /// 'for'
     pub kw_for: KeywordFor,
+    /// '--parallel N', if any
+    pub parallel: Option<ForParallel>,
     /// var_name
     pub var_name: String_,
     /// 'in'
@@ -816,6 +820,20 @@ pub struct ForHeader {
     pub semi_nl: SemiNl,
 }
 
+/// The `--parallel N` option of a for loop, which runs up to N iterations at once.
+#[derive(Default, Debug, Node!, Acceptor!)]
+pub struct ForParallel {
+    /// '--parallel'
+    pub option: Argument,
+    /// The maximum number of concurrent iterations.
+    pub count: Argument,
+}
+impl CheckParse for ForParallel {
+    fn can_be_parsed(pop: &mut Populator<'_>) -> bool {
+        pop.peek_token(0).is_dash_prefix_string()
+    }
+}
+
 #[derive(Default, Debug, Node!, Acceptor!)]
 pub struct WhileHeader {
     /// 'while'
@@ -1216,6 +1234,7 @@ pub fn ast_kind_to_string(k: Kind<'_>) -> &'static wstr {
         Kind::JobConjunction(_) => L!("job_conjunction"),
         Kind::BlockStatementHeader(_) => L!("block_statement_header"),
         Kind::ForHeader(_) => L!("for_header"),
+        Kind::ForParallel(_) => L!("for_parallel"),
         Kind::WhileHeader(_) => L!("while_header"),
         Kind::FunctionHeader(_) => L!("function_header"),
         Kind::BeginHeader(_) => L!("begin_header"),
@@ -1767,6 +1786,7 @@ fn visit_mut<N: NodeMut>(&mut self, node: &mut N) -> VisitResult {
             KM::JobPipeline(node) => node.accept_mut(self),
             KM::JobConjunction(node) => node.accept_mut(self),
             KM::ForHeader(node) => node.accept_mut(self),
+            KM::ForParallel(node) => node.accept_mut(self),
             KM::WhileHeader(node) => node.accept_mut(self),
             KM::FunctionHeader(node) => node.accept_mut(self),
             KM::BeginHeader(node) => node.accept_mut(self),
@@ -2460,6 +2480,7 @@ fn new_decorated_statement(slf: &mut Populator<'_>) -> Statement {
             // looks like an option (starts with a dash), then parse it as a decorated statement.
             let help_only_kws = [
                 ParseKeyword::Begin,
+                ParseKeyword::For,
                 ParseKeyword::Function,
                 ParseKeyword::If,
                 ParseKeyword::Switch,
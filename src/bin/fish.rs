@@ -38,7 +38,9 @@
     eprintf,
     event::{self, Event},
     flog::{self, activate_flog_categories_by_pattern, flog, flogf, set_flog_file_fd},
-    fprintf, function, future_feature_flags as features,
+    fprintf,
+    frecency::frecency_save,
+    function, future_feature_flags as features,
     history::{self, start_private_mode},
     io::IoChain,
     locale::set_libc_locales,
@@ -56,6 +58,7 @@
         set_interactive_session,
     },
     reader::{reader_init, reader_read, term_copy_modes},
+    restricted::mark_restricted,
     signal::{signal_clear_cancel, signal_unblock_all},
     threads::{self},
     topic_monitor,
@@ -97,6 +100,8 @@ struct FishCmdOpts {
     is_interactive_session: bool,
     /// Whether to enable private mode.
     enable_private_mode: bool,
+    /// Whether to enable restricted mode after the configuration was read.
+    restricted: bool,
 }
 
 /// Return a timeval converted to milliseconds.
@@ -219,7 +224,7 @@ fn fish_parse_opt(args: &mut [WString], opts: &mut FishCmdOpts) -> ControlFlow<i
     const PRINT_DEBUG_CATEGORIES_ARG: char = 2 as char;
     const PROFILE_STARTUP_ARG: char = 3 as char;
 
-    const SHORT_OPTS: &wstr = L!("+hPilNnvc:C:p:d:f:D:o:");
+    const SHORT_OPTS: &wstr = L!("+hPilNnrvc:C:p:d:f:D:o:");
     const LONG_OPTS: &[WOption<'static>] = &[
         wopt(L!("command"), RequiredArgument, 'c'),
         wopt(L!("init-command"), RequiredArgument, 'C'),
@@ -240,6 +245,7 @@ fn fish_parse_opt(args: &mut [WString], opts: &mut FishCmdOpts) -> ControlFlow<i
         wopt(L!("profile"), RequiredArgument, 'p'),
         wopt(L!("profile-startup"), RequiredArgument, PROFILE_STARTUP_ARG),
         wopt(L!("private"), NoArgument, 'P'),
+        wopt(L!("restricted"), NoArgument, 'r'),
         wopt(L!("help"), NoArgument, 'h'),
         wopt(L!("version"), NoArgument, 'v'),
     ];
@@ -300,6 +306,7 @@ fn fish_parse_opt(args: &mut [WString], opts: &mut FishCmdOpts) -> ControlFlow<i
                 PROFILING_ACTIVE.store(true);
             }
             'P' => opts.enable_private_mode = true,
+            'r' => opts.restricted = true,
             'v' => {
                 printf!(
                     "%s",
@@ -529,6 +536,11 @@ fn throwing_main() -> i32 {
         res = run_command_list(parser, &opts.postconfig_cmds);
     }
 
+    // Like in bash, the restrictions only apply once the configuration has been read.
+    if opts.restricted {
+        mark_restricted();
+    }
+
     // Clear signals in case we were interrupted (#9024).
     signal_clear_cancel();
 
@@ -620,6 +632,7 @@ fn throwing_main() -> i32 {
     }
 
     history::save_all();
+    frecency_save();
     if opts.print_rusage_self {
         print_rusage_self();
     }
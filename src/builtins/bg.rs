@@ -2,10 +2,16 @@
 
 use std::{collections::HashSet, rc::Rc};
 
-use crate::proc::Pid;
+use crate::proc::{JobRef, Pid};
 
 use super::prelude::*;
 
+/// A job to background, given either by process ID or by job spec.
+enum JobTarget {
+    Pid(Pid),
+    Job(JobRef),
+}
+
 /// Helper function for builtin_bg().
 fn send_to_bg(
     parser: &Parser,
@@ -76,17 +82,18 @@ pub fn bg(parser: &Parser, streams: &mut IoStreams, args: &mut [&wstr]) -> Built
     }
 
     // The user specified at least one job to be backgrounded.
-    // If one argument is not a valid pid (i.e. integer >= 0), fail without backgrounding anything,
-    // but still print errors for all of them.
+    // If one argument is not a valid pid (i.e. integer >= 0) or job spec, fail without
+    // backgrounding anything, but still print errors for all of them.
     let mut retval: BuiltinResult = Ok(SUCCESS);
-    let pids: Vec<Pid> = args[opts.optind..]
+    let targets: Vec<JobTarget> = args[opts.optind..]
         .iter()
-        .filter_map(|arg| match parse_pid(streams, cmd, arg) {
-            Ok(pid) => Some(pid),
-            _ => {
-                retval = Err(STATUS_INVALID_ARGS);
-                None
-            }
+        .filter_map(|arg| {
+            let target = if is_job_spec(arg) {
+                parse_job_spec(parser, streams, cmd, arg).map(|(_pos, job)| JobTarget::Job(job))
+            } else {
+                parse_pid(streams, cmd, arg).map(JobTarget::Pid)
+            };
+            target.map_err(|err| retval = Err(err)).ok()
         })
         .collect();
 
@@ -95,12 +102,21 @@ pub fn bg(parser: &Parser, streams: &mut IoStreams, args: &mut [&wstr]) -> Built
     // Background all existing jobs that match the pids.
     // Non-existent jobs aren't an error, but information about them is useful.
     let mut seen = HashSet::new();
-    for pid in pids {
-        if let Some((job_pos, job)) = parser.job_get_with_index_from_pid(pid) {
+    for target in targets {
+        let found = match &target {
+            JobTarget::Pid(pid) => parser.job_get_with_index_from_pid(*pid),
+            // Backgrounding an earlier job may have moved this one.
+            JobTarget::Job(job) => parser
+                .jobs()
+                .iter()
+                .position(|j| Rc::ptr_eq(j, job))
+                .map(|job_pos| (job_pos, Rc::clone(job))),
+        };
+        if let Some((job_pos, job)) = found {
             if seen.insert(Rc::as_ptr(&job)) {
                 send_to_bg(parser, streams, cmd, job_pos)?;
             }
-        } else {
+        } else if let JobTarget::Pid(pid) = target {
             streams
                 .err
                 .appendln(&wgettext_fmt!(BUILTIN_ERR_COULD_NOT_FIND_JOB, cmd, pid));
@@ -4,34 +4,137 @@
 use crate::{
     env::{EnvMode, Environment},
     fds::{BEST_O_SEARCH, wopen_dir},
+    frecency::frecency_query,
     parser::ParserEnvSetMode,
     path::path_apply_cdpath,
+    restricted::{is_restricted, restriction_violated},
     wutil::{normalize_path, wperror, wreadlink},
 };
 use errno::Errno;
+use fish_wcstringutil::join_strings;
 use libc::{EACCES, ELOOP, ENOENT, ENOTDIR, EPERM};
 use nix::unistd::fchdir;
 use std::sync::Arc;
 
+const JUMP_SHORT: char = 1 as char;
+const LIST_SHORT: char = 2 as char;
+
+#[derive(Default)]
+struct Options {
+    print_help: bool,
+    jump: bool,
+    list: bool,
+}
+
+fn parse_options(
+    args: &mut [&wstr],
+    parser: &Parser,
+    streams: &mut IoStreams,
+) -> Result<(Options, usize), ErrorCode> {
+    let cmd = args[0];
+    let print_hints = false;
+
+    let shortopts: &wstr = L!("h");
+    let longopts: &[WOption] = &[
+        wopt(L!("help"), ArgType::NoArgument, 'h'),
+        wopt(L!("jump"), ArgType::NoArgument, JUMP_SHORT),
+        wopt(L!("list"), ArgType::NoArgument, LIST_SHORT),
+    ];
+
+    let mut opts = Options::default();
+    let mut w = WGetopter::new(shortopts, longopts, args);
+    while let Some(c) = w.next_opt() {
+        match c {
+            'h' => opts.print_help = true,
+            JUMP_SHORT => opts.jump = true,
+            LIST_SHORT => opts.list = true,
+            ';' => {
+                builtin_unexpected_argument(
+                    parser,
+                    streams,
+                    cmd,
+                    args[w.wopt_index - 1],
+                    print_hints,
+                );
+                return Err(STATUS_INVALID_ARGS);
+            }
+            '?' => {
+                builtin_unknown_option(parser, streams, cmd, args[w.wopt_index - 1], print_hints);
+                return Err(STATUS_INVALID_ARGS);
+            }
+            _ => panic!("unexpected retval from WGetopter"),
+        }
+    }
+
+    Ok((opts, w.wopt_index))
+}
+
 // The cd builtin. Changes the current directory to the one specified or to $HOME if none is
 // specified. The directory can be relative to any directory in the CDPATH variable.
+// With --jump, it changes to the best match from the frecency database instead.
 pub fn cd(parser: &Parser, streams: &mut IoStreams, args: &mut [&wstr]) -> BuiltinResult {
     let Some(&cmd) = args.first() else {
         return Err(STATUS_INVALID_ARGS);
     };
 
-    let opts = HelpOnlyCmdOpts::parse(args, parser, streams)?;
+    let (opts, optind) = parse_options(args, parser, streams)?;
 
     if opts.print_help {
         builtin_print_help(parser, streams, cmd);
         return Ok(SUCCESS);
     }
 
+    if opts.list && !opts.jump {
+        streams.err.appendln(&wgettext_fmt!(
+            BUILTIN_ERR_COMBO2,
+            cmd,
+            wgettext!("--list requires --jump")
+        ));
+        return Err(STATUS_INVALID_ARGS);
+    }
+
+    // Listing the jump database is harmless, and used by completions.
+    if is_restricted() && !opts.list {
+        streams
+            .err
+            .appendln(&wgettext_fmt!("%s: Not allowed in restricted mode", cmd));
+        let target = args.get(optind).copied().unwrap_or(L!(""));
+        restriction_violated(parser, L!("cd"), target);
+        return Err(STATUS_CMD_ERROR);
+    }
+
     let vars = parser.vars();
     let tmpstr;
 
-    let dir_in: &wstr = if args.len() > opts.optind {
-        args[opts.optind]
+    let dir_in: &wstr = if opts.jump {
+        // The current directory is never a useful place to jump to.
+        let pwd = vars.get(L!("PWD")).map(|v| v.as_string());
+        let terms = &args[optind..];
+        let mut candidates = frecency_query(terms)
+            .into_iter()
+            .filter(|entry| pwd.as_ref() != Some(&entry.path));
+        if opts.list {
+            for entry in candidates {
+                streams.out.appendln(&entry.path);
+            }
+            return Ok(SUCCESS);
+        }
+        match candidates.next() {
+            Some(entry) => {
+                tmpstr = entry.path;
+                &tmpstr
+            }
+            None => {
+                streams.err.append(&wgettext_fmt!(
+                    "%s: No directory matching '%s' was found in the jump database\n",
+                    cmd,
+                    join_strings(terms, ' ')
+                ));
+                return Err(STATUS_CMD_ERROR);
+            }
+        }
+    } else if args.len() > optind {
+        args[optind]
     } else {
         match vars.get_unless_empty(L!("HOME")) {
             Some(v) => {
@@ -72,12 +72,18 @@ pub fn disown(parser: &Parser, streams: &mut IoStreams, args: &mut [&wstr]) -> B
     } else {
         retval = Ok(SUCCESS);
 
-        // If one argument is not a valid pid (i.e. integer >= 0), fail without disowning anything,
+        // If one argument is not a valid pid (i.e. integer >= 0) or job spec, fail without disowning anything,
         // but still print errors for all of them.
         // Non-existent jobs aren't an error, but information about them is useful.
         let mut jobs: Vec<_> = args[opts.optind..]
             .iter()
             .filter_map(|arg| {
+                if is_job_spec(arg) {
+                    return parse_job_spec(parser, streams, cmd, arg)
+                        .map(|(_pos, job)| job)
+                        .map_err(|code| retval = Err(code))
+                        .ok();
+                }
                 let pid = match parse_pid(streams, cmd, arg) {
                     Ok(pid) => pid,
                     Err(code) => {
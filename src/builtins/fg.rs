@@ -51,7 +51,9 @@ pub fn fg(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Built
         // try to locate the job $argv[1], since we need to determine which error message to
         // emit (ambiguous job specification vs malformed job ID).
         let mut found_job = false;
-        if let Ok(pid) = parse_pid(streams, cmd, argv[optind]) {
+        if is_job_spec(argv[optind]) {
+            found_job = find_job_spec(parser, argv[optind]).is_ok();
+        } else if let Ok(pid) = parse_pid(streams, cmd, argv[optind]) {
             found_job = parser.job_get_from_pid(pid).is_some();
         }
 
@@ -68,6 +70,27 @@ pub fn fg(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Built
         builtin_print_error_trailer(parser, streams.err, cmd);
         job_pos = None;
         job = None;
+    } else if is_job_spec(argv[optind]) {
+        match parse_job_spec(parser, streams, cmd, argv[optind]) {
+            Ok((pos, j)) => {
+                job_pos = Some(pos);
+                job = if !j.wants_job_control() {
+                    streams.err.append(&wgettext_fmt!(
+                        "%s: Can't put job %d, '%s' to foreground because it is not under job control\n",
+                        cmd,
+                        j.job_id(),
+                        j.command()
+                    ));
+                    None
+                } else {
+                    Some(j)
+                };
+            }
+            Err(_err) => {
+                job_pos = None;
+                job = None;
+            }
+        }
     } else {
         match parse_pid(streams, cmd, argv[optind]) {
             Ok(pid) => {
@@ -12,6 +12,7 @@
 use crate::parse_tree::NodeRef;
 use crate::parser_keywords::parser_keywords_is_reserved;
 use crate::proc::Pid;
+use crate::restricted::{is_restricted_variable, restriction_violated};
 use crate::signal::Signal;
 use std::sync::Arc;
 
@@ -42,14 +43,24 @@ fn default() -> Self {
 // This command is atypical in using the "-" (RETURN_IN_ORDER) option for flag parsing.
 // This is needed due to the semantics of the -a/--argument-names flag.
 const SHORT_OPTIONS: &wstr = L!("-a:d:e:hj:p:s:v:w:SV:");
+const ON_TIMER_SHORT: char = 2 as char;
+const ON_TIMEOUT_SHORT: char = 3 as char;
+const ON_JOB_START_SHORT: char = 4 as char;
+const ON_JOB_STOP_SHORT: char = 5 as char;
+const ON_JOB_CONTINUE_SHORT: char = 6 as char;
 #[rustfmt::skip]
 const LONG_OPTIONS: &[WOption] = &[
     wopt(L!("description"), ArgType::RequiredArgument, 'd'),
     wopt(L!("on-signal"), ArgType::RequiredArgument, 's'),
     wopt(L!("on-job-exit"), ArgType::RequiredArgument, 'j'),
+    wopt(L!("on-job-start"), ArgType::NoArgument, ON_JOB_START_SHORT),
+    wopt(L!("on-job-stop"), ArgType::NoArgument, ON_JOB_STOP_SHORT),
+    wopt(L!("on-job-continue"), ArgType::NoArgument, ON_JOB_CONTINUE_SHORT),
     wopt(L!("on-process-exit"), ArgType::RequiredArgument, 'p'),
     wopt(L!("on-variable"), ArgType::RequiredArgument, 'v'),
     wopt(L!("on-event"), ArgType::RequiredArgument, 'e'),
+    wopt(L!("on-timer"), ArgType::RequiredArgument, ON_TIMER_SHORT),
+    wopt(L!("on-timeout"), ArgType::RequiredArgument, ON_TIMEOUT_SHORT),
     wopt(L!("wraps"), ArgType::RequiredArgument, 'w'),
     wopt(L!("help"), ArgType::NoArgument, 'h'),
     wopt(L!("argument-names"), ArgType::RequiredArgument, 'a'),
@@ -97,6 +108,16 @@ fn parse_cmd_opts(
                 ));
                 return Err(STATUS_INVALID_ARGS);
             }
+            // The function would set it when called.
+            if is_restricted_variable(varname) {
+                streams.err.appendln(&wgettext_fmt!(
+                    "%s: Cannot change the variable '%s' in restricted mode",
+                    cmd,
+                    varname
+                ));
+                restriction_violated(parser, L!("variable"), varname);
+                return Err(STATUS_INVALID_ARGS);
+            }
             Ok(())
         };
     fn add_named_argument(
@@ -153,6 +174,21 @@ fn add_named_argument(
                 let param = w.woptarg.unwrap().to_owned();
                 opts.events.push(EventDescription::Generic { param });
             }
+            ON_TIMER_SHORT | ON_TIMEOUT_SHORT => {
+                let spec = w.woptarg.unwrap();
+                let Some(interval) = parse_duration(spec).filter(|d| !d.is_zero()) else {
+                    streams
+                        .err
+                        .append(&wgettext_fmt!("%s: %s: invalid duration", cmd, spec));
+                    return Err(STATUS_INVALID_ARGS);
+                };
+                let repeat = opt == ON_TIMER_SHORT;
+                opts.events
+                    .push(EventDescription::timer(spec.to_owned(), interval, repeat));
+            }
+            ON_JOB_START_SHORT => opts.events.push(EventDescription::JobStart),
+            ON_JOB_STOP_SHORT => opts.events.push(EventDescription::JobStop),
+            ON_JOB_CONTINUE_SHORT => opts.events.push(EventDescription::JobContinue),
             'j' | 'p' => {
                 let woptarg = w.woptarg.unwrap();
                 let e: EventDescription;
@@ -0,0 +1,125 @@
+// Implementation of the hash builtin.
+
+use super::prelude::*;
+use crate::common::escape;
+use crate::path::{command_hash_add, command_hash_clear, command_hash_list, command_hash_remove};
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Options {
+    reset: bool,
+    delete: bool,
+    print_help: bool,
+}
+
+fn parse_options(
+    args: &mut [&wstr],
+    parser: &Parser,
+    streams: &mut IoStreams,
+) -> Result<(Options, usize), ErrorCode> {
+    let cmd = args[0];
+
+    const SHORT_OPTS: &wstr = L!("rdh");
+    const LONG_OPTS: &[WOption] = &[
+        wopt(L!("reset"), ArgType::NoArgument, 'r'),
+        wopt(L!("delete"), ArgType::NoArgument, 'd'),
+        wopt(L!("help"), ArgType::NoArgument, 'h'),
+    ];
+
+    let mut opts = Options::default();
+
+    let mut w = WGetopter::new(SHORT_OPTS, LONG_OPTS, args);
+    while let Some(c) = w.next_opt() {
+        match c {
+            'h' => {
+                opts.print_help = true;
+            }
+            'r' => {
+                opts.reset = true;
+            }
+            'd' => {
+                opts.delete = true;
+            }
+            ';' => {
+                builtin_unexpected_argument(parser, streams, cmd, args[w.wopt_index - 1], false);
+                return Err(STATUS_INVALID_ARGS);
+            }
+            '?' => {
+                builtin_unknown_option(parser, streams, cmd, args[w.wopt_index - 1], false);
+                return Err(STATUS_INVALID_ARGS);
+            }
+            _ => {
+                panic!("unexpected retval from WGetopter");
+            }
+        }
+    }
+
+    Ok((opts, w.wopt_index))
+}
+
+/// The hash builtin, for inspecting and changing the table of command locations in $PATH.
+pub fn hash(parser: &Parser, streams: &mut IoStreams, args: &mut [&wstr]) -> BuiltinResult {
+    let cmd = args[0];
+
+    let (opts, optind) = parse_options(args, parser, streams)?;
+
+    if opts.print_help {
+        builtin_print_help(parser, streams, cmd);
+        return Ok(SUCCESS);
+    }
+
+    if opts.reset && opts.delete {
+        streams.err.appendln(&wgettext_fmt!(
+            BUILTIN_ERR_COMBO2_EXCLUSIVE,
+            cmd,
+            "--reset",
+            "--delete"
+        ));
+        return Err(STATUS_INVALID_ARGS);
+    }
+
+    let names = &args[optind..];
+
+    if opts.reset {
+        if !names.is_empty() {
+            streams
+                .err
+                .appendln(&wgettext_fmt!(BUILTIN_ERR_ARG_COUNT1, cmd, 0, names.len()));
+            return Err(STATUS_INVALID_ARGS);
+        }
+        command_hash_clear();
+        return Ok(SUCCESS);
+    }
+
+    if opts.delete {
+        let mut retval = Ok(SUCCESS);
+        for name in names {
+            if !command_hash_remove(name) {
+                streams
+                    .err
+                    .appendln(&wgettext_fmt!("%s: %s: not found", cmd, name));
+                retval = Err(STATUS_CMD_ERROR);
+            }
+        }
+        return retval;
+    }
+
+    if names.is_empty() {
+        for (name, path) in command_hash_list(parser.vars()) {
+            streams.out.append(&escape(&name));
+            streams.out.append(' ');
+            streams.out.appendln(&escape(&path));
+        }
+        return Ok(SUCCESS);
+    }
+
+    let mut retval = Ok(SUCCESS);
+    for name in names {
+        if command_hash_add(name, parser.vars()).is_none() {
+            streams
+                .err
+                .appendln(&wgettext_fmt!("%s: %s: not found", cmd, name));
+            retval = Err(STATUS_CMD_ERROR);
+        }
+    }
+    retval
+}
@@ -3,14 +3,13 @@
 use super::prelude::*;
 use crate::common::{EscapeFlags, EscapeStringStyle, escape_string, timef};
 use crate::io::IoStreams;
-use crate::job_group::{JobId, MaybeJobId};
 use crate::localization::{wgettext, wgettext_fmt};
 use crate::parser::Parser;
-use crate::proc::{HAVE_PROC_STAT, Job, clock_ticks_to_seconds, proc_get_jiffies};
-use crate::wutil::fish_wcstoi;
+use crate::proc::{
+    HAVE_PROC_STAT, Job, Process, ProcessType, clock_ticks_to_seconds, proc_get_jiffies,
+};
 use fish_wgetopt::{ArgType, WGetopter, WOption, wopt};
 use fish_widestring::{L, WExt, WString, wstr};
-use std::num::NonZeroU32;
 
 /// Print modes for the jobs builtin.
 
@@ -21,6 +20,7 @@ enum JobsPrintMode {
     PrintCommand, // print command name of each process in job
     PrintGroup,   // print group ID of job
     PrintNothing, // print nothing (exit status only)
+    PrintJson,    // print all jobs and their processes as JSON
 }
 
 /// Calculates the cpu usage (as a fraction of 1) of the specified job.
@@ -39,6 +39,81 @@ fn cpu_use(j: &Job) -> f64 {
     u
 }
 
+/// Append `s` to `out` as a JSON string.
+fn append_json_string(out: &mut WString, s: &wstr) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if u32::from(c) < 0x20 => sprintf!(=> out, "\\u%04x", u32::from(c)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Return a JSON object describing the specified process.
+fn process_to_json(p: &Process) -> WString {
+    let mut out = WString::new();
+    match p.pid() {
+        Some(pid) => sprintf!(=> &mut out, "{\"pid\": %d, \"argv\": [", pid),
+        None => out.push_str("{\"pid\": null, \"argv\": ["),
+    }
+    for (i, arg) in p.argv().iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        append_json_string(&mut out, arg);
+    }
+    let typ = match p.typ {
+        ProcessType::External => "external",
+        ProcessType::Builtin => "builtin",
+        ProcessType::Function => "function",
+        ProcessType::BlockNode(_) => "block",
+        ProcessType::Exec => "exec",
+    };
+    sprintf!(=> &mut out, "], \"type\": \"%s\", ", typ);
+    if p.is_completed() {
+        sprintf!(=> &mut out, "\"state\": \"completed\", \"status\": %d}", p.status().status_value());
+    } else if p.is_stopped() {
+        out.push_str("\"state\": \"stopped\", \"status\": null}");
+    } else {
+        out.push_str("\"state\": \"running\", \"status\": null}");
+    }
+    out
+}
+
+/// Return a JSON object describing the specified job and its processes.
+fn job_to_json(j: &Job) -> WString {
+    let mut out = WString::new();
+    sprintf!(=> &mut out, "{\"id\": %d, \"internal_id\": %d, ", j.job_id(), j.internal_job_id);
+    match j.get_pgid() {
+        Some(pgid) => sprintf!(=> &mut out, "\"pgid\": %d, ", pgid),
+        None => out.push_str("\"pgid\": null, "),
+    }
+    sprintf!(
+        => &mut out,
+        "\"foreground\": %s, \"state\": \"%s\", \"start_time\": %.0f, \"command\": ",
+        if j.is_foreground() { "true" } else { "false" },
+        if j.is_stopped() { "stopped" } else { "running" },
+        j.start_time
+    );
+    append_json_string(&mut out, j.command());
+    out.push_str(", \"processes\": [");
+    for (i, p) in j.processes().iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        out.push_utfstr(&process_to_json(p));
+    }
+    out.push_str("]}");
+    out
+}
+
 /// Print information about the specified job.
 fn builtin_jobs_print(j: &Job, mode: JobsPrintMode, header: bool, streams: &mut IoStreams) {
     let pgid = match j.get_pgid() {
@@ -49,6 +124,12 @@ fn builtin_jobs_print(j: &Job, mode: JobsPrintMode, header: bool, streams: &mut
     let mut out = WString::new();
     match mode {
         JobsPrintMode::PrintNothing => (),
+        JobsPrintMode::PrintJson => {
+            // Here the header is the start of the array. The caller closes it.
+            out.push_str(if header { "[" } else { ", " });
+            out += &job_to_json(j)[..];
+            streams.out.append(&out);
+        }
         JobsPrintMode::Default => {
             if header {
                 // Print table header before first job.
@@ -118,11 +199,13 @@ fn builtin_jobs_print(j: &Job, mode: JobsPrintMode, header: bool, streams: &mut
     }
 }
 
+const JSON_SHORT: char = 1 as char;
 const SHORT_OPTIONS: &wstr = L!("cghlpq");
 const LONG_OPTIONS: &[WOption] = &[
     wopt(L!("command"), ArgType::NoArgument, 'c'),
     wopt(L!("group"), ArgType::NoArgument, 'g'),
     wopt(L!("help"), ArgType::NoArgument, 'h'),
+    wopt(L!("json"), ArgType::NoArgument, JSON_SHORT),
     wopt(L!("last"), ArgType::NoArgument, 'l'),
     wopt(L!("pid"), ArgType::NoArgument, 'p'),
     wopt(L!("quiet"), ArgType::NoArgument, 'q'),
@@ -156,6 +239,9 @@ pub fn jobs(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
             'g' => {
                 mode = JobsPrintMode::PrintGroup;
             }
+            JSON_SHORT => {
+                mode = JobsPrintMode::PrintJson;
+            }
             'l' => {
                 print_last = true;
             }
@@ -179,48 +265,50 @@ pub fn jobs(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
         }
     }
 
+    let json = mode == JobsPrintMode::PrintJson;
     if print_last {
         // Ignore unconstructed jobs, i.e. ourself.
         for j in &parser.jobs()[..] {
             if j.is_visible() {
-                builtin_jobs_print(j, mode, !streams.out_is_redirected, streams);
+                builtin_jobs_print(j, mode, json || !streams.out_is_redirected, streams);
+                if json {
+                    streams.out.append(L!("]\n"));
+                }
                 return Ok(SUCCESS);
             }
         }
+        if json {
+            streams.out.append(L!("[]\n"));
+        }
         return Err(STATUS_CMD_ERROR);
     }
 
     if w.wopt_index < argc {
+        let mut status = Ok(SUCCESS);
         for arg in &w.argv[w.wopt_index..] {
-            let j;
-            if arg.char_at(0) == '%' {
-                match fish_wcstoi(&arg[1..]).ok().filter(|&job_id| job_id >= 0) {
-                    None => {
-                        streams.err.appendln(&wgettext_fmt!(
-                            "%s: '%s' is not a valid job ID",
-                            cmd,
-                            arg
-                        ));
-                        return Err(STATUS_INVALID_ARGS);
-                    }
-                    Some(job_id) => {
-                        let job_id = if job_id == 0 {
-                            JobId::NONE
-                        } else {
-                            let job_id = u32::try_from(job_id).unwrap();
-                            let job_id = NonZeroU32::try_from(job_id).unwrap();
-                            MaybeJobId(Some(JobId::new(job_id)))
-                        };
-                        j = parser.job_with_id(job_id);
+            let j = if is_job_spec(arg) {
+                match find_job_spec(parser, arg) {
+                    Ok((_pos, j)) => Some(j),
+                    Err(err) => {
+                        if mode != JobsPrintMode::PrintNothing {
+                            streams.err.appendln(&err.describe(cmd, arg));
+                        }
+                        status = Err(err.status());
+                        break;
                     }
                 }
             } else {
-                let pid = parse_pid(streams, cmd, arg)?;
-                j = parser.job_get_from_pid(pid);
-            }
+                match parse_pid(streams, cmd, arg) {
+                    Ok(pid) => parser.job_get_from_pid(pid),
+                    Err(err) => {
+                        status = Err(err);
+                        break;
+                    }
+                }
+            };
 
             if let Some(j) = j.filter(|j| !j.is_completed() && j.is_constructed()) {
-                builtin_jobs_print(&j, mode, false, streams);
+                builtin_jobs_print(&j, mode, json && !found, streams);
                 found = true;
             } else {
                 if mode != JobsPrintMode::PrintNothing {
@@ -228,22 +316,39 @@ pub fn jobs(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
                         .err
                         .appendln(&wgettext_fmt!("%s: No suitable job: %s", cmd, arg));
                 }
-                return Err(STATUS_CMD_ERROR);
+                status = Err(STATUS_CMD_ERROR);
+                break;
             }
         }
+        if status.is_err() {
+            // Close the array if we already started printing it.
+            if json && found {
+                streams.out.append(L!("]\n"));
+            }
+            return status;
+        }
     } else {
         for j in &parser.jobs()[..] {
             // Ignore unconstructed jobs, i.e. ourself.
             if j.is_visible() {
-                builtin_jobs_print(j, mode, !found && !streams.out_is_redirected, streams);
+                let header = !found && (json || !streams.out_is_redirected);
+                builtin_jobs_print(j, mode, header, streams);
                 found = true;
             }
         }
     }
 
+    if json {
+        streams
+            .out
+            .append(if found { L!("]\n") } else { L!("[]\n") });
+    }
+
     if !found {
         // Do not babble if not interactive.
-        if !streams.out_is_redirected && mode != JobsPrintMode::PrintNothing {
+        if !streams.out_is_redirected
+            && !matches!(mode, JobsPrintMode::PrintNothing | JobsPrintMode::PrintJson)
+        {
             streams
                 .out
                 .appendln(&wgettext_fmt!("%s: There are no jobs", argv[0]));
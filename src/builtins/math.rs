@@ -1,8 +1,13 @@
+use std::sync::Mutex;
+
 use fish_widestring::L;
 use num_traits::pow;
 
 use super::prelude::*;
-use crate::tinyexpr::te_interp;
+use crate::tinyexpr::{
+    Error, UserFunction, is_reserved_name, is_valid_name, parse_definition, te_interp_integer,
+    te_interp_with,
+};
 
 /// The maximum number of points after the decimal that we'll print.
 const DEFAULT_SCALE: usize = 6;
@@ -13,6 +18,9 @@
 /// i.e. this is the first value such that x + 1 == x (or == x + 2, depending on rounding mode).
 const MAX_CONTIGUOUS_INTEGER: f64 = (1_u64 << f64::MANTISSA_DIGITS) as f64;
 
+/// The functions defined with `math --define`. They live for the rest of the session.
+static MATH_FUNCTIONS: Mutex<Vec<UserFunction>> = Mutex::new(Vec::new());
+
 enum ScaleMode {
     Truncate,
     Round,
@@ -26,6 +34,12 @@ struct Options {
     scale: usize,
     base: usize,
     scale_mode: ScaleMode,
+    /// Whether to compute with integers instead of floating point numbers.
+    integer: bool,
+    /// The `NAME=VALUE` arguments of `--var`.
+    variables: Vec<WString>,
+    /// The arguments of `--define`.
+    definitions: Vec<WString>,
 }
 
 fn parse_cmd_opts(
@@ -38,12 +52,16 @@ fn parse_cmd_opts(
 
     // This command is atypical in using the "+" (REQUIRE_ORDER) option for flag parsing.
     // This is needed because of the minus, `-`, operator in math expressions.
-    const SHORT_OPTS: &wstr = L!("+hs:b:m:");
+    const SHORT_OPTS: &wstr = L!("+hs:b:m:v:i");
+    const DEFINE_ARG: char = 1 as char;
     const LONG_OPTS: &[WOption] = &[
         wopt(L!("scale"), ArgType::RequiredArgument, 's'),
         wopt(L!("base"), ArgType::RequiredArgument, 'b'),
         wopt(L!("help"), ArgType::NoArgument, 'h'),
         wopt(L!("scale-mode"), ArgType::RequiredArgument, 'm'),
+        wopt(L!("var"), ArgType::RequiredArgument, 'v'),
+        wopt(L!("integer"), ArgType::NoArgument, 'i'),
+        wopt(L!("define"), ArgType::RequiredArgument, DEFINE_ARG),
     ];
 
     let mut opts = Options {
@@ -51,6 +69,9 @@ fn parse_cmd_opts(
         scale: DEFAULT_SCALE,
         base: 10,
         scale_mode: DEFAULT_SCALE_MODE,
+        integer: false,
+        variables: vec![],
+        definitions: vec![],
     };
 
     let mut have_scale = false;
@@ -99,9 +120,11 @@ fn parse_cmd_opts(
                     opts.base = 16;
                 } else if optarg == "octal" {
                     opts.base = 8;
+                } else if optarg == "binary" {
+                    opts.base = 2;
                 } else {
                     let base = fish_wcstoi(optarg).unwrap_or(-1);
-                    if base != 8 && base != 16 {
+                    if base != 2 && base != 8 && base != 16 {
                         streams.err.append(&wgettext_fmt!(
                             "%s: %s: invalid base value\n",
                             cmd,
@@ -109,10 +132,19 @@ fn parse_cmd_opts(
                         ));
                         return Err(STATUS_INVALID_ARGS);
                     }
-                    // We know the value is 8 or 16.
+                    // We know the value is 2, 8 or 16.
                     opts.base = base as usize;
                 }
             }
+            'i' => {
+                opts.integer = true;
+            }
+            'v' => {
+                opts.variables.push(w.woptarg.unwrap().to_owned());
+            }
+            DEFINE_ARG => {
+                opts.definitions.push(w.woptarg.unwrap().to_owned());
+            }
             'h' => {
                 opts.print_help = true;
             }
@@ -151,6 +183,15 @@ fn parse_cmd_opts(
         return Err(STATUS_INVALID_ARGS);
     }
 
+    if have_scale && opts.scale != 0 && opts.integer {
+        streams.err.appendln(&wgettext_fmt!(
+            BUILTIN_ERR_COMBO2,
+            cmd,
+            "non-zero scale value not valid with --integer"
+        ));
+        return Err(STATUS_INVALID_ARGS);
+    }
+
     Ok((opts, w.wopt_index))
 }
 
@@ -160,6 +201,10 @@ fn format_double(mut v: f64, opts: &Options) -> WString {
         v = v.trunc();
         let mneg = if v.is_sign_negative() { "-" } else { "" };
         return sprintf!("%s0x%x", mneg, v.abs() as u64);
+    } else if opts.base == 2 {
+        v = v.trunc();
+        let mneg = if v.is_sign_negative() { "-" } else { "" };
+        return WString::from_str(&format!("{mneg}0b{:b}", v.abs() as u64));
     } else if opts.base == 8 {
         v = v.trunc();
         if v == 0.0 {
@@ -215,13 +260,114 @@ fn format_double(mut v: f64, opts: &Options) -> WString {
     ret
 }
 
+/// Return `n` formatted in the base given by `opts`, for `--integer`.
+fn format_integer(n: i128, opts: &Options) -> WString {
+    let mneg = if n < 0 { "-" } else { "" };
+    let n = n.unsigned_abs();
+    let s = match opts.base {
+        16 => format!("{mneg}0x{n:x}"),
+        8 if n == 0 => "0".to_owned(),
+        8 => format!("{mneg}0{n:o}"),
+        2 => format!("{mneg}0b{n:b}"),
+        _ => format!("{mneg}{n}"),
+    };
+    WString::from_str(&s)
+}
+
+/// Print the error `err` in `expression` with a caret pointing at it.
+fn print_error(cmd: &wstr, streams: &mut IoStreams, expression: &wstr, err: &Error) {
+    streams.err.append(&sprintf!(
+        L!("%s: Error: %s\n"),
+        cmd,
+        err.kind.describe_wstr()
+    ));
+    streams.err.append(&sprintf!("'%s'\n", expression));
+    let padding = WString::from_chars(vec![' '; err.position + 1]);
+    if err.len >= 2 {
+        let tildes = WString::from_chars(vec!['~'; err.len - 2]);
+        streams.err.append(&sprintf!("%s^%s^\n", padding, tildes));
+    } else {
+        streams.err.append(&sprintf!("%s^\n", padding));
+    }
+}
+
+/// Bind the `NAME=VALUE` arguments of `--var`. Each value is itself an expression, evaluated with
+/// `eval`, which may use the variables bound before it.
+fn bind_variables<T>(
+    cmd: &wstr,
+    streams: &mut IoStreams,
+    opts: &Options,
+    functions: &[UserFunction],
+    mut eval: impl FnMut(&wstr, &mut Vec<(WString, T)>) -> Result<T, Error>,
+) -> Result<Vec<(WString, T)>, ErrorCode> {
+    let mut variables: Vec<(WString, T)> = vec![];
+    for arg in &opts.variables {
+        let Some(eq) = arg.chars().position(|c| c == '=') else {
+            streams.err.append(&wgettext_fmt!(
+                "%s: %s: invalid variable, expected NAME=VALUE\n",
+                cmd,
+                arg
+            ));
+            return Err(STATUS_INVALID_ARGS);
+        };
+        let (name, value) = (&arg[..eq], &arg[eq + 1..]);
+        if !is_valid_name(name)
+            || is_reserved_name(name)
+            || functions.iter().any(|f| f.name == name)
+        {
+            streams
+                .err
+                .append(&wgettext_fmt!("%s: %s: invalid variable name\n", cmd, name));
+            return Err(STATUS_INVALID_ARGS);
+        }
+        let value = match eval(value, &mut variables) {
+            Ok(value) => value,
+            Err(err) => {
+                print_error(cmd, streams, value, &err);
+                return Err(STATUS_CMD_ERROR);
+            }
+        };
+        if let Some(var) = variables.iter_mut().find(|(var, _)| var == name) {
+            var.1 = value;
+        } else {
+            variables.push((name.to_owned(), value));
+        }
+    }
+    Ok(variables)
+}
+
+/// Evaluate `expression` with integers, for `--integer`.
+fn evaluate_integer_expression(
+    cmd: &wstr,
+    streams: &mut IoStreams,
+    opts: &Options,
+    expression: &wstr,
+    variables: &mut Vec<(WString, i128)>,
+    functions: &[UserFunction],
+) -> BuiltinResult {
+    match te_interp_integer(expression, variables, functions) {
+        Ok(n) => {
+            let mut s = format_integer(n, opts);
+            s.push('\n');
+            streams.out.append(&s);
+            Ok(SUCCESS)
+        }
+        Err(err) => {
+            print_error(cmd, streams, expression, &err);
+            Err(STATUS_CMD_ERROR)
+        }
+    }
+}
+
 fn evaluate_expression(
     cmd: &wstr,
     streams: &mut IoStreams,
     opts: &Options,
     expression: &wstr,
+    variables: &mut Vec<(WString, f64)>,
+    functions: &[UserFunction],
 ) -> BuiltinResult {
-    let ret = te_interp(expression);
+    let ret = te_interp_with(expression, variables, functions);
 
     match ret {
         Ok(n) => {
@@ -251,20 +397,7 @@ fn evaluate_expression(
             Err(STATUS_CMD_ERROR)
         }
         Err(err) => {
-            streams.err.append(&sprintf!(
-                L!("%s: Error: %s\n"),
-                cmd,
-                err.kind.describe_wstr()
-            ));
-            streams.err.append(&sprintf!("'%s'\n", expression));
-            let padding = WString::from_chars(vec![' '; err.position + 1]);
-            if err.len >= 2 {
-                let tildes = WString::from_chars(vec!['~'; err.len - 2]);
-                streams.err.append(&sprintf!("%s^%s^\n", padding, tildes));
-            } else {
-                streams.err.append(&sprintf!("%s^\n", padding));
-            }
-
+            print_error(cmd, streams, expression, &err);
             Err(STATUS_CMD_ERROR)
         }
     }
@@ -284,6 +417,26 @@ pub fn math(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
         return Ok(SUCCESS);
     }
 
+    let functions = {
+        let mut functions = MATH_FUNCTIONS.lock().unwrap();
+        for definition in &opts.definitions {
+            match parse_definition(definition, &functions) {
+                Ok(function) => {
+                    if let Some(old) = functions.iter_mut().find(|f| f.name == function.name) {
+                        *old = function;
+                    } else {
+                        functions.push(function);
+                    }
+                }
+                Err(err) => {
+                    print_error(cmd, streams, definition, &err);
+                    return Err(STATUS_CMD_ERROR);
+                }
+            }
+        }
+        functions.clone()
+    };
+
     let mut expression = WString::new();
     for InputValue { arg, .. } in Arguments::new(argv, &mut optind, streams, MATH_CHUNK_SIZE) {
         if !expression.is_empty() {
@@ -293,11 +446,32 @@ pub fn math(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
     }
 
     if expression.is_empty() {
+        if !opts.definitions.is_empty() {
+            // Only defining functions is fine.
+            return Ok(SUCCESS);
+        }
         streams
             .err
             .appendln(&wgettext_fmt!(BUILTIN_ERR_MIN_ARG_COUNT1, cmd, 1, 0));
         return Err(STATUS_CMD_ERROR);
     }
 
-    evaluate_expression(cmd, streams, &opts, &expression)
+    if opts.integer {
+        let mut variables = bind_variables(cmd, streams, &opts, &functions, |value, variables| {
+            te_interp_integer(value, variables, &functions)
+        })?;
+        return evaluate_integer_expression(
+            cmd,
+            streams,
+            &opts,
+            &expression,
+            &mut variables,
+            &functions,
+        );
+    }
+
+    let mut variables = bind_variables(cmd, streams, &opts, &functions, |value, variables| {
+        te_interp_with(value, variables, &functions)
+    })?;
+    evaluate_expression(cmd, streams, &opts, &expression, &mut variables, &functions)
 }
@@ -27,6 +27,7 @@
 pub mod function;
 pub mod functions;
 pub mod r#gettext;
+pub mod hash;
 pub mod history;
 pub mod jobs;
 pub mod math;
@@ -43,6 +44,7 @@
 pub mod status;
 pub mod string;
 pub mod test;
+pub mod time;
 pub mod r#true;
 pub mod r#type;
 pub mod ulimit;
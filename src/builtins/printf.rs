@@ -40,6 +40,8 @@
 //
 // %b = print an argument string, interpreting backslash escapes,
 //   except that octal escapes are of the form \0 or \0ooo.
+// %(fmt)T = print an argument in seconds since the epoch as a time, using strftime.
+// %(fmt)D = print an argument in seconds as a duration.
 //
 // The `format' argument is re-used as many times as necessary
 // to convert all of the given arguments.
@@ -52,12 +54,14 @@
 use crate::locale::{Locale, get_numeric_locale};
 use crate::wutil::{
     errors::Error,
+    timefmt::wstrftime,
     wcstod::wcstod,
     wcstoi::{Options as WcstoiOpts, wcstoi_partial},
     wstr_offset_in,
 };
 use fish_printf::{ToArg, sprintf_locale};
 use fish_widestring::{decode_byte_from_char, encode_byte_to_char};
+use std::time::SystemTime;
 
 /// Return true if `c` is an octal digit.
 fn is_octal_digit(c: char) -> bool {
@@ -193,6 +197,124 @@ fn string_to_scalar_type<T: RawStringToScalarType>(
     }
 }
 
+/// Format a duration of `seconds` according to `format`. `%d`, `%H`, `%M` and `%S` are the days,
+/// hours, minutes and seconds, and `%s` is the total number of seconds. The largest unit used
+/// holds the rest of the duration, so `%H:%M` formats three days as `72:00`.
+/// Return the offending character if the format has an unknown directive.
+fn format_duration(format: &wstr, seconds: i64) -> Result<WString, char> {
+    const UNITS: [(char, u64, u64); 4] = [
+        ('d', 86400, 0),
+        ('H', 3600, 24),
+        ('M', 60, 60),
+        ('S', 1, 60),
+    ];
+    let chars = format.as_char_slice();
+    let largest = UNITS
+        .iter()
+        .position(|&(c, _, _)| chars.windows(2).any(|w| w == ['%', c]))
+        .unwrap_or(0);
+
+    let total = seconds.unsigned_abs();
+    let mut result = WString::new();
+    if seconds < 0 {
+        result.push('-');
+    }
+    let mut iter = chars.iter();
+    while let Some(&c) = iter.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        let Some(&directive) = iter.next() else {
+            return Err('%');
+        };
+        match directive {
+            '%' => result.push('%'),
+            's' => result.push_str(&total.to_string()),
+            _ => {
+                let Some(unit) = UNITS.iter().position(|&(c, _, _)| c == directive) else {
+                    return Err(directive);
+                };
+                let (_, size, modulus) = UNITS[unit];
+                let mut value = total / size;
+                if unit > largest {
+                    value %= modulus;
+                }
+                // Days aren't padded, as they aren't limited by a larger unit.
+                if directive == 'd' {
+                    result.push_str(&value.to_string());
+                } else {
+                    result.push_str(&format!("{value:02}"));
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// The arguments for one pass over the format string. Directives take these in order, unless
+/// they select one by position, like `%2$s` or `%*1$d`.
+struct FormatArgs<'a, 'b> {
+    argv: &'a [&'b wstr],
+    /// The index of the next argument to take in order.
+    next: usize,
+    /// Whether arguments are selected by position, or None if no argument was taken yet.
+    positional: Option<bool>,
+    /// The number of arguments up to the last one that was taken.
+    used: usize,
+}
+
+impl<'a, 'b> FormatArgs<'a, 'b> {
+    fn new(argv: &'a [&'b wstr]) -> Self {
+        Self {
+            argv,
+            next: 0,
+            positional: None,
+            used: 0,
+        }
+    }
+
+    /// Take the argument at `position`, starting from 1, or the next one if None.
+    /// Return None if there is no such argument, or Err if this mixes the two ways.
+    fn take(&mut self, position: Option<usize>) -> Result<Option<&'b wstr>, ()> {
+        let positional = position.is_some();
+        if *self.positional.get_or_insert(positional) != positional {
+            return Err(());
+        }
+        let index = position.map_or(self.next, |p| p - 1);
+        if !positional {
+            self.next += 1;
+        }
+        let argument = self.argv.get(index).copied();
+        if argument.is_some() {
+            self.used = self.used.max(index + 1);
+        }
+        Ok(argument)
+    }
+}
+
+/// If `f` starts with an argument position like `2$`, skip it and return the position.
+/// Positions start at 1, so a leading 0 is the flag instead.
+fn take_position(f: &mut &wstr) -> Option<usize> {
+    let digits = f.chars().take_while(|&c| iswdigit(c)).count();
+    if digits == 0 || f.char_at(0) == '0' || f.char_at(digits) != '$' {
+        return None;
+    }
+    // A position too large to represent can't refer to an argument anyway.
+    let position =
+        fish_wcstoul(&f[..digits]).map_or(usize::MAX, |p| usize::try_from(p).unwrap_or(usize::MAX));
+    *f = &f[digits + 1..];
+    Some(position)
+}
+
+/// Return the text of the directive starting at `directive_start`, up to and including the
+/// character at `f`, for error messages.
+fn directive_text<'a>(directive_start: &'a wstr, f: &wstr) -> &'a wstr {
+    &directive_start[..directive_start
+        .len()
+        .min(wstr_offset_in(f, directive_start) + 1)]
+}
+
 /// For each character in str, set the corresponding boolean in the array to the given flag.
 fn modify_allowed_format_specifiers(ok: &mut [bool; 256], str: &str, flag: bool) {
     for c in str.chars() {
@@ -386,18 +508,81 @@ macro_rules! append_output_fmt {
         }
     }
 
+    /// Evaluate a `%(format)T` or `%(format)D` directive. `conversion` is 'T' to print `argument`,
+    /// in seconds since the epoch, as a time using strftime, or 'D' to print it, in seconds, as a
+    /// duration. The result is then padded and truncated like for %s.
+    fn print_time_directive(
+        &mut self,
+        spec: &wstr,
+        conversion: char,
+        format: &wstr,
+        field_width: Option<i64>,
+        precision: Option<i64>,
+        argument: &wstr,
+    ) {
+        let formatted = if conversion == 'T' {
+            // Like in bash, -1 or a missing argument is the current time.
+            let mut time = -1;
+            if !argument.is_empty() {
+                time = string_to_scalar_type(argument, self);
+            }
+            if time == -1 {
+                time = SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs() as i64);
+            }
+            let format = if format.is_empty() { L!("%X") } else { format };
+            match wstrftime(format, time, false) {
+                Some(formatted) => formatted,
+                None => {
+                    self.fatal_error(wgettext_fmt!("%s: Time out of range", argument));
+                    return;
+                }
+            }
+        } else {
+            let seconds = string_to_scalar_type(argument, self);
+            let format = if format.is_empty() {
+                L!("%H:%M:%S")
+            } else {
+                format
+            };
+            match format_duration(format, seconds) {
+                Ok(formatted) => formatted,
+                Err(c) => {
+                    self.fatal_error(wgettext_fmt!(
+                        "%s: invalid duration format (unknown directive '%%%c')",
+                        format,
+                        c
+                    ));
+                    return;
+                }
+            }
+        };
+        self.print_directive(spec, 's', field_width, precision, &formatted);
+    }
+
     /// Print the text in `format`, using `argv` for arguments to any `%' directives.
     /// Return the number of elements of `argv` used.
-    fn print_formatted(&mut self, format: &wstr, mut argv: &[&wstr]) -> usize {
-        let mut argc = argv.len();
-        let save_argc = argc; /* Preserve original value.  */
+    fn print_formatted(&mut self, format: &wstr, argv: &[&wstr]) -> usize {
+        let mut args = FormatArgs::new(argv);
         let mut f: &wstr; /* Pointer into `format'.  */
         let mut directive_start: &wstr; /* Start of % directive.  */
-        let mut directive_length: usize; /* Length of % directive.  */
+        let mut spec: WString; /* The % directive, without any argument positions.  */
         let mut field_width: Option<i64>; /* Arg to first '*'.  */
         let mut precision: Option<i64>; /* Arg to second '*'.  */
         let mut ok = [false; 256]; /* ok['x'] is true if %x is allowed.  */
 
+        // Report a directive that mixes numbered and unnumbered arguments.
+        macro_rules! mixed_arguments {
+            () => {{
+                self.fatal_error(wgettext_fmt!(
+                    "%s: numbered and unnumbered arguments can't be mixed",
+                    directive_text(directive_start, f)
+                ));
+                return 0;
+            }};
+        }
+
         // N.B. this was originally written as a loop like so:
         //    for (f = format; *f != L'\0'; ++f) {
         // so we emulate that.
@@ -416,20 +601,22 @@ fn print_formatted(&mut self, format: &wstr, mut argv: &[&wstr]) -> usize {
                 '%' => {
                     directive_start = f;
                     f = &f[1..];
-                    directive_length = 1;
+                    spec = L!("%").to_owned();
                     field_width = None;
                     precision = None;
                     if f.char_at(0) == '%' {
                         self.append_output('%');
                         continue;
                     }
+                    // The argument may be given by position, like %2$s.
+                    let position = take_position(&mut f);
                     if f.char_at(0) == 'b' {
                         // FIXME: Field width and precision are not supported for %b, even though POSIX
                         // requires it.
-                        if argc > 0 {
-                            self.print_esc_string(argv[0]);
-                            argv = &argv[1..];
-                            argc -= 1;
+                        match args.take(position) {
+                            Ok(Some(argument)) => self.print_esc_string(argument),
+                            Ok(None) => (),
+                            Err(()) => mixed_arguments!(),
                         }
                         continue;
                     }
@@ -459,96 +646,119 @@ fn print_formatted(&mut self, format: &wstr, mut argv: &[&wstr]) -> usize {
                             }
                         }
                         if continue_looking_for_flags {
+                            spec.push(f.char_at(0));
                             f = &f[1..];
-                            directive_length += 1;
                         }
                     }
 
                     if f.char_at(0) == '*' {
+                        spec.push('*');
                         f = &f[1..];
-                        directive_length += 1;
-                        if argc > 0 {
-                            let width: i64 = string_to_scalar_type(argv[0], self);
-                            if (c_int::MIN as i64) <= width && width <= (c_int::MAX as i64) {
-                                field_width = Some(width);
-                            } else {
-                                self.fatal_error(wgettext_fmt!("invalid field width: %s", argv[0]));
+                        let width_position = take_position(&mut f);
+                        match args.take(width_position) {
+                            Ok(Some(argument)) => {
+                                let width: i64 = string_to_scalar_type(argument, self);
+                                if (c_int::MIN as i64) <= width && width <= (c_int::MAX as i64) {
+                                    field_width = Some(width);
+                                } else {
+                                    self.fatal_error(wgettext_fmt!(
+                                        "invalid field width: %s",
+                                        argument
+                                    ));
+                                }
                             }
-                            argv = &argv[1..];
-                            argc -= 1;
-                        } else {
-                            field_width = Some(0);
+                            Ok(None) => field_width = Some(0),
+                            Err(()) => mixed_arguments!(),
                         }
                     } else {
                         while iswdigit(f.char_at(0)) {
+                            spec.push(f.char_at(0));
                             f = &f[1..];
-                            directive_length += 1;
                         }
                     }
 
                     if f.char_at(0) == '.' {
+                        spec.push('.');
                         f = &f[1..];
-                        directive_length += 1;
                         modify_allowed_format_specifiers(&mut ok, "c", false);
                         if f.char_at(0) == '*' {
+                            spec.push('*');
                             f = &f[1..];
-                            directive_length += 1;
-                            if argc > 0 {
-                                let prec: i64 = string_to_scalar_type(argv[0], self);
-                                if prec < 0 {
-                                    // A negative precision is taken as if the precision were omitted,
-                                    // so -1 is safe here even if prec < INT_MIN.
-                                    precision = Some(-1);
-                                } else if (c_int::MAX as i64) < prec {
-                                    self.fatal_error(wgettext_fmt!(
-                                        "invalid precision: %s",
-                                        argv[0]
-                                    ));
-                                } else {
-                                    precision = Some(prec);
+                            let precision_position = take_position(&mut f);
+                            match args.take(precision_position) {
+                                Ok(Some(argument)) => {
+                                    let prec: i64 = string_to_scalar_type(argument, self);
+                                    if prec < 0 {
+                                        // A negative precision is taken as if the precision were omitted,
+                                        // so -1 is safe here even if prec < INT_MIN.
+                                        precision = Some(-1);
+                                    } else if (c_int::MAX as i64) < prec {
+                                        self.fatal_error(wgettext_fmt!(
+                                            "invalid precision: %s",
+                                            argument
+                                        ));
+                                    } else {
+                                        precision = Some(prec);
+                                    }
                                 }
-                                argv = &argv[1..];
-                                argc -= 1;
-                            } else {
-                                precision = Some(0);
+                                Ok(None) => precision = Some(0),
+                                Err(()) => mixed_arguments!(),
                             }
                         } else {
                             while iswdigit(f.char_at(0)) {
+                                spec.push(f.char_at(0));
                                 f = &f[1..];
-                                directive_length += 1;
                             }
                         }
                     }
 
+                    // A time or duration directive like %(%H:%M)T has its format in parentheses.
+                    let mut time_format = None;
+                    if f.char_at(0) == '(' {
+                        let Some(close) = f.find_char(')') else {
+                            self.fatal_error(wgettext_fmt!(
+                                "%s: missing closing parenthesis",
+                                directive_start
+                            ));
+                            return 0;
+                        };
+                        time_format = Some(&f[1..close]);
+                        f = &f[close + 1..];
+                    }
+
                     while matches!(f.char_at(0), 'l' | 'L' | 'h' | 'j' | 't' | 'z') {
                         f = &f[1..];
                     }
 
                     let conversion = f.char_at(0);
-                    if (conversion as usize) > 0xFF || !ok[conversion as usize] {
-                        let directive = &directive_start[0..directive_start
-                            .len()
-                            .min(wstr_offset_in(f, directive_start) + 1)];
+                    let valid = match time_format {
+                        Some(_) => matches!(conversion, 'T' | 'D') && ok['s' as usize],
+                        None => (conversion as usize) <= 0xFF && ok[conversion as usize],
+                    };
+                    if !valid {
                         self.fatal_error(wgettext_fmt!(
                             "%s: invalid conversion specification",
-                            directive
+                            directive_text(directive_start, f)
                         ));
                         return 0;
                     }
 
-                    let mut argument = L!("");
-                    if argc > 0 {
-                        argument = argv[0];
-                        argv = &argv[1..];
-                        argc -= 1;
+                    let argument = match args.take(position) {
+                        Ok(argument) => argument.unwrap_or(L!("")),
+                        Err(()) => mixed_arguments!(),
+                    };
+                    if let Some(time_format) = time_format {
+                        self.print_time_directive(
+                            &spec,
+                            conversion,
+                            time_format,
+                            field_width,
+                            precision,
+                            argument,
+                        );
+                    } else {
+                        self.print_directive(&spec, conversion, field_width, precision, argument);
                     }
-                    self.print_directive(
-                        &directive_start[..directive_length],
-                        f.char_at(0),
-                        field_width,
-                        precision,
-                        argument,
-                    );
                 }
                 '\\' => {
                     let consumed_minus_1 = self.print_esc(f, false);
@@ -560,7 +770,7 @@ fn print_formatted(&mut self, format: &wstr, mut argv: &[&wstr]) -> usize {
                 }
             }
         }
-        save_argc - argc
+        args.used
     }
 
     fn nonfatal_error<Str: AsRef<wstr>>(&mut self, errstr: Str) {
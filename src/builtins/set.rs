@@ -9,6 +9,8 @@
 use crate::env::EnvStackSetResult;
 use crate::env::EnvVarFlags;
 use crate::env::INHERITED_VARS;
+use crate::env::REDACTED;
+use crate::env::map_key_len;
 use crate::event;
 use crate::event::Event;
 use crate::expand::expand_escape_string;
@@ -17,6 +19,7 @@
 use crate::history::history_session_id;
 use crate::parse_execution::varname_error;
 use crate::parser::ParserEnvSetMode;
+use crate::restricted::{is_restricted_variable, restriction_violated};
 use crate::{
     env::{EnvMode, EnvVar, Environment},
     wutil::wcstoi::wcstoi_partial,
@@ -29,6 +32,9 @@
 
     UVAR_ERR
     "%s: successfully set universal '%s'; but a global by that name shadows it\n"
+
+    NOT_A_MAP
+    "%s: %s: not a map\n"
 );
 
 #[derive(Debug, Clone)]
@@ -51,6 +57,9 @@ struct Options {
     prepend: bool,
     preserve_failure_exit_status: bool,
     no_event: bool,
+    map: bool,
+    keys: bool,
+    secret: bool,
 }
 
 impl Default for Options {
@@ -74,6 +83,9 @@ fn default() -> Self {
             prepend: false,
             preserve_failure_exit_status: true,
             no_event: false,
+            map: false,
+            keys: false,
+            secret: false,
         }
     }
 }
@@ -90,6 +102,8 @@ fn env_mode(&self) -> EnvMode {
             (self.universal, EnvMode::UNIVERSAL),
             (self.pathvar, EnvMode::PATHVAR),
             (self.unpathvar, EnvMode::UNPATHVAR),
+            (self.map, EnvMode::MAP),
+            (self.secret, EnvMode::SECRET),
         ] {
             if is_mode {
                 scope |= mode;
@@ -108,6 +122,9 @@ fn parse(
         const PATH_ARG: char = 1 as char;
         const UNPATH_ARG: char = 2 as char;
         const NO_EVENT_ARG: char = 3 as char;
+        const MAP_ARG: char = 4 as char;
+        const KEYS_ARG: char = 5 as char;
+        const SECRET_ARG: char = 6 as char;
         // Variables used for parsing the argument list. This command is atypical in using the "+"
         // (REQUIRE_ORDER) option for flag parsing. This is not typical of most fish commands. It means
         // we stop scanning for flags when the first non-flag argument is seen.
@@ -129,6 +146,9 @@ fn parse(
             wopt(L!("path"), NoArgument, PATH_ARG),
             wopt(L!("unpath"), NoArgument, UNPATH_ARG),
             wopt(L!("no-event"), NoArgument, NO_EVENT_ARG),
+            wopt(L!("map"), NoArgument, MAP_ARG),
+            wopt(L!("keys"), NoArgument, KEYS_ARG),
+            wopt(L!("secret"), NoArgument, SECRET_ARG),
             wopt(L!("help"), NoArgument, 'h'),
         ];
 
@@ -160,6 +180,12 @@ fn parse(
                 PATH_ARG => opts.pathvar = true,
                 UNPATH_ARG => opts.unpathvar = true,
                 NO_EVENT_ARG => opts.no_event = true,
+                MAP_ARG => opts.map = true,
+                KEYS_ARG => {
+                    opts.keys = true;
+                    opts.preserve_failure_exit_status = false;
+                }
+                SECRET_ARG => opts.secret = true,
                 'U' => opts.universal = true,
                 'L' => opts.shorten_ok = false,
                 'S' => {
@@ -302,10 +328,58 @@ fn validate(
             return Err(STATUS_INVALID_ARGS);
         }
 
-        if args.len() == optind && opts.erase {
+        // Maps can't be exported or path variables, and have no order to prepend to.
+        if opts.map
+            && (opts.exportv
+                || opts.pathvar
+                || opts.prepend
+                || opts.erase
+                || opts.query
+                || opts.list
+                || opts.show)
+        {
+            streams.err.appendln(&wgettext_fmt!(BUILTIN_ERR_COMBO, cmd));
+            builtin_print_error_trailer(parser, streams.err, cmd);
+            return Err(STATUS_INVALID_ARGS);
+        }
+
+        // The --keys flag only takes a scope.
+        if opts.keys
+            && (opts.erase
+                || opts.query
+                || opts.list
+                || opts.show
+                || opts.map
+                || opts.append
+                || opts.prepend
+                || opts.exportv
+                || opts.unexport
+                || opts.pathvar
+                || opts.unpathvar)
+        {
+            streams.err.appendln(&wgettext_fmt!(BUILTIN_ERR_COMBO, cmd));
+            builtin_print_error_trailer(parser, streams.err, cmd);
+            return Err(STATUS_INVALID_ARGS);
+        }
+
+        // The --secret flag only applies to setting variables.
+        if opts.secret && (opts.erase || opts.query || opts.list || opts.show || opts.keys) {
+            streams.err.appendln(&wgettext_fmt!(BUILTIN_ERR_COMBO, cmd));
+            builtin_print_error_trailer(parser, streams.err, cmd);
+            return Err(STATUS_INVALID_ARGS);
+        }
+
+        if args.len() == optind && (opts.erase || opts.keys || opts.secret) {
+            let flag = if opts.erase {
+                L!("--erase")
+            } else if opts.keys {
+                L!("--keys")
+            } else {
+                L!("--secret")
+            };
             streams
                 .err
-                .appendln(&wgettext_fmt!(BUILTIN_ERR_MISSING, cmd, L!("--erase")));
+                .appendln(&wgettext_fmt!(BUILTIN_ERR_MISSING, cmd, flag));
             builtin_print_error_trailer(parser, streams.err, cmd);
             return Err(STATUS_INVALID_ARGS);
         }
@@ -365,6 +439,25 @@ fn handle_env_return(retval: EnvStackSetResult, cmd: &wstr, key: &wstr, streams:
     }
 }
 
+/// In restricted mode, refuse to change the variables that decide what gets run.
+fn check_restricted_variable(
+    cmd: &wstr,
+    varname: &wstr,
+    parser: &Parser,
+    streams: &mut IoStreams,
+) -> Result<(), ErrorCode> {
+    if !is_restricted_variable(varname) {
+        return Ok(());
+    }
+    streams.err.appendln(&wgettext_fmt!(
+        "%s: Cannot change the variable '%s' in restricted mode",
+        cmd,
+        varname
+    ));
+    restriction_violated(parser, L!("variable"), varname);
+    Err(STATUS_CMD_ERROR)
+}
+
 /// Call vars.set. If this is a path variable, e.g. PATH, validate the elements. On error, print a
 /// description of the problem to stderr.
 fn env_set_reporting_errors(
@@ -411,6 +504,8 @@ struct SplitVar<'a> {
     varname: &'a wstr,
     var: Option<EnvVar>,
     indexes: Vec<isize>,
+    /// For maps, the key given in brackets instead of indexes.
+    key: Option<&'a wstr>,
 }
 
 impl<'a> SplitVar<'a> {
@@ -425,16 +520,19 @@ fn varsize(&self) -> usize {
 /// reference. Indexes are "expanded" in the sense that range expressions .. and negative values are
 /// handled.
 ///
+/// If the variable is a map, or `map` is set, the bracket instead holds a single key.
+///
 /// Returns:
 ///   a split var on success, none() on error, in which case an error will have been printed.
 ///   If no index is found, this leaves indexes empty.
 fn split_var_and_indexes<'a>(
     arg: &'a wstr,
     mode: EnvMode,
+    map: bool,
     vars: &dyn Environment,
     streams: &mut IoStreams,
 ) -> Option<SplitVar<'a>> {
-    match split_var_and_indexes_internal(arg, mode, vars) {
+    match split_var_and_indexes_internal(arg, mode, map, vars) {
         Ok(split) => Some(split),
         Err(EnvArrayParseError::InvalidIndex(varname)) => {
             streams.err.append(&wgettext_fmt!(
@@ -450,6 +548,7 @@ fn split_var_and_indexes<'a>(
 fn split_var_and_indexes_internal<'a>(
     arg: &'a wstr,
     mode: EnvMode,
+    map: bool,
     vars: &dyn Environment,
 ) -> Result<SplitVar<'a>, EnvArrayParseError> {
     let mut res = SplitVar::default();
@@ -461,6 +560,16 @@ fn split_var_and_indexes_internal<'a>(
         return Ok(res);
     };
 
+    if map || res.var.as_ref().is_some_and(EnvVar::is_map) {
+        // The key must end at the last character, so it contains no closing bracket.
+        let key = arg.slice_from(open_bracket + 1);
+        let Some(len) = map_key_len(key).filter(|&len| len + 1 == key.len()) else {
+            return Err(EnvArrayParseError::InvalidIndex(key.to_owned()));
+        };
+        res.key = Some(&key[..len]);
+        return Ok(res);
+    }
+
     // We need the length of the array to validate the indexes.
     let len = res
         .var
@@ -519,11 +628,29 @@ fn split_var_and_indexes_internal<'a>(
                 }
             }
 
+            // An optional positive step, like the 2 in "1..-1..2".
+            let mut step = 1;
+            if c.char_at(0) == '.' && c.char_at(1) == '.' {
+                let step_start = c;
+                c = c.slice_from(2);
+                let mut step_consumed = 0;
+                step = wcstoi_partial(c, crate::wutil::Options::default(), &mut step_consumed)
+                    .ok()
+                    .and_then(|step: isize| usize::try_from(step).ok())
+                    .filter(|&step| step > 0)
+                    .ok_or_else(|| EnvArrayParseError::InvalidIndex(step_start.to_owned()))?;
+                c = c.slice_from(step_consumed);
+                // Skip trailing whitespace.
+                while !c.is_empty() && c.char_at(0).is_whitespace() {
+                    c = c.slice_from(1);
+                }
+            }
+
             let l_ind2 = convert_negative_index(l_ind2);
             if l_ind < l_ind2 {
-                res.indexes.extend(l_ind..=l_ind2);
+                res.indexes.extend((l_ind..=l_ind2).step_by(step));
             } else {
-                res.indexes.extend((l_ind2..=l_ind).rev());
+                res.indexes.extend((l_ind2..=l_ind).rev().step_by(step));
             }
         } else {
             res.indexes.push(l_ind);
@@ -563,6 +690,14 @@ fn list(opts: &Options, parser: &Parser, streams: &mut IoStreams) -> BuiltinResu
     names.sort();
 
     for key in names {
+        if opts.map
+            && !parser
+                .vars()
+                .getf(&key, opts.env_mode())
+                .is_some_and(EnvVar::is_map)
+        {
+            continue;
+        }
         let mut out = key.clone();
 
         if !names_only {
@@ -579,7 +714,11 @@ fn list(opts: &Options, parser: &Parser, streams: &mut IoStreams) -> BuiltinResu
                     val += &expand_escape_string(history.item_at_index(i).unwrap().str())[..];
                 }
             } else if let Some(var) = parser.vars().getf_unless_empty(&key, opts.env_mode()) {
-                val = expand_escape_variable(&var);
+                val = if var.is_secret() {
+                    REDACTED.to_owned()
+                } else {
+                    expand_escape_variable(&var)
+                };
             }
             if !val.is_empty() {
                 let mut shorten = false;
@@ -620,12 +759,22 @@ fn query(
     }
 
     for arg in args {
-        let Some(split) = split_var_and_indexes(arg, mode, parser.vars(), streams) else {
+        let Some(split) = split_var_and_indexes(arg, mode, false, parser.vars(), streams) else {
             builtin_print_error_trailer(parser, streams.err, cmd);
             return Err(STATUS_CMD_ERROR);
         };
 
-        if split.indexes.is_empty() {
+        if let Some(key) = split.key {
+            // Increment if the map does not have this key.
+            if split
+                .var
+                .as_ref()
+                .and_then(|var| var.map_get(key))
+                .is_none()
+            {
+                retval += 1;
+            }
+        } else if split.indexes.is_empty() {
             // No indexes, just increment if our variable is missing.
             if split.var.is_none() {
                 retval += 1;
@@ -666,21 +815,33 @@ fn show_scope(var_name: &wstr, scope: EnvMode, streams: &mut IoStreams, vars: &d
         L!("")
     };
     let vals = var.as_list();
-    streams.out.append(&wgettext_fmt!(
-        "$%s: set in %s scope, %s,%s with %d elements",
-        var_name,
-        scope_name,
-        exportv,
-        pathvarv,
-        vals.len()
-    ));
+    if var.is_map() {
+        streams.out.append(&wgettext_fmt!(
+            "$%s: set in %s scope, %s, a map with %d entries",
+            var_name,
+            scope_name,
+            exportv,
+            vals.len()
+        ));
+    } else {
+        streams.out.append(&wgettext_fmt!(
+            "$%s: set in %s scope, %s,%s with %d elements",
+            var_name,
+            scope_name,
+            exportv,
+            pathvarv,
+            vals.len()
+        ));
+    }
     // HACK: PWD can be set, depending on how you ask.
     // For our purposes it's read-only.
     if EnvVar::flags_for(var_name).contains(EnvVarFlags::READ_ONLY) {
-        streams.out.append(wgettext!(" (read-only)\n"));
-    } else {
-        streams.out.append('\n');
+        streams.out.append(wgettext!(" (read-only)"));
     }
+    if var.is_secret() {
+        streams.out.append(wgettext!(" (secret)"));
+    }
+    streams.out.append('\n');
 
     for i in 0..vals.len() {
         if vals.len() > 100 {
@@ -699,16 +860,49 @@ fn show_scope(var_name: &wstr, scope: EnvMode, streams: &mut IoStreams, vars: &d
             }
         }
         let value = &vals[i];
-        let escaped_val = escape_string(
-            value,
-            EscapeStringStyle::Script(EscapeFlags::NO_PRINTABLES | EscapeFlags::NO_QUOTED),
-        );
+        let escaped_val = if var.is_secret() {
+            REDACTED.to_owned()
+        } else {
+            escape_string(
+                value,
+                EscapeStringStyle::Script(EscapeFlags::NO_PRINTABLES | EscapeFlags::NO_QUOTED),
+            )
+        };
+        // Map entries are shown by key, list elements by index.
+        let index = if var.is_map() {
+            escape_string(
+                &var.map_keys()[i],
+                EscapeStringStyle::Script(EscapeFlags::NO_PRINTABLES | EscapeFlags::NO_QUOTED),
+            )
+        } else {
+            (i + 1).to_wstring()
+        };
         streams
             .out
-            .append(&sprintf!("$%s[%d]: |%s|\n", var_name, i + 1, &escaped_val));
+            .append(&sprintf!("$%s[%s]: |%s|\n", var_name, index, &escaped_val));
     }
 }
 
+/// Show the value `var_name` was inherited with, unless the variable has since been made secret.
+fn show_inherited(var_name: &wstr, streams: &mut IoStreams, vars: &dyn Environment) {
+    let Some(inherited) = INHERITED_VARS.get().unwrap().get(var_name) else {
+        return;
+    };
+    let escaped_val = if vars.get(var_name).is_some_and(|var| var.is_secret()) {
+        REDACTED.to_owned()
+    } else {
+        escape_string(
+            inherited,
+            EscapeStringStyle::Script(EscapeFlags::NO_PRINTABLES | EscapeFlags::NO_QUOTED),
+        )
+    };
+    streams.out.append(&wgettext_fmt!(
+        "$%s: originally inherited as |%s|\n",
+        var_name,
+        escaped_val
+    ));
+}
+
 /// Show mode. Show information about the named variable(s).
 fn show(cmd: &wstr, parser: &Parser, streams: &mut IoStreams, args: &[&wstr]) -> BuiltinResult {
     let vars = parser.vars();
@@ -725,17 +919,7 @@ fn show(cmd: &wstr, parser: &Parser, streams: &mut IoStreams, args: &[&wstr]) ->
             show_scope(&name, EnvMode::UNIVERSAL, streams, vars);
 
             // Show the originally imported value as a debugging aid.
-            if let Some(inherited) = INHERITED_VARS.get().unwrap().get(&name) {
-                let escaped_val = escape_string(
-                    inherited,
-                    EscapeStringStyle::Script(EscapeFlags::NO_PRINTABLES | EscapeFlags::NO_QUOTED),
-                );
-                streams.out.append(&wgettext_fmt!(
-                    "$%s: originally inherited as |%s|\n",
-                    name,
-                    escaped_val
-                ));
-            }
+            show_inherited(&name, streams, vars);
         }
     } else {
         for arg in args.iter().copied() {
@@ -757,23 +941,40 @@ fn show(cmd: &wstr, parser: &Parser, streams: &mut IoStreams, args: &[&wstr]) ->
             show_scope(arg, EnvMode::LOCAL, streams, vars);
             show_scope(arg, EnvMode::GLOBAL, streams, vars);
             show_scope(arg, EnvMode::UNIVERSAL, streams, vars);
-            if let Some(inherited) = INHERITED_VARS.get().unwrap().get(arg) {
-                let escaped_val = escape_string(
-                    inherited,
-                    EscapeStringStyle::Script(EscapeFlags::NO_PRINTABLES | EscapeFlags::NO_QUOTED),
-                );
-                streams.out.append(&wgettext_fmt!(
-                    "$%s: originally inherited as |%s|\n",
-                    arg,
-                    escaped_val
-                ));
-            }
+            show_inherited(arg, streams, vars);
         }
     }
 
     Ok(SUCCESS)
 }
 
+/// Keys mode. Print the keys of the named map variables, one per line.
+fn keys(
+    cmd: &wstr,
+    opts: &Options,
+    parser: &Parser,
+    streams: &mut IoStreams,
+    args: &[&wstr],
+) -> BuiltinResult {
+    let mut retval = Ok(SUCCESS);
+    for arg in args.iter().copied() {
+        match parser.vars().getf(arg, opts.env_mode()) {
+            Some(var) if var.is_map() => {
+                for key in var.map_keys() {
+                    streams.out.appendln(key);
+                }
+            }
+            Some(_) => {
+                streams.err.append(&wgettext_fmt!(NOT_A_MAP, cmd, arg));
+                retval = Err(STATUS_CMD_ERROR);
+            }
+            // Missing variables are only reported through $status, like with --query.
+            None => retval = Err(STATUS_CMD_ERROR),
+        }
+    }
+    retval
+}
+
 fn erase(
     cmd: &wstr,
     opts: &Options,
@@ -784,7 +985,8 @@ fn erase(
     let mut ret = Ok(SUCCESS);
     let mut erase_with_mode = |mode| {
         for arg in args {
-            let Some(split) = split_var_and_indexes(arg, mode, parser.vars(), streams) else {
+            let Some(split) = split_var_and_indexes(arg, mode, false, parser.vars(), streams)
+            else {
                 builtin_print_error_trailer(parser, streams.err, cmd);
                 return Err(STATUS_CMD_ERROR);
             };
@@ -794,8 +996,30 @@ fn erase(
                 builtin_print_error_trailer(parser, streams.err, cmd);
                 return Err(STATUS_INVALID_ARGS);
             }
+            check_restricted_variable(cmd, split.varname, parser, streams)?;
             let retval;
-            if split.indexes.is_empty() {
+            if let Some(key) = split.key {
+                // remove just the specified key of the map
+                let Some(var) = split.var else {
+                    return Err(STATUS_CMD_ERROR);
+                };
+                if let Some(idx) = var.map_keys().iter().position(|k| k == key) {
+                    let mut pairs = var.map_pairs();
+                    pairs.drain(2 * idx..2 * idx + 2);
+                    retval = env_set_reporting_errors(
+                        cmd,
+                        opts,
+                        split.varname,
+                        mode | EnvMode::MAP,
+                        pairs,
+                        streams,
+                        parser,
+                    );
+                } else {
+                    // Like a missing variable, a missing key is reported only through $status.
+                    retval = EnvStackSetResult::NotFound;
+                }
+            } else if split.indexes.is_empty() {
                 // unset the var
                 retval = parser.remove_var(split.varname, ParserEnvSetMode::new(mode));
                 // When a non-existent-variable is unset, return NotFound as $status
@@ -942,6 +1166,97 @@ fn new_var_values_by_index(split: &SplitVar, argv: &[&wstr]) -> Vec<WString> {
     result
 }
 
+/// Set a map variable, either all of its entries with `set --map name key value...`, or a single
+/// entry with `set name[key] value`.
+fn set_map(
+    cmd: &wstr,
+    opts: &Options,
+    parser: &Parser,
+    streams: &mut IoStreams,
+    split: &SplitVar,
+    argv: &[&wstr],
+) -> BuiltinResult {
+    // Return the entries of an existing map, or complain if the variable is not a map.
+    let existing_pairs = |var: Option<EnvVar>, streams: &mut IoStreams| match var {
+        None => Ok(vec![]),
+        Some(var) if var.is_map() => Ok(var.map_pairs()),
+        Some(_) => {
+            streams
+                .err
+                .append(&wgettext_fmt!(NOT_A_MAP, cmd, split.varname));
+            builtin_print_error_trailer(parser, streams.err, cmd);
+            Err(STATUS_INVALID_ARGS)
+        }
+    };
+
+    let mut pairs;
+    if let Some(key) = split.key {
+        // Setting a single entry. Like setting a slice, this starts with the variable in the
+        // scope we are setting.
+        if opts.append || opts.prepend {
+            streams.err.append(&wgettext_fmt!(
+                "%s: Cannot use --append or --prepend when assigning to a slice",
+                cmd
+            ));
+            builtin_print_error_trailer(parser, streams.err, cmd);
+            return Err(STATUS_INVALID_ARGS);
+        }
+        if argv.len() != 1 {
+            streams
+                .err
+                .append(&wgettext_fmt!(MISMATCHED_ARGS, cmd, 1, argv.len()));
+            return Err(STATUS_INVALID_ARGS);
+        }
+        pairs = existing_pairs(split.var.clone(), streams)?;
+        pairs.push(key.to_owned());
+        pairs.push(argv[0].to_owned());
+    } else {
+        if argv.len() % 2 != 0 {
+            streams.err.append(&wgettext_fmt!(
+                "%s: --map expects a value for every key\n",
+                cmd
+            ));
+            builtin_print_error_trailer(parser, streams.err, cmd);
+            return Err(STATUS_INVALID_ARGS);
+        }
+        if let Some(key) = argv
+            .iter()
+            .copied()
+            .step_by(2)
+            .find(|key| map_key_len(key).is_some())
+        {
+            streams.err.append(&wgettext_fmt!(
+                "%s: %s: map keys cannot contain ']'\n",
+                cmd,
+                key
+            ));
+            builtin_print_error_trailer(parser, streams.err, cmd);
+            return Err(STATUS_INVALID_ARGS);
+        }
+        // As with lists, appending uses the existing variable from any scope.
+        pairs = if opts.append {
+            existing_pairs(parser.vars().get(split.varname), streams)?
+        } else {
+            vec![]
+        };
+        pairs.extend(argv.iter().copied().map(|s| s.to_owned()));
+    }
+
+    let retval = env_set_reporting_errors(
+        cmd,
+        opts,
+        split.varname,
+        opts.env_mode() | EnvMode::MAP,
+        pairs,
+        streams,
+        parser,
+    );
+    if retval == EnvStackSetResult::Ok {
+        warn_if_uvar_shadows_global(cmd, opts, split.varname, streams, parser);
+    }
+    retval.into()
+}
+
 /// Set a variable.
 fn set_internal(
     cmd: &wstr,
@@ -962,7 +1277,8 @@ fn set_internal(
     let var_expr = argv[0];
     let argv = &argv[1..];
 
-    let Some(split) = split_var_and_indexes(var_expr, mode, parser.vars(), streams) else {
+    let Some(split) = split_var_and_indexes(var_expr, mode, opts.map, parser.vars(), streams)
+    else {
         builtin_print_error_trailer(parser, streams.err, cmd);
         return Err(STATUS_INVALID_ARGS);
     };
@@ -981,6 +1297,7 @@ fn set_internal(
         builtin_print_error_trailer(parser, streams.err, cmd);
         return Err(STATUS_INVALID_ARGS);
     }
+    check_restricted_variable(cmd, split.varname, parser, streams)?;
 
     // Setting with explicit indexes like `set foo[3] ...` has additional error handling.
     if !split.indexes.is_empty() {
@@ -1016,6 +1333,22 @@ fn set_internal(
         }
     }
 
+    if split.key.is_some() || opts.map {
+        return set_map(cmd, opts, parser, streams, &split, argv);
+    }
+
+    // Lists may be appended to, but maps need --map to know that the arguments are entries.
+    if (opts.append || opts.prepend) && parser.vars().get(split.varname).is_some_and(EnvVar::is_map)
+    {
+        streams.err.append(&wgettext_fmt!(
+            "%s: %s: is a map, use `set --map --append` to add entries\n",
+            cmd,
+            split.varname
+        ));
+        builtin_print_error_trailer(parser, streams.err, cmd);
+        return Err(STATUS_INVALID_ARGS);
+    }
+
     let new_values = if split.indexes.is_empty() {
         // Handle the simple, common, case. Set the var to the specified values.
         new_var_values(split.varname, opts, argv, parser.vars())
@@ -1053,6 +1386,8 @@ pub fn set(parser: &Parser, streams: &mut IoStreams, args: &mut [&wstr]) -> Buil
         list(&opts, parser, streams)
     } else if opts.show {
         show(cmd, parser, streams, args)
+    } else if opts.keys {
+        keys(cmd, &opts, parser, streams, args)
     } else if args.is_empty() {
         list(&opts, parser, streams)
     } else {
@@ -5,12 +5,14 @@
 use crate::parse_constants::UNKNOWN_BUILTIN_ERR_MSG;
 use crate::parse_util::argument_is_help;
 use crate::parser::{BlockType, LoopStatus};
-use crate::proc::{Pid, ProcStatus, no_exec};
+use crate::proc::{JobRef, Pid, ProcStatus, no_exec};
 use crate::{builtins::*, wutil};
 use errno::errno;
 use fish_common::assert_sorted_by_name;
 use fish_widestring::L;
 use std::io::{BufRead, BufReader, Read};
+use std::rc::Rc;
+use std::time::Duration;
 
 pub type BuiltinCmd = fn(&Parser, &mut IoStreams, &mut [&wstr]) -> BuiltinResult;
 
@@ -174,6 +176,8 @@ fn builtin_status_code(&self) -> c_int {
 pub const STATUS_READ_TOO_MUCH: c_int = 122;
 /// The status code when an expansion fails, for example, "$foo["
 pub const STATUS_EXPAND_ERROR: c_int = 121;
+/// The status code used when `wait` gives up because its timeout expired. This matches timeout(1).
+pub const STATUS_WAIT_TIMEOUT: c_int = 124;
 
 pub const STATUS_NO_VARIABLES_GIVEN: c_int = 255;
 
@@ -341,6 +345,10 @@ struct BuiltinData {
         name: L!("functions"),
         func: functions::functions,
     },
+    BuiltinData {
+        name: L!("hash"),
+        func: hash::hash,
+    },
     BuiltinData {
         name: L!("history"),
         func: history::history,
@@ -423,7 +431,7 @@ struct BuiltinData {
     },
     BuiltinData {
         name: L!("time"),
-        func: builtin_generic,
+        func: time::time,
     },
     BuiltinData {
         name: L!("true"),
@@ -594,6 +602,7 @@ pub fn builtin_get_desc(name: &wstr) -> Option<&'static wstr> {
         _ if name == "for" => wgettext!("Perform a set of commands multiple times"),
         _ if name == "function" => wgettext!("Define a new function"),
         _ if name == "functions" => wgettext!("List or remove functions"),
+        _ if name == "hash" => wgettext!("Inspect or clear remembered command locations"),
         _ if name == "history" => wgettext!("History of commands executed by user"),
         _ if name == "if" => wgettext!("Evaluate block if condition is true"),
         _ if name == "jobs" => wgettext!("Print currently running jobs"),
@@ -990,6 +999,117 @@ fn parsed_pid(
     }
 }
 
+/// The ways in which a job spec can fail to select a job.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum JobSpecError {
+    /// The spec is a malformed job ID, like `%0x`.
+    Invalid,
+    /// No job matches the spec.
+    NoSuchJob,
+    /// More than one job matches the spec, which records how many.
+    Ambiguous(usize),
+}
+
+impl JobSpecError {
+    /// Return the error message for the job spec `arg` given to `cmd`.
+    pub fn describe(self, cmd: &wstr, arg: &wstr) -> WString {
+        match self {
+            JobSpecError::Invalid => wgettext_fmt!("%s: '%s' is not a valid job ID", cmd, arg),
+            JobSpecError::NoSuchJob => wgettext_fmt!("%s: No suitable job: %s", cmd, arg),
+            JobSpecError::Ambiguous(count) => wgettext_fmt!(
+                "%s: Ambiguous job specification '%s' matches %d jobs",
+                cmd,
+                arg,
+                count
+            ),
+        }
+    }
+
+    /// Return the status a builtin should return for this error.
+    pub fn status(self) -> ErrorCode {
+        match self {
+            JobSpecError::Invalid | JobSpecError::Ambiguous(_) => STATUS_INVALID_ARGS,
+            JobSpecError::NoSuchJob => STATUS_CMD_ERROR,
+        }
+    }
+}
+
+/// Return whether `arg` is a job spec rather than a process ID or name.
+pub fn is_job_spec(arg: &wstr) -> bool {
+    arg.char_at(0) == '%'
+}
+
+/// Find the job selected by a POSIX-style job spec, along with its position in the job list.
+/// Jobs are ordered from most to least recently used, as printed by `jobs`.
+///
+/// - `%N` selects the job with ID N.
+/// - `%`, `%%`, `%+` and `%0` select the current job, `%-` the previous one.
+/// - `%name` selects the job whose command starts with `name`.
+/// - `%?text` selects the job whose command contains `text`.
+pub fn find_job_spec(parser: &Parser, arg: &wstr) -> Result<(usize, JobRef), JobSpecError> {
+    let Some(spec) = arg.strip_prefix('%') else {
+        return Err(JobSpecError::Invalid);
+    };
+    let jobs = parser.jobs();
+    let mut candidates = jobs.iter().enumerate().filter(|(_pos, j)| j.is_visible());
+    let matches: Vec<(usize, &JobRef)> = match spec.as_char_slice() {
+        [] | ['%'] | ['+'] | ['0'] => candidates.take(1).collect(),
+        ['-'] => candidates.skip(1).take(1).collect(),
+        ['?', text @ ..] => candidates
+            .filter(|(_pos, j)| j.command().find(text).is_some())
+            .collect(),
+        [c, ..] if c.is_ascii_digit() => {
+            let job_id = fish_wcstoi(spec)
+                .ok()
+                .and_then(|job_id| u32::try_from(job_id).ok())
+                .ok_or(JobSpecError::Invalid)?;
+            candidates
+                .filter(|(_pos, j)| j.job_id().as_num() == i64::from(job_id))
+                .collect()
+        }
+        _ => candidates
+            .filter(|(_pos, j)| j.command().starts_with(spec))
+            .collect(),
+    };
+    match matches.as_slice() {
+        [] => Err(JobSpecError::NoSuchJob),
+        [(pos, j)] => Ok((*pos, Rc::clone(j))),
+        _ => Err(JobSpecError::Ambiguous(matches.len())),
+    }
+}
+
+/// Like [`find_job_spec`], but print an error to `streams` on failure.
+pub fn parse_job_spec(
+    parser: &Parser,
+    streams: &mut IoStreams,
+    cmd: &wstr,
+    arg: &wstr,
+) -> Result<(usize, JobRef), ErrorCode> {
+    find_job_spec(parser, arg).map_err(|err| {
+        streams.err.appendln(&err.describe(cmd, arg));
+        err.status()
+    })
+}
+
+/// Parse a duration such as `1.5`, `250ms`, `30s`, `5m`, `2h` or `1d`.
+/// A number without a unit is taken to be in seconds. Negative and non-finite values are rejected.
+pub fn parse_duration(arg: &wstr) -> Option<Duration> {
+    let mut consumed = 0;
+    let value = wutil::wcstod::wcstod(arg, '.', &mut consumed).ok()?;
+    let scale = match arg.slice_from(consumed).to_string().as_str() {
+        "ms" => 0.001,
+        "" | "s" => 1.0,
+        "m" => 60.0,
+        "h" => 60.0 * 60.0,
+        "d" => 24.0 * 60.0 * 60.0,
+        _ => return None,
+    };
+    if !value.is_finite() || value < 0.0 {
+        return None;
+    }
+    Duration::try_from_secs_f64(value * scale).ok()
+}
+
 /// A generic builtin that only supports showing a help message. This is only a placeholder that
 /// prints the help message. Useful for commands that live in the parser.
 fn builtin_generic(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> BuiltinResult {
@@ -1003,7 +1123,7 @@ fn builtin_generic(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr])
 
     // Hackish - if we have no arguments other than the command, we are a "naked invocation" and we
     // just print help.
-    if argc == 1 || argv[0] == "time" {
+    if argc == 1 {
         builtin_print_help(parser, streams, argv[0]);
         return Err(STATUS_INVALID_ARGS);
     }
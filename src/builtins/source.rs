@@ -6,6 +6,9 @@
     nix::isatty,
     parser::Block,
     reader::reader_read,
+    restricted::{
+        ENV_RESTRICTED_SOURCE_PATH, is_allowed_source, is_restricted, restriction_violated,
+    },
 };
 use nix::{fcntl::OFlag, sys::stat::Mode};
 
@@ -54,6 +57,16 @@ pub fn source(parser: &Parser, streams: &mut IoStreams, args: &mut [&wstr]) -> B
         func_filename = FilenameRef::new(L!("-").to_owned());
         fd = streams.stdin_fd();
     } else {
+        if is_restricted() && !is_allowed_source(args[optind], parser.vars()) {
+            streams.err.appendln(&wgettext_fmt!(
+                "%s: Cannot source '%s' in restricted mode, it is not in $%s",
+                cmd,
+                &escape(args[optind]),
+                ENV_RESTRICTED_SOURCE_PATH
+            ));
+            restriction_violated(parser, L!("source"), args[optind]);
+            return Err(STATUS_CMD_ERROR);
+        }
         match wopen_cloexec(args[optind], OFlag::O_RDONLY, Mode::empty()) {
             Ok(file) => {
                 opened_file = file;
@@ -9,6 +9,7 @@
 mod length;
 mod r#match;
 mod pad;
+mod parse_time;
 mod repeat;
 mod replace;
 mod shorten;
@@ -349,6 +350,7 @@ pub fn string(parser: &Parser, streams: &mut IoStreams, args: &mut [&wstr]) -> B
         .run(parser, streams, args),
         "match" => r#match::Match::default().run(parser, streams, args),
         "pad" => pad::Pad::default().run(parser, streams, args),
+        "parse-time" => parse_time::ParseTime::default().run(parser, streams, args),
         "repeat" => repeat::Repeat::default().run(parser, streams, args),
         "replace" => replace::Replace::default().run(parser, streams, args),
         "shorten" => shorten::Shorten::default().run(parser, streams, args),
@@ -5,6 +5,8 @@ pub struct Join<'args> {
     no_empty: bool,
     pub is_join0: bool,
     sep: &'args wstr,
+    csv: bool,
+    quote: char,
 }
 
 impl Default for Join<'_> {
@@ -14,21 +16,61 @@ fn default() -> Self {
             no_empty: false,
             is_join0: false,
             sep: L!("\0"),
+            csv: false,
+            quote: '"',
         }
     }
 }
 
+/// Quote a field of CSV data if it contains `sep`, `quote` or a line break, doubling any quotes
+/// inside it.
+fn quote_csv_field(field: &wstr, sep: char, quote: char) -> Cow<'_, wstr> {
+    if !field
+        .chars()
+        .any(|c| c == sep || c == quote || c == '\n' || c == '\r')
+    {
+        return Cow::Borrowed(field);
+    }
+    let mut quoted = WString::with_capacity(field.len() + 2);
+    quoted.push(quote);
+    for c in field.chars() {
+        if c == quote {
+            quoted.push(quote);
+        }
+        quoted.push(c);
+    }
+    quoted.push(quote);
+    Cow::Owned(quoted)
+}
+
+const CSV_SHORT: char = 1 as char;
+const QUOTE_SHORT: char = 2 as char;
+
 impl<'args> StringSubCommand<'args> for Join<'args> {
     const LONG_OPTIONS: &'static [WOption<'static>] = &[
         wopt(L!("quiet"), NoArgument, 'q'),
         wopt(L!("no-empty"), NoArgument, 'n'),
+        wopt(L!("csv"), NoArgument, CSV_SHORT),
+        wopt(L!("quote"), RequiredArgument, QUOTE_SHORT),
     ];
     const SHORT_OPTIONS: &'static wstr = L!("qn");
 
-    fn parse_opt(&mut self, _n: &wstr, c: char, _arg: Option<&wstr>) -> Result<(), StringError> {
+    fn parse_opt(&mut self, name: &wstr, c: char, arg: Option<&wstr>) -> Result<(), StringError> {
         match c {
             'q' => self.quiet = true,
             'n' => self.no_empty = true,
+            CSV_SHORT => self.csv = true,
+            QUOTE_SHORT => {
+                let quote = arg.unwrap();
+                if quote.len() != 1 {
+                    return Err(invalid_args!(
+                        "%s: Invalid quote character '%s'\n",
+                        name,
+                        arg
+                    ));
+                }
+                self.quote = quote.char_at(0);
+            }
             _ => return Err(StringError::UnknownOption),
         }
         Ok(())
@@ -61,6 +103,25 @@ fn handle(
         optind: &mut usize,
         args: &[&wstr],
     ) -> Result<(), ErrorCode> {
+        if self.csv {
+            if self.sep.len() != 1 {
+                string_error!(
+                    streams,
+                    "%s: The separator must be a single character with --csv",
+                    args[0]
+                );
+                return Err(STATUS_INVALID_ARGS);
+            }
+        } else if self.quote != '"' {
+            string_error!(
+                streams,
+                BUILTIN_ERR_COMBO2,
+                args[0],
+                wgettext!("--quote is only valid with --csv")
+            );
+            return Err(STATUS_INVALID_ARGS);
+        }
+
         let sep = self.sep;
         let mut nargs = 0usize;
         let mut print_trailing_newline = true;
@@ -74,7 +135,13 @@ fn handle(
                     streams.out.append(sep);
                 }
 
-                streams.out.append(&arg);
+                if self.csv {
+                    streams
+                        .out
+                        .append(&quote_csv_field(&arg, sep.char_at(0), self.quote));
+                } else {
+                    streams.out.append(&arg);
+                }
             } else if nargs > 1 {
                 return Ok(());
             }
@@ -121,4 +188,17 @@ fn plain() {
         validate!(["string", "join", "-q", "."], STATUS_CMD_ERROR, "");
         validate!(["string", "join", "-q", ".", "."], STATUS_CMD_ERROR, "");
     }
+
+    #[test]
+    #[serial]
+    #[rustfmt::skip]
+    fn csv() {
+        let _cleanup = test_init();
+        validate!(["string", "join", "--csv", ",", "a", "b,c", "x\"y"], STATUS_CMD_OK, "a,\"b,c\",\"x\"\"y\"\n");
+        validate!(["string", "join", "--csv", ",", "a b", "", "c\nd"], STATUS_CMD_OK, "a b,,\"c\nd\"\n");
+        validate!(["string", "join", "--csv", "\t", "a,b", "c\td"], STATUS_CMD_OK, "a,b\t\"c\td\"\n");
+        validate!(["string", "join", "--csv", "--quote", "'", ";", "it's", "a;b", "\"c\""], STATUS_CMD_OK, "'it''s';'a;b';\"c\"\n");
+        validate!(["string", "join", "--csv", ", ", "a", "b"], STATUS_INVALID_ARGS, "");
+        validate!(["string", "join", "--quote", "'", ",", "a", "b"], STATUS_INVALID_ARGS, "");
+    }
 }
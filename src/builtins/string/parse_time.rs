@@ -0,0 +1,70 @@
+use super::*;
+use crate::wutil::timefmt::{parse_iso8601, wstrptime};
+
+#[derive(Default)]
+pub struct ParseTime<'args> {
+    format: Option<&'args wstr>,
+    utc: bool,
+    quiet: bool,
+}
+
+impl<'args> StringSubCommand<'args> for ParseTime<'args> {
+    const LONG_OPTIONS: &'static [WOption<'static>] = &[
+        wopt(L!("format"), RequiredArgument, 'f'),
+        wopt(L!("utc"), NoArgument, 'u'),
+        wopt(L!("quiet"), NoArgument, 'q'),
+    ];
+    const SHORT_OPTIONS: &'static wstr = L!("f:uq");
+
+    fn parse_opt(
+        &mut self,
+        _n: &wstr,
+        c: char,
+        arg: Option<&'args wstr>,
+    ) -> Result<(), StringError> {
+        match c {
+            'f' => self.format = arg,
+            'u' => self.utc = true,
+            'q' => self.quiet = true,
+            _ => return Err(StringError::UnknownOption),
+        }
+        Ok(())
+    }
+
+    fn handle(
+        &mut self,
+        _parser: &Parser,
+        streams: &mut IoStreams,
+        optind: &mut usize,
+        args: &[&wstr],
+    ) -> Result<(), ErrorCode> {
+        let cmd = args[0];
+        let mut all_parsed = true;
+
+        for InputValue { arg, .. } in arguments(args, optind, streams) {
+            let time = match self.format {
+                Some(format) => wstrptime(&arg, format, self.utc),
+                None => parse_iso8601(&arg, self.utc),
+            };
+            match time {
+                Some(time) => {
+                    if !self.quiet {
+                        streams.out.appendln(&time.to_wstring());
+                    }
+                }
+                None => {
+                    all_parsed = false;
+                    if !self.quiet {
+                        string_error!(streams, "%s: Cannot parse time '%s'", cmd, &*arg);
+                    }
+                }
+            }
+        }
+
+        if all_parsed {
+            Ok(())
+        } else {
+            Err(STATUS_CMD_ERROR)
+        }
+    }
+}
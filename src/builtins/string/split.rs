@@ -12,6 +12,8 @@ pub struct Split<'args> {
     allow_empty: bool,
     pub is_split0: bool,
     sep: &'args wstr,
+    csv: bool,
+    quote: char,
 }
 
 impl Default for Split<'_> {
@@ -25,6 +27,8 @@ fn default() -> Self {
             allow_empty: false,
             is_split0: false,
             sep: L!("\0"),
+            csv: false,
+            quote: '"',
         }
     }
 }
@@ -101,6 +105,50 @@ fn parse_field(f: &wstr) -> Result<Vec<usize>, FieldParseError> {
     }
 }
 
+/// Split one record of CSV data into its fields, as described by RFC 4180. Fields are separated
+/// by `sep`, and a field enclosed in `quote` may contain `sep`, newlines and doubled quotes.
+/// Return None if a quoted field is not terminated, so the record continues on the next line.
+fn split_csv(record: &wstr, sep: char, quote: char) -> Option<Vec<WString>> {
+    let mut fields = Vec::new();
+    let mut field = WString::new();
+    let mut at_field_start = true;
+    let mut in_quotes = false;
+    let mut chars = record.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c != quote {
+                field.push(c);
+            } else if chars.peek() == Some(&quote) {
+                // A doubled quote stands for a single one.
+                field.push(quote);
+                chars.next();
+            } else {
+                in_quotes = false;
+            }
+        } else if c == sep {
+            fields.push(std::mem::take(&mut field));
+            at_field_start = true;
+            continue;
+        } else if c == quote && at_field_start {
+            in_quotes = true;
+        } else if c == '\r' && chars.peek().is_none() {
+            // Ignore the carriage return of a CRLF line ending.
+        } else {
+            // Like other parsers, keep quotes that don't start the field.
+            field.push(c);
+        }
+        at_field_start = false;
+    }
+    if in_quotes {
+        return None;
+    }
+    fields.push(field);
+    Some(fields)
+}
+
+const CSV_SHORT: char = 1 as char;
+const QUOTE_SHORT: char = 2 as char;
+
 impl<'args> StringSubCommand<'args> for Split<'args> {
     const LONG_OPTIONS: &'static [WOption<'static>] = &[
         wopt(L!("quiet"), NoArgument, 'q'),
@@ -109,6 +157,8 @@ impl<'args> StringSubCommand<'args> for Split<'args> {
         wopt(L!("no-empty"), NoArgument, 'n'),
         wopt(L!("fields"), RequiredArgument, 'f'),
         wopt(L!("allow-empty"), NoArgument, 'a'),
+        wopt(L!("csv"), NoArgument, CSV_SHORT),
+        wopt(L!("quote"), RequiredArgument, QUOTE_SHORT),
     ];
     const SHORT_OPTIONS: &'static wstr = L!("qrm:nf:a");
 
@@ -134,6 +184,18 @@ fn parse_opt(&mut self, name: &wstr, c: char, arg: Option<&wstr>) -> Result<(),
                 })?;
             }
             'a' => self.allow_empty = true,
+            CSV_SHORT => self.csv = true,
+            QUOTE_SHORT => {
+                let quote = arg.unwrap();
+                if quote.len() != 1 {
+                    return Err(invalid_args!(
+                        "%s: Invalid quote character '%s'\n",
+                        name,
+                        arg
+                    ));
+                }
+                self.quote = quote.char_at(0);
+            }
             _ => return Err(StringError::UnknownOption),
         }
         Ok(())
@@ -172,6 +234,33 @@ fn handle(
             ));
             return Err(STATUS_INVALID_ARGS);
         }
+        if self.csv {
+            if self.max != usize::MAX || self.split_from == Direction::Right {
+                string_error!(
+                    streams,
+                    BUILTIN_ERR_COMBO2,
+                    args[0],
+                    wgettext!("--max and --right can't be used with --csv")
+                );
+                return Err(STATUS_INVALID_ARGS);
+            }
+            if self.sep.len() != 1 {
+                string_error!(
+                    streams,
+                    "%s: The separator must be a single character with --csv",
+                    args[0]
+                );
+                return Err(STATUS_INVALID_ARGS);
+            }
+        } else if self.quote != '"' {
+            string_error!(
+                streams,
+                BUILTIN_ERR_COMBO2,
+                args[0],
+                wgettext!("--quote is only valid with --csv")
+            );
+            return Err(STATUS_INVALID_ARGS);
+        }
 
         let sep = self.sep;
         let mut all_splits: Vec<Vec<Cow<'args, wstr>>> = Vec::new();
@@ -182,34 +271,56 @@ fn handle(
             false => SplitBehavior::Newline,
             true => SplitBehavior::Never,
         });
+        // The start of a CSV record whose quoted field continues on the next line.
+        let mut pending_record: Option<WString> = None;
         for InputValue { arg, .. } in argiter {
-            let splits: Vec<Cow<'args, wstr>> = match (self.split_from, arg) {
-                (Direction::Right, arg) => {
-                    let mut rev = arg.into_owned();
-                    rev.as_char_slice_mut().reverse();
-                    let sep: WString = sep.chars().rev().collect();
-                    split_about(&rev, &sep, self.max, self.no_empty)
-                        .into_iter()
-                        // If we are from the right, split_about gave us reversed strings, in reversed order!
-                        .map(|s| Cow::Owned(s.chars().rev().collect::<WString>()))
-                        .rev()
-                        .collect()
-                }
-                // we need to special-case the Cow::Borrowed case, since
-                // let arg: &'args wstr = &arg;
-                // does not compile since `arg` can be dropped at the end of this scope
-                // making the reference invalid if it is owned.
-                (Direction::Left, Cow::Borrowed(arg)) => {
-                    split_about(arg, sep, self.max, self.no_empty)
-                        .into_iter()
-                        .map(Cow::Borrowed)
-                        .collect()
-                }
-                (Direction::Left, Cow::Owned(arg)) => {
-                    split_about(&arg, sep, self.max, self.no_empty)
-                        .into_iter()
-                        .map(|s| Cow::Owned(s.to_owned()))
-                        .collect()
+            let splits: Vec<Cow<'args, wstr>> = if self.csv {
+                let record = match pending_record.take() {
+                    Some(mut record) => {
+                        record.push('\n');
+                        record.push_utfstr(&arg);
+                        Cow::Owned(record)
+                    }
+                    None => arg,
+                };
+                let Some(fields) = split_csv(&record, sep.char_at(0), self.quote) else {
+                    pending_record = Some(record.into_owned());
+                    continue;
+                };
+                fields
+                    .into_iter()
+                    .filter(|field| !self.no_empty || !field.is_empty())
+                    .map(Cow::Owned)
+                    .collect()
+            } else {
+                match (self.split_from, arg) {
+                    (Direction::Right, arg) => {
+                        let mut rev = arg.into_owned();
+                        rev.as_char_slice_mut().reverse();
+                        let sep: WString = sep.chars().rev().collect();
+                        split_about(&rev, &sep, self.max, self.no_empty)
+                            .into_iter()
+                            // If we are from the right, split_about gave us reversed strings, in reversed order!
+                            .map(|s| Cow::Owned(s.chars().rev().collect::<WString>()))
+                            .rev()
+                            .collect()
+                    }
+                    // we need to special-case the Cow::Borrowed case, since
+                    // let arg: &'args wstr = &arg;
+                    // does not compile since `arg` can be dropped at the end of this scope
+                    // making the reference invalid if it is owned.
+                    (Direction::Left, Cow::Borrowed(arg)) => {
+                        split_about(arg, sep, self.max, self.no_empty)
+                            .into_iter()
+                            .map(Cow::Borrowed)
+                            .collect()
+                    }
+                    (Direction::Left, Cow::Owned(arg)) => {
+                        split_about(&arg, sep, self.max, self.no_empty)
+                            .into_iter()
+                            .map(|s| Cow::Owned(s.to_owned()))
+                            .collect()
+                    }
                 }
             };
 
@@ -222,8 +333,13 @@ fn handle(
             all_splits.push(splits);
         }
 
+        let unterminated = pending_record.is_some();
+        if unterminated && !self.quiet {
+            string_error!(streams, "%s: Unterminated quoted field", args[0]);
+        }
+
         if self.quiet {
-            return if split_count > arg_count {
+            return if split_count > arg_count && !unterminated {
                 Ok(())
             } else {
                 Err(STATUS_CMD_ERROR)
@@ -271,7 +387,7 @@ fn handle(
         }
 
         // We split something if we have more split values than args.
-        if split_count > arg_count {
+        if split_count > arg_count && !unterminated {
             Ok(())
         } else {
             Err(STATUS_CMD_ERROR)
@@ -317,4 +433,29 @@ fn plain() {
         validate!(["string", "split", "-q", ":"], STATUS_CMD_ERROR, "");
         validate!(["string", "split", "-q", "x", "axbxc"], STATUS_CMD_OK, "");
     }
+
+    #[test]
+    #[serial]
+    #[rustfmt::skip]
+    fn csv() {
+        let _cleanup = test_init();
+        validate!(["string", "split", "--csv", ",", "a,\"b,c\",\"say \"\"hi\"\"\""], STATUS_CMD_OK, "a\nb,c\nsay \"hi\"\n");
+        validate!(["string", "split", "--csv", ",", "a,,b,"], STATUS_CMD_OK, "a\n\nb\n\n");
+        validate!(["string", "split", "--csv", "-n", ",", "a,,b,"], STATUS_CMD_OK, "a\nb\n");
+        validate!(["string", "split", "--csv", ",", "a\"b,c\"d"], STATUS_CMD_OK, "a\"b\nc\"d\n");
+        validate!(["string", "split", "--csv", ",", "\"a\"b,c"], STATUS_CMD_OK, "ab\nc\n");
+        validate!(["string", "split", "--csv", ",", "a,b\r"], STATUS_CMD_OK, "a\nb\n");
+        validate!(["string", "split", "--csv", "\t", "a b\t\"c\td\""], STATUS_CMD_OK, "a b\nc\td\n");
+        validate!(["string", "split", "--csv", "--quote", "'", ";", "'a;b';\"c"], STATUS_CMD_OK, "a;b\n\"c\n");
+        validate!(["string", "split", "--csv", "-f2", ",", "a,\"b,c\",d", "e,f"], STATUS_CMD_OK, "b,c\nf\n");
+        validate!(["string", "split", "--csv", "-f3", ",", "a,b,c", "d,e"], STATUS_CMD_ERROR, "");
+        validate!(["string", "split", "--csv", ",", "abc"], STATUS_CMD_ERROR, "abc\n");
+        validate!(["string", "split", "--csv", ",", "a,\"b"], STATUS_CMD_ERROR, "");
+        validate!(["string", "split", "--csv", "-q", ",", "a,b"], STATUS_CMD_OK, "");
+        validate!(["string", "split", "--csv", ",,", "a,b"], STATUS_INVALID_ARGS, "");
+        validate!(["string", "split", "--csv", "-m1", ",", "a,b"], STATUS_INVALID_ARGS, "");
+        validate!(["string", "split", "--csv", "-r", ",", "a,b"], STATUS_INVALID_ARGS, "");
+        validate!(["string", "split", "--csv", "--quote", "ab", ",", "a,b"], STATUS_INVALID_ARGS, "");
+        validate!(["string", "split", "--quote", "'", ",", "a,b"], STATUS_INVALID_ARGS, "");
+    }
 }
@@ -0,0 +1,133 @@
+//! Implementation of the time builtin. Plain `time COMMAND` is a keyword handled by the parser; this
+//! only runs when `time` is given options, like `time --repeat 10 COMMAND`.
+
+use super::prelude::*;
+use crate::common::{escape, str2wcstring};
+use crate::env::Environment;
+use crate::signal::signal_check_cancel;
+use crate::timer::{self, TimerSnapshot};
+
+const JSON_SHORT: char = 1 as char;
+
+const SHORT_OPTIONS: &wstr = L!("+hr:w:");
+const LONG_OPTIONS: &[WOption] = &[
+    wopt(L!("help"), NoArgument, 'h'),
+    wopt(L!("repeat"), RequiredArgument, 'r'),
+    wopt(L!("warmup"), RequiredArgument, 'w'),
+    wopt(L!("json"), NoArgument, JSON_SHORT),
+];
+
+/// Parse a number of runs, which must be at least `min`.
+fn parse_count(
+    streams: &mut IoStreams,
+    cmd: &wstr,
+    arg: &wstr,
+    min: i32,
+) -> Result<usize, ErrorCode> {
+    match fish_wcstoi(arg) {
+        Ok(count) if count >= min => Ok(usize::try_from(count).unwrap()),
+        _ => {
+            streams
+                .err
+                .appendln(&wgettext_fmt!(BUILTIN_ERR_NOT_NUMBER, cmd, arg));
+            Err(STATUS_INVALID_ARGS)
+        }
+    }
+}
+
+pub fn time(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> BuiltinResult {
+    let cmd = argv[0];
+    let mut repeat = None;
+    let mut warmup = 0;
+    let mut json = false;
+
+    let mut w = WGetopter::new(SHORT_OPTIONS, LONG_OPTIONS, argv);
+    while let Some(opt) = w.next_opt() {
+        match opt {
+            'h' => {
+                builtin_print_help(parser, streams, cmd);
+                return Ok(SUCCESS);
+            }
+            'r' => repeat = Some(parse_count(streams, cmd, w.woptarg.unwrap(), 1)?),
+            'w' => warmup = parse_count(streams, cmd, w.woptarg.unwrap(), 0)?,
+            JSON_SHORT => json = true,
+            ':' => {
+                builtin_missing_argument(parser, streams, cmd, argv[w.wopt_index - 1], false);
+                return Err(STATUS_INVALID_ARGS);
+            }
+            ';' => {
+                builtin_unexpected_argument(parser, streams, cmd, argv[w.wopt_index - 1], false);
+                return Err(STATUS_INVALID_ARGS);
+            }
+            '?' => {
+                builtin_unknown_option(parser, streams, cmd, argv[w.wopt_index - 1], false);
+                return Err(STATUS_INVALID_ARGS);
+            }
+            _ => panic!("unexpected retval from WGetopter"),
+        }
+    }
+    let optind = w.wopt_index;
+
+    // Without options, `time` is only reached if it is not in command position, like in
+    // `a=b time true`. That is not supported.
+    if optind == 1 {
+        builtin_print_help(parser, streams, cmd);
+        return Err(STATUS_INVALID_ARGS);
+    }
+
+    if optind == argv.len() {
+        streams
+            .err
+            .appendln(&wgettext_fmt!(BUILTIN_ERR_MIN_ARG_COUNT1, cmd, 1, 0));
+        return Err(STATUS_INVALID_ARGS);
+    }
+
+    // Run the command like eval does, so output to pipes is handled correctly. The arguments have
+    // already been expanded, so escape them to keep eval from expanding them again.
+    let mut command = WString::new();
+    for arg in &argv[optind..] {
+        if !command.is_empty() {
+            command.push(' ');
+        }
+        command.push_utfstr(&escape(arg));
+    }
+    let eval_argv = [L!("eval"), command.as_utfstr()];
+
+    let mut status = Ok(SUCCESS);
+    for _ in 0..warmup {
+        status = super::eval::eval(parser, streams, &mut eval_argv.clone());
+        if signal_check_cancel() != 0 {
+            return status;
+        }
+    }
+
+    let mut runs = vec![];
+    for _ in 0..repeat.unwrap_or(1) {
+        let start = TimerSnapshot::take();
+        status = super::eval::eval(parser, streams, &mut eval_argv.clone());
+        runs.push(TimerSnapshot::delta(&start, &TimerSnapshot::take()));
+        if signal_check_cancel() != 0 {
+            break;
+        }
+    }
+
+    let output = if json {
+        str2wcstring(timer::runs_to_json(&runs, warmup))
+    } else if repeat.is_some() {
+        str2wcstring(timer::describe_runs(&runs, warmup))
+    } else {
+        // A single run, as with the time keyword.
+        match parser.vars().get(L!("fish_time_format")) {
+            Some(format) if format.as_string().is_empty() => WString::new(),
+            Some(format) => {
+                let mut output = runs[0].format(&format.as_string());
+                output.push('\n');
+                output
+            }
+            None => str2wcstring(runs[0].describe(true) + "\n"),
+        }
+    };
+    streams.err.append(&output);
+
+    status
+}
@@ -4,6 +4,7 @@
 use crate::highlight::highlight_and_colorize;
 use crate::parse_util::{apply_indents, compute_indents};
 use crate::path::{path_get_path, path_get_paths};
+use crate::suggest::suggest_commands;
 
 #[derive(Default)]
 struct type_cmd_opts_t {
@@ -14,9 +15,12 @@ struct type_cmd_opts_t {
     path: bool,
     force_path: bool,
     query: bool,
+    suggest: bool,
     color: ColorEnabled,
 }
 
+const SUGGEST_SHORT: char = 1 as char;
+
 pub fn r#type(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> BuiltinResult {
     let cmd = argv[0];
     let argc = argv.len();
@@ -35,6 +39,7 @@ pub fn r#type(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> B
         wopt(L!("query"), ArgType::NoArgument, 'q'),
         wopt(L!("quiet"), ArgType::NoArgument, 'q'),
         wopt(L!("color"), ArgType::RequiredArgument, COLOR_OPTION_CHAR),
+        wopt(L!("suggest"), ArgType::NoArgument, SUGGEST_SHORT),
     ];
 
     let mut w = WGetopter::new(shortopts, longopts, argv);
@@ -47,6 +52,7 @@ pub fn r#type(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> B
             'p' => opts.path = true,
             'P' => opts.force_path = true,
             'q' => opts.query = true,
+            SUGGEST_SHORT => opts.suggest = true,
             'h' => {
                 builtin_print_help(parser, streams, cmd);
                 return Ok(SUCCESS);
@@ -78,7 +84,13 @@ pub fn r#type(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> B
         }
     }
 
-    if opts.query as i64 + opts.path as i64 + opts.get_type as i64 + opts.force_path as i64 > 1 {
+    if opts.query as i64
+        + opts.path as i64
+        + opts.get_type as i64
+        + opts.force_path as i64
+        + opts.suggest as i64
+        > 1
+    {
         streams.err.appendln(&wgettext_fmt!(BUILTIN_ERR_COMBO, cmd));
         return Err(STATUS_INVALID_ARGS);
     }
@@ -86,6 +98,21 @@ pub fn r#type(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> B
     let mut res = false;
 
     let optind = w.wopt_index;
+    if opts.suggest {
+        // Print the names that each argument might be a misspelling of, closest first.
+        for arg in &argv[optind..] {
+            for suggestion in suggest_commands(arg, parser.vars()) {
+                res = true;
+                streams.out.appendln(&suggestion);
+            }
+        }
+        return if res {
+            Ok(SUCCESS)
+        } else {
+            Err(STATUS_CMD_ERROR)
+        };
+    }
+
     for arg in argv.iter().take(argc).skip(optind) {
         let mut found = 0;
         if !opts.force_path && !opts.no_functions {
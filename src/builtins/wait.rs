@@ -1,7 +1,12 @@
 use super::prelude::*;
 use crate::proc::{Job, Pid, proc_wait_any};
-use crate::signal::SigChecker;
-use crate::wait_handle::{WaitHandleRef, WaitHandleStore};
+use crate::signal::{SigChecker, Signal};
+use crate::threads;
+use crate::topic_monitor::{Topic, topic_monitor_principal};
+use crate::wait_handle::{InternalJobId, WaitHandleRef, WaitHandleStore};
+use nix::sys::signal::Signal as NixSignal;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
 
 /// Return true if we can wait on a job.
 fn can_wait_on_job(j: &Job) -> bool {
@@ -98,14 +103,92 @@ fn is_completed(wh: &WaitHandleRef) -> bool {
     wh.is_completed()
 }
 
+/// Limits on how long `wait` may block, and what to do once they are reached.
+#[derive(Default)]
+struct WaitLimits {
+    /// Give up (or signal the jobs) after this long.
+    timeout: Option<Duration>,
+    /// The signal to send to the waited-on jobs when the timeout expires.
+    signal: Option<Signal>,
+    /// If the jobs are still running this long after being signalled, send SIGKILL.
+    kill_after: Option<Duration>,
+}
+
+/// Wakes up a thread blocked in [`proc_wait_any()`] once a deadline has passed.
+/// The wakeup is cancelled when this is dropped.
+struct DeadlineWaker {
+    cancelled: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl DeadlineWaker {
+    fn new(deadline: Instant) -> Self {
+        let cancelled = Arc::new((Mutex::new(false), Condvar::new()));
+        let thread_cancelled = Arc::clone(&cancelled);
+        threads::spawn(move || {
+            let (lock, cvar) = &*thread_cancelled;
+            let mut cancelled = lock.lock().unwrap();
+            while !*cancelled {
+                let now = Instant::now();
+                if now >= deadline {
+                    // A spurious post is harmless: the waiter merely finds nothing to reap and
+                    // gets to check its deadline.
+                    let tm = topic_monitor_principal();
+                    tm.post(Topic::SigChld);
+                    tm.post(Topic::InternalExit);
+                    break;
+                }
+                cancelled = cvar.wait_timeout(cancelled, deadline - now).unwrap().0;
+            }
+        });
+        DeadlineWaker { cancelled }
+    }
+}
+
+impl Drop for DeadlineWaker {
+    fn drop(&mut self) {
+        let (lock, cvar) = &*self.cancelled;
+        *lock.lock().unwrap() = true;
+        cvar.notify_all();
+    }
+}
+
+/// Send `sig` to every job which still has an incomplete process in `whs`.
+fn signal_jobs(parser: &Parser, whs: &[WaitHandleRef], sig: Signal) {
+    let Ok(sig) = NixSignal::try_from(sig.code()) else {
+        return;
+    };
+    let mut signalled: Vec<InternalJobId> = Vec::new();
+    for wh in whs.iter().filter(|wh| !is_completed(wh)) {
+        if signalled.contains(&wh.internal_job_id) {
+            continue;
+        }
+        signalled.push(wh.internal_job_id);
+        if let Some(j) = parser
+            .jobs()
+            .iter()
+            .find(|j| j.internal_job_id == wh.internal_job_id)
+        {
+            j.signal(sig);
+        }
+    }
+}
+
 /// Wait for the given wait handles to be marked as completed.
 /// If `any_flag` is set, wait for the first one; otherwise wait for all.
 /// Return a status code.
-fn wait_for_completion(parser: &Parser, whs: &[WaitHandleRef], any_flag: bool) -> BuiltinResult {
+fn wait_for_completion(
+    parser: &Parser,
+    whs: &[WaitHandleRef],
+    any_flag: bool,
+    limits: &WaitLimits,
+) -> BuiltinResult {
     if whs.is_empty() {
         return Ok(SUCCESS);
     }
 
+    let mut deadline = limits.timeout.map(|timeout| Instant::now() + timeout);
+    let mut _waker = deadline.map(DeadlineWaker::new);
+    let mut timed_out = false;
     let mut sigint = SigChecker::new_sighupint();
     loop {
         let finished = if any_flag {
@@ -124,11 +207,32 @@ fn wait_for_completion(parser: &Parser, whs: &[WaitHandleRef], any_flag: bool) -
                     }
                 }
             }
-            return Ok(SUCCESS);
+            return if timed_out {
+                Err(STATUS_WAIT_TIMEOUT)
+            } else {
+                Ok(SUCCESS)
+            };
         }
         if sigint.check() {
             return Err(128 + libc::SIGINT);
         }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            if timed_out {
+                // The jobs ignored our signal for too long.
+                signal_jobs(parser, whs, Signal::new(libc::SIGKILL));
+                deadline = None;
+            } else {
+                timed_out = true;
+                let Some(sig) = limits.signal else {
+                    return Err(STATUS_WAIT_TIMEOUT);
+                };
+                signal_jobs(parser, whs, sig);
+                deadline = limits
+                    .kill_after
+                    .map(|kill_after| Instant::now() + kill_after);
+            }
+            _waker = deadline.map(DeadlineWaker::new);
+        }
         proc_wait_any(parser);
     }
 }
@@ -137,12 +241,16 @@ pub fn wait(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
     let cmd = argv[0];
     let argc = argv.len();
     let mut any_flag = false; // flag for -n option
+    let mut limits = WaitLimits::default();
     let mut print_help = false;
     let print_hints = false;
 
-    let shortopts: &wstr = L!("nh");
+    let shortopts: &wstr = L!("nt:s:k:h");
     let longopts: &[WOption] = &[
         wopt(L!("any"), ArgType::NoArgument, 'n'),
+        wopt(L!("timeout"), ArgType::RequiredArgument, 't'),
+        wopt(L!("signal"), ArgType::RequiredArgument, 's'),
+        wopt(L!("kill-after"), ArgType::RequiredArgument, 'k'),
         wopt(L!("help"), ArgType::NoArgument, 'h'),
     ];
 
@@ -152,6 +260,30 @@ pub fn wait(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
             'n' => {
                 any_flag = true;
             }
+            't' | 'k' => {
+                let arg = w.woptarg.unwrap();
+                let Some(duration) = parse_duration(arg) else {
+                    streams
+                        .err
+                        .append(&wgettext_fmt!("%s: %s: invalid duration\n", cmd, arg));
+                    return Err(STATUS_INVALID_ARGS);
+                };
+                if c == 't' {
+                    limits.timeout = Some(duration);
+                } else {
+                    limits.kill_after = Some(duration);
+                }
+            }
+            's' => {
+                let arg = w.woptarg.unwrap();
+                let Some(sig) = Signal::parse(arg) else {
+                    streams
+                        .err
+                        .append(&wgettext_fmt!("%s: Unknown signal '%s'\n", cmd, arg));
+                    return Err(STATUS_INVALID_ARGS);
+                };
+                limits.signal = Some(sig);
+            }
             'h' => {
                 print_help = true;
             }
@@ -184,17 +316,38 @@ pub fn wait(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
         return Ok(SUCCESS);
     }
 
+    if limits.timeout.is_none() && (limits.signal.is_some() || limits.kill_after.is_some()) {
+        streams.err.appendln(&wgettext_fmt!(
+            BUILTIN_ERR_COMBO2,
+            cmd,
+            wgettext!("--signal and --kill-after require --timeout")
+        ));
+        return Err(STATUS_INVALID_ARGS);
+    }
+    if limits.kill_after.is_some() && limits.signal.is_none() {
+        limits.signal = Some(Signal::new(libc::SIGTERM));
+    }
+
     if w.wopt_index == argc {
         // No jobs specified.
         // Note this may succeed with an empty wait list.
-        return wait_for_completion(parser, &get_all_wait_handles(parser), any_flag);
+        return wait_for_completion(parser, &get_all_wait_handles(parser), any_flag, &limits);
     }
 
     // Get the list of wait handles for our waiting.
     let mut wait_handles: Vec<WaitHandleRef> = Vec::new();
     let optind = w.wopt_index;
     for item in &argv[optind..argc] {
-        if iswnumeric(item) {
+        if is_job_spec(item) {
+            let Ok((_pos, job)) = parse_job_spec(parser, streams, cmd, item) else {
+                continue;
+            };
+            if can_wait_on_job(&job) {
+                for proc in job.processes().iter() {
+                    wait_handles.extend(proc.make_wait_handle(job.internal_job_id));
+                }
+            }
+        } else if iswnumeric(item) {
             let Ok(pid) = parse_pid(streams, cmd, item) else {
                 continue;
             };
@@ -219,5 +372,5 @@ pub fn wait(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
     if wait_handles.is_empty() {
         return Err(STATUS_INVALID_ARGS);
     }
-    wait_for_completion(parser, &wait_handles, any_flag)
+    wait_for_completion(parser, &wait_handles, any_flag, &limits)
 }
@@ -3,6 +3,7 @@
 use crate::expand::{
     BRACE_BEGIN, BRACE_END, BRACE_SEP, BRACE_SPACE, HOME_DIRECTORY, INTERNAL_SEPARATOR,
     PROCESS_EXPAND_SELF, PROCESS_EXPAND_SELF_STR, VARIABLE_EXPAND, VARIABLE_EXPAND_SINGLE,
+    is_brace_range,
 };
 use crate::future_feature_flags::{FeatureFlag, feature_test};
 use crate::global_safety::AtomicRef;
@@ -12,7 +13,10 @@
 use crate::prelude::*;
 use crate::terminal::Output;
 use crate::termsize::Termsize;
-use crate::wildcard::{ANY_CHAR, ANY_STRING, ANY_STRING_RECURSIVE};
+use crate::wildcard::{
+    ANY_CHAR, ANY_STRING, ANY_STRING_RECURSIVE, QUALIFIERS_BEGIN, WildcardQualifiers,
+    wildcard_has_internal,
+};
 use crate::wutil::fish_iswalnum;
 use fish_fallback::fish_wcwidth;
 use fish_wcstringutil::str2bytes_callback;
@@ -160,6 +164,9 @@ fn escape_string_script(input: &wstr, flags: EscapeFlags) -> WString {
             ANY_STRING_RECURSIVE => {
                 out += L!("**");
             }
+            QUALIFIERS_BEGIN => {
+                out.push('[');
+            }
             ',' => {
                 if escape_comma {
                     need_escape = true;
@@ -378,6 +385,9 @@ fn unescape_string_internal(input: &wstr, flags: UnescapeFlags) -> Option<WStrin
     let mut vars_or_seps = vec![];
     let mut brace_count = 0;
     let mut potential_word_start = None;
+    // The positions of the last unquoted brackets, which may hold wildcard qualifiers.
+    let mut open_bracket = None;
+    let mut close_bracket = None;
 
     let mut errored = false;
     #[derive(PartialEq, Eq)]
@@ -488,7 +498,10 @@ enum Mode {
                             // HACK: To reduce accidental use of brace expansion, treat a brace
                             // with zero or one items as literal input. See #4632. (The hack is
                             // doing it here and like this.)
-                            if vars_or_seps.last().is_none_or(|i| *i < brace) {
+                            // Ranges like {1..10} have a single item but still expand.
+                            if vars_or_seps.last().is_none_or(|i| *i < brace)
+                                && !is_brace_range(&result[brace + 1..])
+                            {
                                 result.as_char_slice_mut()[brace] = '{';
                                 // We also need to turn all spaces back.
                                 for i in brace + 1..result.len() {
@@ -508,6 +521,16 @@ enum Mode {
                         }
                     }
                 }
+                '[' => {
+                    if unescape_special {
+                        open_bracket = Some(result.len());
+                    }
+                }
+                ']' => {
+                    if unescape_special {
+                        close_bracket = Some(result.len());
+                    }
+                }
                 ',' => {
                     if unescape_special && brace_count > 0 {
                         to_append_or_none = Some(BRACE_SEP);
@@ -625,9 +648,34 @@ enum Mode {
     if errored {
         return None;
     }
+    if let (Some(open), Some(close)) = (open_bracket, close_bracket) {
+        if open < close && close + 1 == result.len() && is_wildcard_qualifiers(&result, open) {
+            result.as_char_slice_mut()[open] = QUALIFIERS_BEGIN;
+        }
+    }
     Some(result)
 }
 
+/// Whether the brackets at `open` and the end of the unescaped string `s` hold qualifiers for a
+/// wildcard before them, like in `*.txt[file]`, rather than being literal or the index of a
+/// variable like `$var[1]`.
+fn is_wildcard_qualifiers(s: &wstr, open: usize) -> bool {
+    let before = &s[..open];
+    if !wildcard_has_internal(before) {
+        return false;
+    }
+    if let Some(var) = before
+        .as_char_slice()
+        .iter()
+        .rposition(|&c| c == VARIABLE_EXPAND || c == VARIABLE_EXPAND_SINGLE)
+    {
+        if valid_var_name(&before[var + 1..]) {
+            return false;
+        }
+    }
+    WildcardQualifiers::parse(&s[open + 1..s.len() - 1]).is_some()
+}
+
 /// Reverse the effects of `escape_string_url()`. By definition the input should consist of just
 /// ASCII chars.
 fn unescape_string_url(input: &wstr) -> Option<WString> {
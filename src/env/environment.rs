@@ -223,7 +223,7 @@ pub fn set(&self, key: &wstr, mode: EnvSetMode, mut vals: Vec<WString>) -> EnvSt
         // Hacky stuff around PATH and CDPATH: #3914.
         // Not MANPATH; see #4158.
         // Replace empties with dot. Note we ignore pathvar here.
-        if key == "PATH" || key == "CDPATH" {
+        if (key == "PATH" || key == "CDPATH") && !mode.mode.contains(EnvMode::MAP) {
             // Split on colons.
             let mut munged_vals = colon_split(&vals);
             // Replace empties with dots.
@@ -450,6 +450,34 @@ fn get_pwd_slash(&self) -> WString {
     }
 }
 
+/// What is shown instead of the value of a secret variable.
+pub const REDACTED: &wstr = L!("<redacted>");
+
+/// Return the values of all secret variables, longest first, so that a value which contains
+/// another one is redacted as a whole.
+pub fn secret_values(vars: &dyn Environment) -> Vec<WString> {
+    let mut secrets: Vec<WString> = vars
+        .get_names(EnvMode::default())
+        .into_iter()
+        .filter_map(|name| vars.get(&name))
+        .filter(EnvVar::is_secret)
+        .flat_map(|var| var.as_list().to_vec())
+        .filter(|value| !value.is_empty())
+        .collect();
+    secrets.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    secrets.dedup();
+    secrets
+}
+
+/// Replace every occurrence of one of `secrets` in `text` with [`REDACTED`].
+pub fn redact_secrets(text: &wstr, secrets: &[WString]) -> WString {
+    let mut result = text.to_owned();
+    for secret in secrets {
+        result = result.replace(secret.as_char_slice(), REDACTED);
+    }
+    result
+}
+
 /// Some configuration path environment variables.
 const FISH_DATADIR_VAR: &wstr = L!("__fish_data_dir");
 const FISH_SYSCONFDIR_VAR: &wstr = L!("__fish_sysconf_dir");
@@ -790,7 +818,7 @@ pub fn env_init(paths: Option<&ConfigPaths>, do_uvars: bool, default_paths: bool
 
 #[cfg(test)]
 mod tests {
-    use super::{EnvMode, EnvStack, Environment};
+    use super::{EnvMode, EnvStack, Environment, redact_secrets};
     use crate::env::EnvSetMode;
     use crate::prelude::*;
     use crate::tests::prelude::*;
@@ -879,4 +907,14 @@ fn test_no_global_push() {
     fn test_no_global_pop() {
         EnvStack::globals().pop(false);
     }
+
+    #[test]
+    fn test_redact_secrets() {
+        let secrets = [L!("hunter22").to_owned(), L!("hunter2").to_owned()];
+        assert_eq!(
+            redact_secrets(L!("user:hunter22 hunter2"), &secrets),
+            L!("user:<redacted> <redacted>")
+        );
+        assert_eq!(redact_secrets(L!("nothing"), &secrets), L!("nothing"));
+    }
 }
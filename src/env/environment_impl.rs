@@ -12,6 +12,7 @@
 use crate::null_terminated_array::OwningNullTerminatedArray;
 use crate::prelude::*;
 use crate::reader::{commandline_get_state, reader_status_count};
+use crate::restricted::is_restricted_variable;
 use crate::threads::{is_forked_child, is_main_thread};
 use crate::wutil::fish_wcstol_radix;
 
@@ -109,6 +110,12 @@ struct Query {
     pub pathvar: bool,
     pub unpathvar: bool,
 
+    /// Whether the values are the keys and values of a map.
+    pub map: bool,
+
+    /// Whether to mark the variable as secret.
+    pub secret: bool,
+
     /// Whether this is a "user" set.
     pub user: bool,
 }
@@ -144,6 +151,10 @@ fn new(mode: EnvMode, user: bool) -> Self {
             pathvar: mode.contains(EnvMode::PATHVAR),
             unpathvar: mode.contains(EnvMode::UNPATHVAR),
 
+            map: mode.contains(EnvMode::MAP),
+
+            secret: mode.contains(EnvMode::SECRET),
+
             user,
         }
     }
@@ -681,6 +692,12 @@ struct VarFlags {
 
     /// Whether the variable is exported by some parent.
     pub parent_exports: bool,
+
+    /// Whether the values are the keys and values of a map.
+    pub map: bool,
+
+    /// Whether the variable should be secret. A secret variable stays secret when it is set again.
+    pub secret: bool,
 }
 
 #[derive(Copy, Clone, Default)]
@@ -733,6 +750,10 @@ pub fn set(&mut self, key: &wstr, mode: EnvSetMode, mut val: Vec<WString>) -> Mo
         if let Some(ret) = self.try_set_electric(key, &query, &mut val) {
             return ModResult::new(ret);
         }
+        // A restricted shell may not change the variables that decide what gets run.
+        if query.user && is_restricted_variable(key) {
+            return ModResult::new(EnvStackSetResult::Perm);
+        }
 
         // Resolve as much of our flags as we can. Note these contain maybes, and we may defer the final
         // decision until the set_in_node call. Also note that we only inherit pathvar, not export. For
@@ -743,6 +764,7 @@ pub fn set(&mut self, key: &wstr, mode: EnvSetMode, mut val: Vec<WString>) -> Mo
         if let Some(existing) = self.find_variable(key) {
             flags.pathvar = Some(existing.is_pathvar());
             flags.parent_exports = existing.exports();
+            flags.secret = existing.is_secret();
         }
         if query.has_export_unexport {
             flags.exports = Some(query.exports);
@@ -750,6 +772,8 @@ pub fn set(&mut self, key: &wstr, mode: EnvSetMode, mut val: Vec<WString>) -> Mo
         if query.has_pathvar_unpathvar {
             flags.pathvar = Some(query.pathvar);
         }
+        flags.map = query.map;
+        flags.secret |= query.secret;
 
         let mut result = ModResult::new(EnvStackSetResult::Ok);
         if query.has_scope {
@@ -795,8 +819,8 @@ pub fn set(&mut self, key: &wstr, mode: EnvSetMode, mut val: Vec<WString>) -> Mo
             // Existing global variable.
             Self::set_in_node(&mut node, key, val, flags);
             result.global_modified = true;
-        } else if !UVAR_SCOPE_IS_GLOBAL.load() && uvars().get(key).is_some() {
-            // Existing universal variable.
+        } else if !UVAR_SCOPE_IS_GLOBAL.load() && !query.secret && uvars().get(key).is_some() {
+            // Existing universal variable. Secrets are only stored universally when asked to.
             self.set_universal(key, val, query);
             result.uvar_modified = true;
         } else {
@@ -812,7 +836,7 @@ pub fn set(&mut self, key: &wstr, mode: EnvSetMode, mut val: Vec<WString>) -> Mo
     pub fn remove(&mut self, key: &wstr, mode: EnvSetMode) -> ModResult {
         let query = Query::from(mode);
         // Users can't remove read-only keys.
-        if query.user && is_read_only(key) {
+        if query.user && (is_read_only(key) || is_restricted_variable(key)) {
             return ModResult::new(EnvStackSetResult::Scope);
         }
 
@@ -959,6 +983,11 @@ fn try_set_electric(
             return Some(EnvStackSetResult::Perm);
         }
 
+        // Electric variables are always lists.
+        if query.map {
+            return Some(EnvStackSetResult::Invalid);
+        }
+
         // Be picky about exporting.
         if query.has_export_unexport {
             let matches = if ev.exports() {
@@ -991,6 +1020,8 @@ fn try_set_electric(
             exports: Some(ev.exports()),
             parent_exports: ev.exports(),
             pathvar: Some(false),
+            map: false,
+            secret: false,
         };
         Self::set_in_node(&mut self.base.globals, key, val, flags);
         Some(EnvStackSetResult::Ok)
@@ -1011,9 +1042,11 @@ fn set_universal(&mut self, key: &wstr, mut val: Vec<WString>, query: Query) {
         }
 
         // Resolve whether to be a path variable.
-        // Here we fall back to the auto-pathvar behavior.
+        // Here we fall back to the auto-pathvar behavior. Maps are never path variables.
         let pathvar;
-        if query.has_pathvar_unpathvar {
+        if query.map {
+            pathvar = false;
+        } else if query.has_pathvar_unpathvar {
             pathvar = query.pathvar;
         } else if let Some(v) = oldvar {
             pathvar = v.is_pathvar();
@@ -1026,11 +1059,19 @@ fn set_universal(&mut self, key: &wstr, mut val: Vec<WString>, query: Query) {
             val = colon_split(&val);
         }
 
+        // A secret variable stays secret.
+        let secret = query.secret || oldvar.is_some_and(EnvVar::is_secret);
+
         // Construct and set the new variable.
         let mut varflags = EnvVarFlags::empty();
         varflags.set(EnvVarFlags::EXPORT, exports);
         varflags.set(EnvVarFlags::PATHVAR, pathvar);
-        let new_var = EnvVar::new_vec(val, varflags);
+        varflags.set(EnvVarFlags::SECRET, secret);
+        let new_var = if query.map {
+            EnvVar::new_map(val, varflags)
+        } else {
+            EnvVar::new_vec(val, varflags)
+        };
 
         locked_uvars.set(key, new_var);
     }
@@ -1048,7 +1089,9 @@ fn set_in_node(node: &mut EnvNodeRef, key: &wstr, mut val: Vec<WString>, flags:
         };
 
         // Pathvar is inferred from the name. If set, split our entry about colons.
+        // Maps are never path variables.
         let res_pathvar = match flags.pathvar {
+            _ if flags.map => false,
             Some(pathvar) => pathvar,
             None => variable_should_auto_pathvar(key),
         };
@@ -1056,10 +1099,16 @@ fn set_in_node(node: &mut EnvNodeRef, key: &wstr, mut val: Vec<WString>, flags:
             val = colon_split(&val);
         }
 
-        *var = var
-            .setting_vals(val)
+        let var_with_vals = if flags.map {
+            var.setting_map(val)
+        } else {
+            var.setting_vals(val)
+        };
+        let res_secret = flags.secret || var.is_secret();
+        *var = var_with_vals
             .setting_exports(res_exports)
-            .setting_pathvar(res_pathvar);
+            .setting_pathvar(res_pathvar)
+            .setting_secret(res_secret);
 
         // Perhaps mark that this node contains an exported variable, or shadows an exported variable.
         // If so regenerate the export list.
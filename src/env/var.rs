@@ -11,6 +11,12 @@
 pub const PATH_ARRAY_SEP: char = ':';
 pub const NONPATH_ARRAY_SEP: char = ' ';
 
+/// Given the text after the opening bracket of a map index like `NAME[KEY]`, return the length of
+/// the key. It ends at the first closing bracket, so keys can't contain one.
+pub fn map_key_len(s: &wstr) -> Option<usize> {
+    s.chars().position(|c| c == ']')
+}
+
 bitflags! {
     /// Flags that may be passed as the 'mode' in env_stack_t::set() / environment_t::get().
     /// The default is empty.
@@ -32,6 +38,10 @@ pub struct EnvMode: u16 {
         const PATHVAR = 1 << 6;
         /// Flag to unmark a variable as a path variable.
         const UNPATHVAR = 1 << 7;
+        /// Flag to make the variable a map. The values are then given as alternating keys and values.
+        const MAP = 1 << 8;
+        /// Flag to mark a variable as secret, so its value is not shown.
+        const SECRET = 1 << 9;
     }
 }
 
@@ -114,6 +124,8 @@ pub struct EnvVarFlags: u8 {
         const EXPORT = 1 << 0;    // whether the variable is exported
         const READ_ONLY = 1 << 1; // whether the variable is read only
         const PATHVAR = 1 << 2;   // whether the variable is a path variable
+        const MAP = 1 << 3;       // whether the variable maps keys to values
+        const SECRET = 1 << 4;    // whether the value is hidden from listings and traces
     }
 }
 
@@ -124,6 +136,8 @@ pub struct EnvVar {
     /// The list of values in this variable.
     /// Arc allows for cheap copying
     values: Arc<[WString]>,
+    /// For maps, the key of each value, in insertion order. Empty for lists.
+    keys: Arc<[WString]>,
     /// The variable's flags.
     flags: EnvVarFlags,
 }
@@ -137,6 +151,7 @@ fn default() -> Self {
 
         EnvVar {
             values: Arc::clone(empty_list),
+            keys: Arc::clone(empty_list),
             flags: EnvVarFlags::empty(),
         }
     }
@@ -152,7 +167,32 @@ pub fn new(value: WString, flags: EnvVarFlags) -> Self {
     pub fn new_vec(values: Vec<WString>, flags: EnvVarFlags) -> Self {
         EnvVar {
             values: values.into(),
-            flags,
+            flags: flags - EnvVarFlags::MAP,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a new map `EnvVar` from alternating keys and values.
+    /// A key which appears more than once keeps its first position, but takes its last value.
+    pub fn new_map(pairs: Vec<WString>, flags: EnvVarFlags) -> Self {
+        let mut keys: Vec<WString> = vec![];
+        let mut values: Vec<WString> = vec![];
+        let mut positions = HashMap::new();
+        let mut pairs = pairs.into_iter();
+        while let Some(key) = pairs.next() {
+            let value = pairs.next().unwrap_or_default();
+            if let Some(&idx) = positions.get(&key) {
+                values[idx] = value;
+            } else {
+                positions.insert(key.clone(), keys.len());
+                keys.push(key);
+                values.push(value);
+            }
+        }
+        EnvVar {
+            values: values.into(),
+            keys: keys.into(),
+            flags: flags | EnvVarFlags::MAP,
         }
     }
 
@@ -181,6 +221,16 @@ pub fn is_pathvar(&self) -> bool {
         self.flags.contains(EnvVarFlags::PATHVAR)
     }
 
+    /// Returns whether the variable is a map.
+    pub fn is_map(&self) -> bool {
+        self.flags.contains(EnvVarFlags::MAP)
+    }
+
+    /// Returns whether the variable is secret.
+    pub fn is_secret(&self) -> bool {
+        self.flags.contains(EnvVarFlags::SECRET)
+    }
+
     /// Returns whether the variable is read-only.
     pub fn is_read_only(&self) -> bool {
         self.flags.contains(EnvVarFlags::READ_ONLY)
@@ -201,6 +251,27 @@ pub fn as_list(&self) -> &[WString] {
         &self.values
     }
 
+    /// Returns the keys of a map, in the same order as the values. Lists have no keys.
+    pub fn map_keys(&self) -> &[WString] {
+        &self.keys
+    }
+
+    /// Returns the value of `key` in a map.
+    pub fn map_get(&self, key: &wstr) -> Option<&WString> {
+        let idx = self.keys.iter().position(|k| k == key)?;
+        Some(&self.values[idx])
+    }
+
+    /// Returns the entries of a map as alternating keys and values, as accepted by `new_map`.
+    pub fn map_pairs(&self) -> Vec<WString> {
+        let mut result = Vec::with_capacity(2 * self.keys.len());
+        for (key, value) in self.keys.iter().zip(self.values.iter()) {
+            result.push(key.clone());
+            result.push(value.clone());
+        }
+        result
+    }
+
     /// Returns the delimiter character used when converting from a list to a string.
     pub fn get_delimiter(&self) -> char {
         if self.is_pathvar() {
@@ -210,12 +281,14 @@ pub fn get_delimiter(&self) -> char {
         }
     }
 
-    /// Returns a copy of the variable with new values.
+    /// Returns a copy of the variable with new values. This turns a map into a list.
     pub fn setting_vals(&self, values: Vec<WString>) -> Self {
-        EnvVar {
-            values: values.into(),
-            flags: self.flags,
-        }
+        Self::new_vec(values, self.flags)
+    }
+
+    /// Returns a copy of the variable with new map entries, given as alternating keys and values.
+    pub fn setting_map(&self, pairs: Vec<WString>) -> Self {
+        Self::new_map(pairs, self.flags)
     }
 
     /// Returns a copy of the variable with the export flag changed.
@@ -224,6 +297,7 @@ pub fn setting_exports(&self, export: bool) -> Self {
         flags.set(EnvVarFlags::EXPORT, export);
         EnvVar {
             values: self.values.clone(),
+            keys: self.keys.clone(),
             flags,
         }
     }
@@ -234,6 +308,18 @@ pub fn setting_pathvar(&self, pathvar: bool) -> Self {
         flags.set(EnvVarFlags::PATHVAR, pathvar);
         EnvVar {
             values: self.values.clone(),
+            keys: self.keys.clone(),
+            flags,
+        }
+    }
+
+    /// Returns a copy of the variable with the secret flag changed.
+    pub fn setting_secret(&self, secret: bool) -> Self {
+        let mut flags = self.flags;
+        flags.set(EnvVarFlags::SECRET, secret);
+        EnvVar {
+            values: self.values.clone(),
+            keys: self.keys.clone(),
             flags,
         }
     }
@@ -384,4 +470,22 @@ fn test_env_vars() {
         assert_ne!(v1, v3);
         assert_ne!(v1, v4);
     }
+
+    #[test]
+    fn test_map_vars() {
+        let pairs = |strs: &[&str]| strs.iter().map(|s| WString::from(*s)).collect::<Vec<_>>();
+        let map = EnvVar::new_map(pairs(&["a", "1", "b", "2", "a", "3"]), EnvVarFlags::empty());
+        assert!(map.is_map());
+        assert_eq!(map.map_keys(), pairs(&["a", "b"]));
+        assert_eq!(map.as_list(), pairs(&["3", "2"]));
+        assert_eq!(map.map_get(L!("b")), Some(&WString::from("2")));
+        assert_eq!(map.map_get(L!("c")), None);
+        assert_eq!(map.map_pairs(), pairs(&["a", "3", "b", "2"]));
+
+        // Setting plain values turns a map into a list.
+        let list = map.setting_vals(pairs(&["x"]));
+        assert!(!list.is_map());
+        assert!(list.map_keys().is_empty());
+        assert!(map.setting_exports(true).is_map());
+    }
 }
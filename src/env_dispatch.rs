@@ -6,6 +6,7 @@
 use crate::input_common::{update_wait_on_escape_ms, update_wait_on_sequence_key_ms};
 use crate::locale::{invalidate_numeric_locale, set_libc_locales};
 use crate::prelude::*;
+use crate::proc::is_interactive_session;
 use crate::reader::{
     reader_change_cursor_end_mode, reader_change_cursor_selection_mode, reader_change_history,
     reader_current_data, reader_schedule_prompt_repaint, reader_set_autosuggestion_enabled,
@@ -82,6 +83,8 @@ macro_rules! vars {
         table.add_anon(L!("fish_function_path"), vars!(handle_function_path_change));
         table.add_anon(L!("fish_read_limit"), vars!(handle_read_limit_change));
         table.add_anon(L!("fish_history"), vars!(handle_fish_history_change));
+        table.add_anon(L!("PWD"), vars!(handle_pwd_change));
+        table.add_anon(L!("PATH"), vars!(handle_path_change));
         table.add_anon(
             L!("fish_autosuggestion_enabled"),
             vars!(handle_autosuggestion_change),
@@ -276,6 +279,21 @@ fn handle_fish_history_change(vars: &EnvStack) {
     reader_change_history(&session_id);
 }
 
+fn handle_path_change(_: &EnvStack) {
+    crate::path::invalidate_command_hash();
+}
+
+fn handle_pwd_change(vars: &EnvStack) {
+    // Only directories visited in interactive sessions feed `cd --jump`, so scripts that change
+    // directories a lot neither pollute the database nor slow down.
+    if !is_interactive_session() {
+        return;
+    }
+    if let Some(pwd) = vars.get(L!("PWD")) {
+        crate::frecency::frecency_add(vars, &pwd.as_string());
+    }
+}
+
 fn handle_fish_cursor_selection_mode_change(vars: &EnvStack) {
     use crate::reader::CursorSelectionMode;
 
@@ -325,10 +325,15 @@ fn serialize_with_vars(vars: &VarTable) -> Vec<u8> {
         for (key, var) in cloned {
             // Append the entry. Note that append_file_entry may fail, but that only affects one
             // variable; soldier on.
+            let vals = if var.is_map() {
+                &var.map_pairs()[..]
+            } else {
+                var.as_list()
+            };
             append_file_entry(
                 var.get_flags(),
                 key,
-                &encode_serialized(var.as_list()),
+                &encode_serialized(vals),
                 &mut contents,
             );
         }
@@ -531,7 +536,12 @@ fn populate_1_variable(
             return false;
         };
         *storage = unescaped;
-        let var = EnvVar::new_vec(decode_serialized(&*storage), flags);
+        let vals = decode_serialized(&*storage);
+        let var = if flags.contains(EnvVarFlags::MAP) {
+            EnvVar::new_map(vals, flags)
+        } else {
+            EnvVar::new_vec(vals, flags)
+        };
 
         // Parse out the key and write into the map.
         *storage = s[..colon].to_owned();
@@ -562,6 +572,10 @@ fn parse_message_30_internal(msg: &wstr, vars: &mut VarTable, storage: &mut WStr
                 flags |= EnvVarFlags::EXPORT;
             } else if r#match(&mut cursor, f3::PATH) {
                 flags |= EnvVarFlags::PATHVAR;
+            } else if r#match(&mut cursor, f3::MAP) {
+                flags |= EnvVarFlags::MAP;
+            } else if r#match(&mut cursor, f3::SECRET) {
+                flags |= EnvVarFlags::SECRET;
             } else {
                 // Skip this unknown flag, for future proofing.
                 while !cursor.is_empty() && !matches!(cursor.char_at(0), ' ' | '\t') {
@@ -653,6 +667,10 @@ mod fish3_uvars {
     pub const SETUVAR: &[u8] = b"SETUVAR";
     pub const EXPORT: &[u8] = b"--export";
     pub const PATH: &[u8] = b"--path";
+    /// Maps store alternating keys and values. Older versions skip this flag and see a list.
+    pub const MAP: &[u8] = b"--map";
+    /// Secret variables are only stored when set with `set --universal --secret`.
+    pub const SECRET: &[u8] = b"--secret";
 }
 
 /// Return the default variable path, or an empty string on failure.
@@ -742,6 +760,14 @@ fn append_file_entry(
         result.extend_from_slice(f3::PATH);
         result.push(b' ');
     }
+    if flags.contains(EnvVarFlags::MAP) {
+        result.extend_from_slice(f3::MAP);
+        result.push(b' ');
+    }
+    if flags.contains(EnvVarFlags::SECRET) {
+        result.extend_from_slice(f3::SECRET);
+        result.push(b' ');
+    }
 
     // Append variable name like "fish_color_cwd".
     if !valid_var_name(key_in) {
@@ -932,6 +958,25 @@ fn test_universal_output() {
                 EnvVarFlags::empty(),
             ),
         );
+        vars.insert(
+            L!("varG").to_owned(),
+            EnvVar::new_map(
+                vec![
+                    L!("KeyG1").to_owned(),
+                    L!("ValG1").to_owned(),
+                    L!("KeyG2").to_owned(),
+                    L!("ValG2").to_owned(),
+                ],
+                EnvVarFlags::empty(),
+            ),
+        );
+        vars.insert(
+            L!("varH").to_owned(),
+            EnvVar::new_vec(
+                vec![L!("ValH1").to_owned()],
+                flag_export | EnvVarFlags::SECRET,
+            ),
+        );
 
         let text = EnvUniversal::serialize_with_vars(&vars);
         let expected = concat!(
@@ -943,6 +988,8 @@ fn test_universal_output() {
             "SETUVAR --export --path varD:ValD1\n",
             "SETUVAR --path varE:ValE1\\x1eValE2\n",
             "SETUVAR varF:\\xfc\n",
+            "SETUVAR --map varG:KeyG1\\x1eValG1\\x1eKeyG2\\x1eValG2\n",
+            "SETUVAR --export --secret varH:ValH1\n",
         )
         .as_bytes();
         assert_eq!(text, expected);
@@ -960,6 +1007,8 @@ fn test_universal_parsing() {
             "SETUVAR --nonsenseflag varC:ValC1\n",
             "SETUVAR --export --path varD:ValD1\n",
             "SETUVAR --path --path varE:ValE1\\x1eValE2\n",
+            "SETUVAR --map varF:KeyF1\\x1eValF1\\x1eKeyF2\\x1eValF2\n",
+            "SETUVAR --secret varG:ValG1\n",
         )
         .as_bytes();
 
@@ -994,6 +1043,22 @@ fn test_universal_parsing() {
                 flag_pathvar,
             ),
         );
+        vars.insert(
+            L!("varF").to_owned(),
+            EnvVar::new_map(
+                vec![
+                    L!("KeyF1").to_owned(),
+                    L!("ValF1").to_owned(),
+                    L!("KeyF2").to_owned(),
+                    L!("ValF2").to_owned(),
+                ],
+                EnvVarFlags::empty(),
+            ),
+        );
+        vars.insert(
+            L!("varG").to_owned(),
+            EnvVar::new_vec(vec![L!("ValG1").to_owned()], EnvVarFlags::SECRET),
+        );
 
         let mut parsed_vars = VarTable::new();
         EnvUniversal::populate_variables(input, &mut parsed_vars);
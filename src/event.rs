@@ -4,10 +4,12 @@
 //! defined when these functions produce output or perform memory allocations, since such functions
 //! may not be safely called by signal handlers.
 
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 use crate::common::{ScopeGuard, escape, str2wcstring};
+use crate::fd_monitor::FdEventSignaller;
 use crate::flog::flog;
 use crate::io::{IoChain, IoStreams};
 use crate::job_group::MaybeJobId;
@@ -16,6 +18,7 @@
 use crate::proc::Pid;
 use crate::reader::reader_update_termsize;
 use crate::signal::{Signal, signal_check_cancel, signal_handle};
+use crate::threads;
 
 pub enum EventType {
     Any,
@@ -25,6 +28,10 @@ pub enum EventType {
     JobExit,
     CallerExit,
     Generic,
+    Timer,
+    JobStart,
+    JobStop,
+    JobContinue,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -59,18 +66,50 @@ pub enum EventDescription {
         /// The parameter describing this generic event.
         param: WString,
     },
+    /// An event triggered by a timer, either once (--on-timeout) or repeatedly (--on-timer).
+    Timer {
+        /// The duration as given by the user, e.g. "30s".
+        spec: WString,
+        /// The parsed duration.
+        interval: Duration,
+        /// Whether the timer fires every `interval`, rather than once.
+        repeat: bool,
+        /// Unique id of this timer, used to match it up with the timer thread.
+        id: u64,
+    },
+    /// An event triggered when a job is started.
+    JobStart,
+    /// An event triggered when a job is stopped, e.g. by SIGTSTP.
+    JobStop,
+    /// An event triggered when a stopped job is continued.
+    JobContinue,
 }
 
 impl EventDescription {
+    /// Create a description for a new timer, with a fresh id.
+    pub fn timer(spec: WString, interval: Duration, repeat: bool) -> Self {
+        static NEXT_TIMER_ID: AtomicU64 = AtomicU64::new(1);
+        EventDescription::Timer {
+            spec,
+            interval,
+            repeat,
+            id: NEXT_TIMER_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
     fn str_param1(&self) -> Option<&wstr> {
         match self {
             EventDescription::Any
             | EventDescription::Signal { .. }
             | EventDescription::ProcessExit { .. }
             | EventDescription::JobExit { .. }
-            | EventDescription::CallerExit { .. } => None,
+            | EventDescription::CallerExit { .. }
+            | EventDescription::JobStart
+            | EventDescription::JobStop
+            | EventDescription::JobContinue => None,
             EventDescription::Variable { name } => Some(name),
             EventDescription::Generic { param } => Some(param),
+            EventDescription::Timer { spec, .. } => Some(spec),
         }
     }
 
@@ -83,6 +122,11 @@ fn name(&self) -> &'static wstr {
             EventDescription::JobExit { .. } => L!("job-exit"),
             EventDescription::CallerExit { .. } => L!("caller-exit"),
             EventDescription::Generic { .. } => L!("generic"),
+            EventDescription::Timer { repeat: true, .. } => L!("timer"),
+            EventDescription::Timer { repeat: false, .. } => L!("timeout"),
+            EventDescription::JobStart => L!("job-start"),
+            EventDescription::JobStop => L!("job-stop"),
+            EventDescription::JobContinue => L!("job-continue"),
         }
     }
 
@@ -115,6 +159,10 @@ fn from(desc: &EventDescription) -> Self {
             EventDescription::JobExit { .. } => EventType::JobExit,
             EventDescription::CallerExit { .. } => EventType::CallerExit,
             EventDescription::Generic { .. } => EventType::Generic,
+            EventDescription::Timer { .. } => EventType::Timer,
+            EventDescription::JobStart => EventType::JobStart,
+            EventDescription::JobStop => EventType::JobStop,
+            EventDescription::JobContinue => EventType::JobContinue,
         }
     }
 }
@@ -148,9 +196,13 @@ fn is_one_shot(&self) -> bool {
             EventDescription::ProcessExit { pid } => pid.is_some(),
             EventDescription::JobExit { pid, .. } => pid.is_some(),
             EventDescription::CallerExit { .. } => true,
+            EventDescription::Timer { repeat, .. } => !repeat,
             EventDescription::Signal { .. }
             | EventDescription::Variable { .. }
             | EventDescription::Generic { .. }
+            | EventDescription::JobStart
+            | EventDescription::JobStop
+            | EventDescription::JobContinue
             | EventDescription::Any => false,
         }
     }
@@ -190,6 +242,12 @@ fn matches(&self, event: &Event) -> bool {
                 EventDescription::Generic { param },
                 EventDescription::Generic { param: ev_param },
             ) => param == ev_param,
+            (EventDescription::Timer { id, .. }, EventDescription::Timer { id: ev_id, .. }) => {
+                id == ev_id
+            }
+            (EventDescription::JobStart, EventDescription::JobStart)
+            | (EventDescription::JobStop, EventDescription::JobStop)
+            | (EventDescription::JobContinue, EventDescription::JobContinue) => true,
             (_, _) => false,
         }
     }
@@ -262,6 +320,41 @@ pub fn caller_exit(internal_job_id: u64, job_id: MaybeJobId) -> Self {
         }
     }
 
+    pub fn job_start(job_id: MaybeJobId, pgid: Option<Pid>, command: &wstr) -> Self {
+        Self::job_state(EventDescription::JobStart, job_id, pgid, command)
+    }
+
+    pub fn job_stop(job_id: MaybeJobId, pgid: Option<Pid>, command: &wstr) -> Self {
+        Self::job_state(EventDescription::JobStop, job_id, pgid, command)
+    }
+
+    pub fn job_continue(job_id: MaybeJobId, pgid: Option<Pid>, command: &wstr) -> Self {
+        Self::job_state(EventDescription::JobContinue, job_id, pgid, command)
+    }
+
+    fn job_state(
+        desc: EventDescription,
+        job_id: MaybeJobId,
+        pgid: Option<Pid>,
+        command: &wstr,
+    ) -> Self {
+        let name = match desc {
+            EventDescription::JobStart => L!("JOB_START"),
+            EventDescription::JobStop => L!("JOB_STOP"),
+            EventDescription::JobContinue => L!("JOB_CONTINUE"),
+            _ => unreachable!(),
+        };
+        Self {
+            desc,
+            arguments: vec![
+                name.to_owned(),
+                job_id.to_wstring(),
+                pgid.map_or_else(|| L!("-").to_owned(), |pgid| pgid.to_wstring()),
+                command.to_owned(),
+            ],
+        }
+    }
+
     /// Test if specified event is blocked.
     fn is_blocked(&self, parser: &Parser) -> bool {
         for block in parser.blocks_iter_rev() {
@@ -399,6 +492,17 @@ pub fn get_desc(parser: &Parser, evt: &Event) -> WString {
             "exit handler for command substitution caller".to_string()
         }
         EventDescription::Generic { param } => format!("handler for generic event '{param}'"),
+        EventDescription::Timer {
+            spec, repeat: true, ..
+        } => format!("timer handler every {spec}"),
+        EventDescription::Timer {
+            spec,
+            repeat: false,
+            ..
+        } => format!("timeout handler after {spec}"),
+        EventDescription::JobStart => "start handler for any job".to_string(),
+        EventDescription::JobStop => "stop handler for any job".to_string(),
+        EventDescription::JobContinue => "continue handler for any job".to_string(),
         EventDescription::Any => unreachable!(),
     };
 
@@ -407,9 +511,18 @@ pub fn get_desc(parser: &Parser, evt: &Event) -> WString {
 
 /// Add an event handler.
 pub fn add_handler(eh: EventHandler) {
-    if let EventDescription::Signal { signal } = eh.desc {
-        signal_handle(signal);
-        inc_signal_observed(signal);
+    match eh.desc {
+        EventDescription::Signal { signal } => {
+            signal_handle(signal);
+            inc_signal_observed(signal);
+        }
+        EventDescription::Timer {
+            interval,
+            repeat,
+            id,
+            ..
+        } => schedule_timer(id, interval, repeat),
+        _ => (),
     }
 
     EVENT_HANDLERS
@@ -418,6 +531,122 @@ pub fn add_handler(eh: EventHandler) {
         .push(Arc::new(eh));
 }
 
+/// A pending timer, as tracked by the timer thread.
+struct ScheduledTimer {
+    id: u64,
+    deadline: Instant,
+    /// The interval to reschedule the timer with, or [`None`] if it fires only once.
+    repeat: Option<Duration>,
+}
+
+struct TimerQueue {
+    timers: Vec<ScheduledTimer>,
+    /// Whether the timer thread is running. It exits once there are no timers left.
+    thread_running: bool,
+}
+
+static TIMER_QUEUE: Mutex<TimerQueue> = Mutex::new(TimerQueue {
+    timers: Vec::new(),
+    thread_running: false,
+});
+
+/// Notified when the timer queue changes, to wake up the timer thread.
+static TIMER_QUEUE_CHANGED: Condvar = Condvar::new();
+
+/// Ids of timers which have expired, but whose handlers have not run yet.
+static EXPIRED_TIMERS: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+
+/// Set when EXPIRED_TIMERS is non-empty, so the main thread can check it without locking.
+static TIMERS_EXPIRED: AtomicBool = AtomicBool::new(false);
+
+/// Return the signaller which is posted when a timer expires. The reader waits on this, so that
+/// timer handlers run while it is idle.
+pub fn timer_signaller() -> &'static Arc<FdEventSignaller> {
+    static SIGNALLER: OnceLock<Arc<FdEventSignaller>> = OnceLock::new();
+    SIGNALLER.get_or_init(|| Arc::new(FdEventSignaller::new()))
+}
+
+fn schedule_timer(id: u64, interval: Duration, repeat: bool) {
+    let mut queue = TIMER_QUEUE.lock().expect("Mutex poisoned!");
+    queue.timers.push(ScheduledTimer {
+        id,
+        deadline: Instant::now() + interval,
+        repeat: repeat.then_some(interval),
+    });
+    if !queue.thread_running {
+        // If this fails, the next timer we schedule will try again.
+        queue.thread_running = threads::spawn(run_timer_thread);
+    }
+    TIMER_QUEUE_CHANGED.notify_one();
+}
+
+fn cancel_timer(id: u64) {
+    let mut queue = TIMER_QUEUE.lock().expect("Mutex poisoned!");
+    queue.timers.retain(|t| t.id != id);
+    TIMER_QUEUE_CHANGED.notify_one();
+}
+
+/// The body of the timer thread. This sleeps until the earliest deadline, marks the timers that
+/// have expired and wakes up the main thread to run their handlers.
+fn run_timer_thread() {
+    let mut queue = TIMER_QUEUE.lock().expect("Mutex poisoned!");
+    loop {
+        let now = Instant::now();
+        let mut expired = vec![];
+        queue.timers.retain_mut(|timer| {
+            if timer.deadline > now {
+                return true;
+            }
+            expired.push(timer.id);
+            let Some(interval) = timer.repeat else {
+                return false;
+            };
+            // If we have fallen behind, don't try to catch up with a burst of events.
+            let next = timer.deadline + interval;
+            timer.deadline = if next > now { next } else { now + interval };
+            true
+        });
+        if !expired.is_empty() {
+            EXPIRED_TIMERS
+                .lock()
+                .expect("Mutex poisoned!")
+                .extend(expired);
+            TIMERS_EXPIRED.store(true, Ordering::Release);
+            timer_signaller().post();
+        }
+
+        let Some(next) = queue.timers.iter().map(|t| t.deadline).min() else {
+            queue.thread_running = false;
+            return;
+        };
+        queue = TIMER_QUEUE_CHANGED
+            .wait_timeout(queue, next.saturating_duration_since(now))
+            .expect("Mutex poisoned!")
+            .0;
+    }
+}
+
+/// Return events for all timers which have expired since the last call.
+/// A repeating timer which expired several times in the meantime fires only once.
+fn take_expired_timers() -> Vec<Event> {
+    if !TIMERS_EXPIRED.swap(false, Ordering::Acquire) {
+        return vec![];
+    }
+    let ids = std::mem::take(&mut *EXPIRED_TIMERS.lock().expect("Mutex poisoned!"));
+    EVENT_HANDLERS
+        .lock()
+        .expect("event handler list should not be poisoned")
+        .iter()
+        .filter_map(|h| match &h.desc {
+            EventDescription::Timer { spec, id, .. } if ids.contains(id) => Some(Event {
+                desc: h.desc.clone(),
+                arguments: vec!["TIMER".into(), spec.clone()],
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
 /// Remove handlers where `pred` returns true. Simultaneously update our `signal_observed` array.
 fn remove_handlers_if(pred: impl Fn(&EventHandler) -> bool) -> usize {
     let mut handlers = EVENT_HANDLERS
@@ -429,8 +658,10 @@ fn remove_handlers_if(pred: impl Fn(&EventHandler) -> bool) -> usize {
         let handler = &handlers[i];
         if pred(handler) {
             handler.removed.store(true, Ordering::Relaxed);
-            if let EventDescription::Signal { signal } = handler.desc {
-                dec_signal_observed(signal);
+            match handler.desc {
+                EventDescription::Signal { signal } => dec_signal_observed(signal),
+                EventDescription::Timer { id, .. } => cancel_timer(id),
+                _ => (),
             }
             handlers.remove(i);
             removed += 1;
@@ -558,6 +789,9 @@ pub fn fire_delayed(parser: &Parser) {
         to_send.push(event);
     }
 
+    // Append all expired timers.
+    to_send.extend(take_expired_timers());
+
     // Fire or re-block all events. Don't obtain BLOCKED_EVENTS until we know that we have at least
     // one event that is blocked.
     let mut blocked_events = None;
@@ -592,7 +826,15 @@ pub fn fire(parser: &Parser, event: Event) {
     }
 }
 
-pub const EVENT_FILTER_NAMES: [&wstr; 7] = [
+/// Fire the handlers of any timers which have expired. This is cheap if none have, so it may be
+/// called between commands.
+pub fn fire_expired_timers(parser: &Parser) {
+    if TIMERS_EXPIRED.load(Ordering::Acquire) {
+        fire_delayed(parser);
+    }
+}
+
+pub const EVENT_FILTER_NAMES: [&wstr; 12] = [
     L!("signal"),
     L!("variable"),
     L!("exit"),
@@ -600,6 +842,11 @@ pub fn fire(parser: &Parser, event: Event) {
     L!("job-exit"),
     L!("caller-exit"),
     L!("generic"),
+    L!("timer"),
+    L!("timeout"),
+    L!("job-start"),
+    L!("job-stop"),
+    L!("job-continue"),
 ];
 
 /// Print all events. If type_filter is not empty, only output events with that type.
@@ -646,7 +893,16 @@ pub fn print(streams: &mut IoStreams, type_filter: &wstr) {
                     .out
                     .append(&sprintf!("caller-exit %s\n", evt.function_name));
             }
-            EventDescription::Variable { name: param } | EventDescription::Generic { param } => {
+            EventDescription::JobStart
+            | EventDescription::JobStop
+            | EventDescription::JobContinue => {
+                streams
+                    .out
+                    .append(&sprintf!("%s %s\n", evt.desc.name(), evt.function_name));
+            }
+            EventDescription::Variable { name: param }
+            | EventDescription::Generic { param }
+            | EventDescription::Timer { spec: param, .. } => {
                 streams
                     .out
                     .append(&sprintf!("%s %s\n", param, evt.function_name));
@@ -86,6 +86,14 @@ pub fn exec_job(parser: &Parser, job: &Job, block_io: IoChain) -> bool {
         return true;
     }
 
+    // Redirections from a previous `exec 3>log` apply to every job.
+    let block_io = parser.with_persistent_ios(block_io);
+
+    // Handle a redirection-only exec, like `exec 3>log`.
+    if is_redirection_only_exec(job) {
+        return exec_persistent_redirections(parser, job);
+    }
+
     // Handle an exec call.
     if job.processes()[0].is_exec() {
         // If we are interactive, perhaps disallow exec if there are background jobs.
@@ -485,7 +493,13 @@ fn can_use_posix_spawn_for_job(job: &Job, dup2s: &Dup2List) -> bool {
 fn internal_exec(vars: &EnvStack, is_repainting: bool, j: &Job, block_io: IoChain) {
     // Do a regular launch -  but without forking first...
     let mut all_ios = block_io;
-    if !all_ios.append_from_specs(j.processes()[0].redirection_specs(), &vars.get_pwd_slash()) {
+    // The process is replaced, so there is no point in storing named fds in variables.
+    let mut named_fds = vec![];
+    if !all_ios.append_from_specs(
+        j.processes()[0].redirection_specs(),
+        &vars.get_pwd_slash(),
+        &mut named_fds,
+    ) {
         return;
     }
 
@@ -1288,9 +1302,12 @@ fn exec_process_in_job(
 
     // Append IOs from the process's redirection specs.
     // This may fail, e.g. a failed redirection.
-    if !process_net_io_chain
-        .append_from_specs(p.redirection_specs(), &parser.vars().get_pwd_slash())
-    {
+    // Named fds are only allowed with a redirection-only exec, which is handled separately.
+    if !process_net_io_chain.append_from_specs(
+        p.redirection_specs(),
+        &parser.vars().get_pwd_slash(),
+        &mut vec![],
+    ) {
         return Err(());
     }
 
@@ -1409,6 +1426,51 @@ fn abort_pipeline_from(job: &Job, offset: usize) {
     }
 }
 
+/// Return whether the job is a plain `exec` with nothing but redirections, like `exec 3>log`.
+fn is_redirection_only_exec(job: &Job) -> bool {
+    let [p] = &job.processes()[..] else {
+        return false;
+    };
+    p.is_builtin()
+        && p.argv().len() == 1
+        && p.argv()[0] == "exec"
+        && !p.redirection_specs().is_empty()
+}
+
+/// Install the redirections of a redirection-only `exec` for all later jobs, instead of running
+/// anything. Return false if a redirection failed.
+fn exec_persistent_redirections(parser: &Parser, job: &Job) -> bool {
+    let p = &job.processes()[0];
+    let mut ios = IoChain::new();
+    let mut named_fds = vec![];
+    if !ios.append_from_specs(
+        p.redirection_specs(),
+        &parser.vars().get_pwd_slash(),
+        &mut named_fds,
+    ) {
+        p.mark_aborted_before_launch();
+        return false;
+    }
+    parser.add_persistent_ios(&ios);
+    set_named_fd_vars(parser, named_fds);
+
+    p.status.set(ProcStatus::from_exit_code(EXIT_SUCCESS));
+    job.mark_constructed();
+    run_internal_process_or_short_circuit(parser, job, p, vec![], vec![], &ios);
+    true
+}
+
+/// Store the fds allocated for redirections like {fd}>file in their variables.
+fn set_named_fd_vars(parser: &Parser, named_fds: Vec<(WString, RawFd)>) {
+    for (name, fd) in named_fds {
+        parser.set_var(
+            &name,
+            ParserEnvSetMode::new(EnvMode::empty()),
+            vec![fd.to_wstring()],
+        );
+    }
+}
+
 // Given that we are about to execute an exec() call, check if the parser is interactive and there
 // are extant background jobs. If so, warn the user and do not exec().
 // Return true if we should allow exec, false to disallow it.
@@ -12,17 +12,20 @@
     escape_string_for_double_quotes, osstr2wcstring, unescape_string, valid_var_name_char,
 };
 use crate::complete::{CompleteFlags, Completion, CompletionList, CompletionReceiver};
-use crate::env::{EnvVar, Environment};
+use crate::env::{EnvVar, Environment, map_key_len};
 use crate::exec::exec_subshell_for_expand;
 use crate::future_feature_flags::{FeatureFlag, feature_test};
 use crate::history::{History, history_session_id};
 use crate::operation_context::OperationContext;
 use crate::parse_constants::{ParseError, ParseErrorCode, ParseErrorList, SOURCE_LOCATION_UNKNOWN};
-use crate::parse_util::{MaybeParentheses, expand_variable_error, locate_cmdsubst_range};
+use crate::parse_util::{
+    MaybeParentheses, expand_variable_error, locate_cmdsubst_range, unescape_wildcards,
+};
 use crate::path::path_apply_working_directory;
 use crate::prelude::*;
 use crate::wildcard::{ANY_CHAR, ANY_STRING, ANY_STRING_RECURSIVE, WildcardResult};
-use crate::wildcard::{wildcard_expand_string, wildcard_has_internal};
+use crate::wildcard::{QUALIFIERS_BEGIN, WildcardQualifiers};
+use crate::wildcard::{wildcard_expand_string, wildcard_has_internal, wildcard_sort_by_qualifier};
 use crate::wutil::{Options, normalize_path, wcstoi_partial};
 use bitflags::bitflags;
 use fish_common::{EXPAND_RESERVED_BASE, EXPAND_RESERVED_END};
@@ -251,6 +254,20 @@ pub fn expand_to_command_and_args(
 pub fn expand_escape_variable(var: &EnvVar) -> WString {
     let mut buff = WString::new();
 
+    // Maps are shown as `[key]=value` entries.
+    if var.is_map() {
+        for (key, value) in var.map_keys().iter().zip(var.as_list()) {
+            if !buff.is_empty() {
+                buff.push(' ');
+            }
+            buff.push('[');
+            buff.push_utfstr(&escape(key));
+            buff.push_str("]=");
+            buff.push_utfstr(&escape(value));
+        }
+        return buff;
+    }
+
     let lst = var.as_list();
     for el in lst {
         if !buff.is_empty() {
@@ -426,6 +443,8 @@ enum ParseSliceError {
 /// Parse an array slicing specification Returns 0 on success. If a parse error occurs, returns the
 /// index of the bad token. Note that 0 can never be a bad index because the string always starts
 /// with [.
+///
+/// A range may have a step, like `[1..-1..2]` for every other item.
 fn parse_slice(
     input: &wstr,
     idx: &mut Vec<i64>,
@@ -513,6 +532,35 @@ fn parse_slice(
                 }
             };
 
+            // An optional positive step, like the 2 in "1..-1..2".
+            let mut step = 1;
+            while input.char_at(pos) == INTERNAL_SEPARATOR {
+                pos += 1;
+            }
+            if input.char_at(pos) == '.' && input.char_at(pos + 1) == '.' {
+                let step_pos = pos;
+                pos += 2;
+                while input.char_at(pos) == INTERNAL_SEPARATOR || input.char_at(pos).is_whitespace()
+                {
+                    pos += 1;
+                }
+                let mut consumed = 0;
+                match wcstoi_partial(&input[pos..], Options::default(), &mut consumed) {
+                    Ok(tmp) if tmp > 0 => {
+                        step = tmp;
+                        pos += consumed;
+                        // Skip trailing whitespace.
+                        pos += input[pos..]
+                            .chars()
+                            .take_while(|c| c.is_whitespace())
+                            .count();
+                    }
+                    _ => {
+                        return Err((step_pos, ParseSliceError::invalid_index));
+                    }
+                }
+            }
+
             let mut i2 = if tmp1 > -1 { tmp1 } else { size + tmp1 + 1 };
             // Skip sequences that are entirely outside.
             // This means "17..18" expands to nothing if there are less than 17 elements.
@@ -534,7 +582,11 @@ fn parse_slice(
             let mut jjj = i1;
             while jjj * direction <= i2 * direction {
                 idx.push(jjj);
-                jjj += direction;
+                // A huge step just ends the range.
+                match jjj.checked_add(direction * step) {
+                    Some(next) => jjj = next,
+                    None => break,
+                }
             }
             continue;
         }
@@ -648,8 +700,24 @@ fn expand_variables(
     let mut all_values = true;
     let slice_start = var_name_stop;
     let mut var_idx_list = vec![];
+    let mut map_key = None;
 
-    if instr.as_char_slice().get(slice_start) == Some(&'[') {
+    if instr.as_char_slice().get(slice_start) == Some(&'[')
+        && var.as_ref().is_some_and(EnvVar::is_map)
+    {
+        // Maps are indexed by a single key, which is everything up to the closing bracket.
+        all_values = false;
+        let Some(len) = map_key_len(&instr[slice_start + 1..]) else {
+            append_syntax_error!(errors, slice_start, "Invalid index value");
+            return ExpandResult::make_error(STATUS_EXPAND_ERROR);
+        };
+        let key: WString = instr[slice_start + 1..slice_start + 1 + len]
+            .chars()
+            .filter(|&c| c != INTERNAL_SEPARATOR)
+            .collect();
+        map_key = Some(key);
+        var_name_and_slice_stop = slice_start + len + 2;
+    } else if instr.as_char_slice().get(slice_start) == Some(&'[') {
         all_values = false;
         // If a variable is missing, behave as though we have one value, so that $var[1] always
         // works.
@@ -727,6 +795,10 @@ fn expand_variables(
                     var_item_list.push(item.clone());
                 }
             }
+        } else if let Some(key) = map_key {
+            if let Some(value) = var.as_ref().unwrap().map_get(&key) {
+                var_item_list.push(value.clone());
+            }
         } else {
             let all_var_items = var.as_ref().unwrap().as_list();
             for item_index in var_idx_list {
@@ -792,6 +864,170 @@ fn expand_variables(
     ExpandResult::ok()
 }
 
+/// The largest number of items a brace expansion with a range like `{1..10}` may produce, so a
+/// typo can't allocate gigabytes.
+pub const BRACE_RANGE_MAX_ITEMS: u64 = 100_000;
+
+/// A sequence expression in braces, like `{1..10}`, `{01..20..2}` or `{a..e}`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum BraceRange {
+    /// Integers. If `width` is nonzero, each one is padded with zeros to that width.
+    Int {
+        start: i64,
+        end: i64,
+        step: u64,
+        width: usize,
+    },
+    /// Characters, by code point.
+    Char { start: char, end: char, step: u64 },
+}
+
+/// Parse an optionally negative decimal integer, returning it and whether it has leading zeros.
+fn parse_range_int(s: &wstr) -> Option<(i64, bool)> {
+    let digits = s.strip_prefix(L!("-")).unwrap_or(s);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let value = s.to_string().parse().ok()?;
+    Some((value, digits.len() > 1 && digits.char_at(0) == '0'))
+}
+
+/// Parse the contents of a pair of braces as a range: `START..END` or `START..END..STEP`, where
+/// START and END are either both integers or both single ASCII letters.
+fn parse_brace_range(s: &wstr) -> Option<BraceRange> {
+    let mut parts = vec![];
+    let mut rest = s;
+    while let Some(pos) = rest.find(L!("..")) {
+        parts.push(&rest[..pos]);
+        rest = &rest[pos + 2..];
+    }
+    parts.push(rest);
+    let (start, end, step) = match parts[..] {
+        [start, end] => (start, end, None),
+        [start, end, step] => (start, end, Some(step)),
+        _ => return None,
+    };
+    // Like bash, the direction comes from the endpoints and the sign of the step is ignored.
+    // A step of 0 means 1.
+    let step = match step {
+        Some(step) => parse_range_int(step)?.0.unsigned_abs().max(1),
+        None => 1,
+    };
+    if let (Some((start_num, start_zeros)), Some((end_num, end_zeros))) =
+        (parse_range_int(start), parse_range_int(end))
+    {
+        let width = if start_zeros || end_zeros {
+            start.len().max(end.len())
+        } else {
+            0
+        };
+        return Some(BraceRange::Int {
+            start: start_num,
+            end: end_num,
+            step,
+            width,
+        });
+    }
+    match (start.as_char_slice(), end.as_char_slice()) {
+        (&[start], &[end]) if start.is_ascii_alphabetic() && end.is_ascii_alphabetic() => {
+            Some(BraceRange::Char { start, end, step })
+        }
+        _ => None,
+    }
+}
+
+/// The number of items the top-level brace ranges in `s` multiply to, e.g. 6 for `{1..2}x{a..c}`.
+/// Lists of alternatives and nested braces are not counted.
+fn brace_range_product(s: &wstr) -> u64 {
+    let mut product: u64 = 1;
+    let mut depth = 0;
+    let mut begin = 0;
+    for (pos, c) in s.chars().enumerate() {
+        match c {
+            BRACE_BEGIN => {
+                if depth == 0 {
+                    begin = pos + 1;
+                }
+                depth += 1;
+            }
+            BRACE_END if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    if let Some(range) = parse_brace_range(&s[begin..pos]) {
+                        product = product.saturating_mul(range.len());
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    product
+}
+
+/// Return whether the contents of a pair of braces form a range like `1..10`.
+pub fn is_brace_range(s: &wstr) -> bool {
+    parse_brace_range(s).is_some()
+}
+
+impl BraceRange {
+    /// The number of items in the range.
+    fn len(&self) -> u64 {
+        let (distance, step) = match *self {
+            BraceRange::Int {
+                start, end, step, ..
+            } => (start.abs_diff(end), step),
+            BraceRange::Char { start, end, step } => {
+                (u64::from(u32::from(start).abs_diff(u32::from(end))), step)
+            }
+        };
+        distance / step + 1
+    }
+
+    /// The items of the range, in order.
+    fn items(&self) -> Vec<WString> {
+        let count = self.len();
+        let mut result = Vec::with_capacity(usize::try_from(count).unwrap_or_default());
+        match *self {
+            BraceRange::Int {
+                start,
+                end,
+                step,
+                width,
+            } => {
+                let mut value = i128::from(start);
+                let step = if start <= end {
+                    i128::from(step)
+                } else {
+                    -i128::from(step)
+                };
+                for _ in 0..count {
+                    // Pad after the sign, so -5 with a width of 3 becomes -05.
+                    let digits = value.unsigned_abs().to_string();
+                    let item = if value < 0 {
+                        format!("-{digits:0>0$}", width.saturating_sub(1))
+                    } else {
+                        format!("{digits:0>width$}")
+                    };
+                    result.push(WString::from_str(&item));
+                    value += step;
+                }
+            }
+            BraceRange::Char { start, end, step } => {
+                let mut value = i64::from(u32::from(start));
+                let step = i64::try_from(step).unwrap_or(i64::MAX);
+                let step = if start <= end { step } else { -step };
+                for _ in 0..count {
+                    // Both ends are ASCII, so everything in between is a valid char.
+                    let c = char::from_u32(u32::try_from(value).unwrap()).unwrap();
+                    result.push(WString::from_chars([c]));
+                    value += step;
+                }
+            }
+        }
+        result
+    }
+}
+
 /// Perform brace expansion, placing the expanded strings into `out`.
 fn expand_braces(
     input: WString,
@@ -870,6 +1106,44 @@ fn expand_braces(
     };
     let brace_end = brace_end.unwrap();
 
+    // A range like `{1..10}` produces its items instead of a list of alternatives.
+    if let Some(range) = parse_brace_range(&input[brace_begin + 1..brace_end]) {
+        // Count everything this expansion produces, so `{1..1000}{1..1000}` or several
+        // alternatives each with a range can't get around the limit.
+        let total = u64::try_from(out.size())
+            .unwrap_or(u64::MAX)
+            .saturating_add(
+                range
+                    .len()
+                    .saturating_mul(brace_range_product(&input[brace_end + 1..])),
+            );
+        if total > BRACE_RANGE_MAX_ITEMS {
+            // Completions just don't expand it, there is no one to show an error to.
+            if flags.contains(ExpandFlags::FOR_COMPLETIONS) {
+                return ExpandResult::ok();
+            }
+            append_syntax_error!(
+                errors,
+                SOURCE_LOCATION_UNKNOWN,
+                "Brace range '%s' makes the expansion produce %d items, more than the limit of %d",
+                &input[brace_begin + 1..brace_end],
+                total,
+                BRACE_RANGE_MAX_ITEMS
+            );
+            return ExpandResult::make_error(STATUS_EXPAND_ERROR);
+        }
+        for item in range.items() {
+            let mut whole_item = input[..brace_begin].to_owned();
+            whole_item.push_utfstr(&item);
+            whole_item.push_utfstr(&input[brace_end + 1..]);
+            let result = expand_braces(whole_item, flags, out, errors);
+            if result.result != ExpandResultCode::ok {
+                return result;
+            }
+        }
+        return ExpandResult::ok();
+    }
+
     let length_preceding_braces = brace_begin;
     let length_following_braces = input.len() - brace_end - 1;
     let tot_len = length_preceding_braces + length_following_braces;
@@ -896,7 +1170,10 @@ fn expand_braces(
             whole_item.push_utfstr(&input[..length_preceding_braces]);
             whole_item.push_utfstr(&item);
             whole_item.push_utfstr(&input[brace_end + 1..]);
-            let _ = expand_braces(whole_item, flags, out, errors);
+            let result = expand_braces(whole_item, flags, out, errors);
+            if result.result != ExpandResultCode::ok {
+                return result;
+            }
 
             item_begin = pos + 1;
             if pos == brace_end {
@@ -1188,6 +1465,9 @@ fn remove_internal_separator(s: &mut WString, conv: bool) {
                 ANY_STRING | ANY_STRING_RECURSIVE => {
                     *idx = '*';
                 }
+                QUALIFIERS_BEGIN => {
+                    *idx = '[';
+                }
                 _ => {
                     // we ignore all other characters
                 }
@@ -1394,10 +1674,50 @@ fn stage_wildcards(
     ) -> ExpandResult {
         let mut result = ExpandResult::ok();
 
+        let mut path_to_expand = path_to_expand;
         let has_wildcard = wildcard_has_internal(&path_to_expand); // e.g. ANY_STRING
         let for_completions = self.flags.contains(ExpandFlags::FOR_COMPLETIONS);
         let skip_wildcards = self.flags.contains(ExpandFlags::SKIP_WILDCARDS);
 
+        // Split off qualifiers like `[file]`. Completions and commands treat them as literal text.
+        let mut qualifiers = None;
+        if has_wildcard && !self.flags.contains(ExpandFlags::EXECUTABLES_ONLY) {
+            if !for_completions {
+                let (wc, parsed) = WildcardQualifiers::split(&path_to_expand);
+                if let Some(mut parsed) = parsed {
+                    if let Err(qualifier) = parsed.resolve_owners() {
+                        append_syntax_error!(
+                            self.errors,
+                            SOURCE_LOCATION_UNKNOWN,
+                            "Unknown user or group in wildcard qualifier '%s'",
+                            qualifier
+                        );
+                        return ExpandResult::make_error(STATUS_EXPAND_ERROR);
+                    }
+                    path_to_expand = wc.to_owned();
+                    qualifiers = Some(parsed);
+                }
+            }
+            // Recursive wildcards skip the names in $fish_glob_ignore, for completions too.
+            if path_to_expand.contains(ANY_STRING_RECURSIVE) {
+                if let Some(ignore) = self.ctx.vars().get(L!("fish_glob_ignore")) {
+                    qualifiers
+                        .get_or_insert_with(WildcardQualifiers::default)
+                        .ignore_patterns = ignore
+                        .as_list()
+                        .iter()
+                        .filter(|pattern| !pattern.is_empty())
+                        .map(|pattern| unescape_wildcards(pattern))
+                        .collect();
+                }
+            }
+        }
+        for c in path_to_expand.as_char_slice_mut() {
+            if *c == QUALIFIERS_BEGIN {
+                *c = '[';
+            }
+        }
+
         if has_wildcard && self.flags.contains(ExpandFlags::EXECUTABLES_ONLY) {
             // don't do wildcard expansion for executables, see issue #785
         } else if (for_completions && !skip_wildcards) || has_wildcard {
@@ -1463,6 +1783,7 @@ fn stage_wildcards(
                     &path_to_expand,
                     &effective_working_dir,
                     self.flags,
+                    qualifiers.as_ref(),
                     &*self.ctx.cancel_checker,
                     &mut expanded_recv,
                 );
@@ -1476,6 +1797,10 @@ fn stage_wildcards(
 
             let mut expanded = expanded_recv.take();
             expanded.sort_by(|a, b| wcsfilecmp_glob(&a.completion, &b.completion));
+            if let Some((key, descending)) = qualifiers.as_ref().and_then(|q| q.sort) {
+                let working_dir = self.ctx.vars().get_pwd_slash();
+                wildcard_sort_by_qualifier(&mut expanded, &working_dir, key, descending);
+            }
             if !out.extend(expanded) {
                 result = ExpandResult::new(ExpandResultCode::overflow);
             }
@@ -1673,6 +1998,18 @@ macro_rules! expand_test {
             ("abe", "ace", "ade"),
             "Bracket expansion is broken"
         );
+        expand_test!(
+            "a{08..11..3}e",
+            noflags,
+            ("a08e", "a11e"),
+            "Brace ranges are broken"
+        );
+        expand_test!(
+            "{1..1000000}",
+            ExpandFlags::FOR_COMPLETIONS,
+            (),
+            "Huge brace ranges are expanded for completions"
+        );
         expand_test!(
             "a*",
             ExpandFlags::SKIP_WILDCARDS,
@@ -0,0 +1,334 @@
+//! A database of visited directories, ranked by "frecency": a mix of how often and how recently
+//! each directory was visited. This backs `cd --jump`.
+//!
+//! The database is a file in the data directory with one directory per line, in the form
+//! `RANK<tab>LAST_VISIT<tab>PATH`. Like the history file, it is rewritten via a temporary file
+//! while holding a lock, so concurrent shells do not lose each other's updates. Visits are
+//! collected in memory and written by a background thread, so changing directories does not wait
+//! for the file.
+
+use crate::common::{bytes2wcstring, wcs2bytes};
+use crate::env::Environment;
+use crate::flog;
+use crate::fs::{PotentialUpdate, lock_and_load, rewrite_via_temporary_file};
+use crate::history::in_private_mode;
+use crate::path::path_get_data;
+use crate::prelude::*;
+use crate::threads;
+use crate::wutil::{wbasename, wstat};
+use fish_widestring::subslice_position;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::sync::{Condvar, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The name of the database file in the data directory.
+const FRECENCY_FILE_NAME: &str = "dir_frecency";
+
+/// Once the ranks of all entries add up to more than this, they are scaled down, so directories
+/// that are no longer visited eventually drop out.
+const MAX_TOTAL_RANK: f64 = 9000.0;
+
+/// The factor by which ranks are scaled down when the total exceeds [`MAX_TOTAL_RANK`].
+const AGING_FACTOR: f64 = 0.99;
+
+/// Visits which have not been written to the database yet.
+struct PendingVisits {
+    /// Each visited directory with the time of the visit.
+    visits: Vec<(WString, u64)>,
+    /// Whether a background thread is currently writing visits.
+    writing: bool,
+}
+
+static PENDING: Mutex<PendingVisits> = Mutex::new(PendingVisits {
+    visits: Vec::new(),
+    writing: false,
+});
+
+/// Notified when the background thread is done writing.
+static WRITING_DONE: Condvar = Condvar::new();
+
+#[derive(Clone, Debug)]
+pub struct FrecencyEntry {
+    pub path: WString,
+    /// Roughly the number of visits, decayed by aging.
+    pub rank: f64,
+    /// Seconds since the epoch.
+    pub last_visit: u64,
+}
+
+impl FrecencyEntry {
+    /// The score of this entry at time `now`. Recent visits weigh more than old ones.
+    pub fn score(&self, now: u64) -> f64 {
+        const HOUR: u64 = 60 * 60;
+        const DAY: u64 = 24 * HOUR;
+        const WEEK: u64 = 7 * DAY;
+        let age = now.saturating_sub(self.last_visit);
+        let weight = if age < HOUR {
+            4.0
+        } else if age < DAY {
+            2.0
+        } else if age < WEEK {
+            0.5
+        } else {
+            0.25
+        };
+        self.rank * weight
+    }
+
+    /// Whether this entry matches all of `terms`, which must already be lowercased if `icase`.
+    /// The terms must appear in the path in order, and the last one must appear in the final
+    /// path component.
+    fn matches(&self, terms: &[WString], icase: bool) -> bool {
+        let fold = |s: &wstr| -> WString {
+            if icase {
+                s.chars().flat_map(char::to_lowercase).collect()
+            } else {
+                s.to_owned()
+            }
+        };
+        let path = fold(&self.path);
+        let mut rest = path.as_utfstr();
+        for term in terms {
+            let Some(pos) = subslice_position(rest.as_char_slice(), term.as_char_slice()) else {
+                return false;
+            };
+            rest = &rest[pos + term.len()..];
+        }
+        terms.last().is_none_or(|last| {
+            subslice_position(
+                fold(wbasename(&self.path)).as_char_slice(),
+                last.as_char_slice(),
+            )
+            .is_some()
+        })
+    }
+}
+
+fn frecency_file_path() -> Option<WString> {
+    let mut path = path_get_data()?;
+    path.push('/');
+    path.push_str(FRECENCY_FILE_NAME);
+    Some(path)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn parse_entries(contents: &[u8]) -> Vec<FrecencyEntry> {
+    let parse_field = |field: &[u8]| std::str::from_utf8(field).ok().map(str::to_owned);
+    contents
+        .split(|&b| b == b'\n')
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, |&b| b == b'\t');
+            let rank: f64 = parse_field(fields.next()?)?.parse().ok()?;
+            let last_visit: u64 = parse_field(fields.next()?)?.parse().ok()?;
+            let path = bytes2wcstring(fields.next()?);
+            (rank.is_finite() && rank > 0.0 && !path.is_empty()).then_some(FrecencyEntry {
+                path,
+                rank,
+                last_visit,
+            })
+        })
+        .collect()
+}
+
+fn serialize_entries(entries: &[FrecencyEntry]) -> Vec<u8> {
+    let mut out = vec![];
+    for entry in entries {
+        out.extend_from_slice(format!("{}\t{}\t", entry.rank, entry.last_visit).as_bytes());
+        out.extend_from_slice(&wcs2bytes(&entry.path));
+        out.push(b'\n');
+    }
+    out
+}
+
+fn read_entries(mut file: &File) -> std::io::Result<Vec<FrecencyEntry>> {
+    let mut contents = vec![];
+    file.read_to_end(&mut contents)?;
+    Ok(parse_entries(&contents))
+}
+
+/// Record a visit to the directory `dir`. Nothing is recorded in private mode, or for the home
+/// directory, which is easy enough to reach already.
+pub fn frecency_add(vars: &dyn Environment, dir: &wstr) {
+    if in_private_mode(vars)
+        || dir.is_empty()
+        || dir.chars().any(|c| c == '\n')
+        || vars
+            .get(L!("HOME"))
+            .is_some_and(|home| home.as_string() == dir)
+    {
+        return;
+    }
+    let mut pending = PENDING.lock().unwrap();
+    pending.visits.push((dir.to_owned(), now()));
+    if !pending.writing {
+        // If no thread can be started, the visits are written by the next call to
+        // frecency_save().
+        pending.writing = threads::spawn(write_pending_visits);
+    }
+}
+
+/// Write visits from a background thread until none are left.
+fn write_pending_visits() {
+    loop {
+        let visits = {
+            let mut pending = PENDING.lock().unwrap();
+            if pending.visits.is_empty() {
+                pending.writing = false;
+                WRITING_DONE.notify_all();
+                return;
+            }
+            std::mem::take(&mut pending.visits)
+        };
+        write_visits(&visits);
+    }
+}
+
+/// Write all visits that have not been written yet, waiting for the background thread if it is
+/// busy. This is called when fish exits.
+pub fn frecency_save() {
+    let visits = {
+        let mut pending = PENDING.lock().unwrap();
+        while pending.writing {
+            pending = WRITING_DONE.wait(pending).unwrap();
+        }
+        std::mem::take(&mut pending.visits)
+    };
+    if !visits.is_empty() {
+        write_visits(&visits);
+    }
+}
+
+/// Add `visits` to the database in a single update.
+fn write_visits(visits: &[(WString, u64)]) {
+    let Some(path) = frecency_file_path() else {
+        return;
+    };
+    let rewrite = |old_file: &File, tmp_file: &mut File| -> std::io::Result<PotentialUpdate<()>> {
+        let mut entries = read_entries(old_file)?;
+        for (dir, time) in visits {
+            match entries.iter_mut().find(|entry| entry.path == *dir) {
+                Some(entry) => {
+                    entry.rank += 1.0;
+                    entry.last_visit = *time;
+                }
+                None => entries.push(FrecencyEntry {
+                    path: dir.clone(),
+                    rank: 1.0,
+                    last_visit: *time,
+                }),
+            }
+        }
+        if entries.iter().map(|entry| entry.rank).sum::<f64>() > MAX_TOTAL_RANK {
+            for entry in &mut entries {
+                entry.rank *= AGING_FACTOR;
+            }
+            entries.retain(|entry| entry.rank >= 1.0);
+        }
+        tmp_file.write_all(&serialize_entries(&entries))?;
+        Ok(PotentialUpdate {
+            do_save: true,
+            data: (),
+        })
+    };
+    if let Err(e) = rewrite_via_temporary_file(&path, rewrite) {
+        flog!(
+            synced_file_access,
+            "Failed to update the directory frecency database:",
+            e
+        );
+    }
+}
+
+/// Return the existing directories from the database that match `terms`, best first.
+/// Matching is case-insensitive unless a term contains an uppercase character.
+pub fn frecency_query(terms: &[&wstr]) -> Vec<FrecencyEntry> {
+    // Include our own recent visits.
+    frecency_save();
+    let Some(path) = frecency_file_path() else {
+        return vec![];
+    };
+    let Ok((_, entries)) = lock_and_load(&path, |file, _| read_entries(file)) else {
+        return vec![];
+    };
+    let icase = !terms
+        .iter()
+        .any(|term| term.chars().any(char::is_uppercase));
+    let terms: Vec<WString> = terms
+        .iter()
+        .map(|term| {
+            if icase {
+                term.chars().flat_map(char::to_lowercase).collect()
+            } else {
+                (*term).to_owned()
+            }
+        })
+        .collect();
+    let now = now();
+    let mut matches: Vec<FrecencyEntry> = entries
+        .into_iter()
+        .filter(|entry| entry.matches(&terms, icase))
+        .filter(|entry| wstat(&entry.path).is_ok_and(|md| md.is_dir()))
+        .collect();
+    matches.sort_by(|a, b| b.score(now).total_cmp(&a.score(now)));
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FrecencyEntry, parse_entries, serialize_entries};
+    use crate::prelude::*;
+
+    fn entry(path: &str) -> FrecencyEntry {
+        FrecencyEntry {
+            path: WString::from_str(path),
+            rank: 1.0,
+            last_visit: 0,
+        }
+    }
+
+    #[test]
+    fn test_frecency_roundtrip() {
+        let mut entries = vec![entry("/usr/src/fish-shell"), entry("/tmp/with\ttab")];
+        entries[0].rank = 2.5;
+        entries[1].last_visit = 1700000000;
+        let parsed = parse_entries(&serialize_entries(&entries));
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].path.as_utfstr(), "/usr/src/fish-shell");
+        assert_eq!(parsed[0].rank, 2.5);
+        assert_eq!(parsed[1].path.as_utfstr(), "/tmp/with\ttab");
+        assert_eq!(parsed[1].last_visit, 1700000000);
+        assert!(parse_entries(b"garbage\n1\tnot a time\t/x\n").is_empty());
+    }
+
+    #[test]
+    fn test_frecency_matching() {
+        let terms = |terms: &[&str]| -> Vec<WString> {
+            terms.iter().map(|t| WString::from_str(t)).collect()
+        };
+        let e = entry("/home/me/Projects/fish-shell");
+        assert!(e.matches(&terms(&["fish"]), false));
+        assert!(e.matches(&terms(&["proj", "fish"]), true));
+        assert!(e.matches(&[], false));
+        // Terms must appear in order.
+        assert!(!e.matches(&terms(&["fish", "proj"]), true));
+        // The last term must match the final component.
+        assert!(!e.matches(&terms(&["proj"]), true));
+        assert!(!e.matches(&terms(&["proj"]), false));
+    }
+
+    #[test]
+    fn test_frecency_score() {
+        let mut e = entry("/tmp");
+        e.rank = 10.0;
+        e.last_visit = 1000;
+        assert_eq!(e.score(1000), 40.0);
+        assert_eq!(e.score(1000 + 2 * 60 * 60), 20.0);
+        assert_eq!(e.score(1000 + 30 * 24 * 60 * 60), 2.5);
+    }
+}
@@ -463,6 +463,27 @@ pub fn annotated_definition(&self, name: &wstr) -> WString {
                 EventDescription::Generic { param } => {
                     sprintf!(=> &mut out, " --on-event %s", param);
                 }
+                EventDescription::Timer {
+                    spec, repeat: true, ..
+                } => {
+                    sprintf!(=> &mut out, " --on-timer %s", spec);
+                }
+                EventDescription::Timer {
+                    spec,
+                    repeat: false,
+                    ..
+                } => {
+                    sprintf!(=> &mut out, " --on-timeout %s", spec);
+                }
+                EventDescription::JobStart => {
+                    out.push_str(" --on-job-start");
+                }
+                EventDescription::JobStop => {
+                    out.push_str(" --on-job-stop");
+                }
+                EventDescription::JobContinue => {
+                    out.push_str(" --on-job-continue");
+                }
                 EventDescription::Any => {
                     panic!("Unexpected event handler type");
                 }
@@ -0,0 +1,283 @@
+//! Reading `.gitignore` and `.ignore` files, for wildcards with the `gitignore` qualifier.
+//!
+//! This supports the pattern syntax of gitignore(5): comments, negation with `!`, patterns that
+//! only match directories with a trailing `/`, patterns anchored to their file's directory when
+//! they contain a `/`, and `*`, `?`, `[...]` and `**`. Global exclude files and
+//! `core.excludesFile` are not read.
+
+use crate::common::{bytes2wcstring, wcs2osstring};
+use crate::path::append_path_component;
+use crate::prelude::*;
+use crate::wutil::waccess;
+use nix::unistd::AccessFlags;
+
+/// The names of the files read in each directory. Rules in later files win.
+const IGNORE_FILE_NAMES: [&wstr; 2] = [L!(".gitignore"), L!(".ignore")];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct IgnoreRule {
+    pattern: Vec<char>,
+    /// Whether the pattern started with `!`, so matching paths are not ignored after all.
+    negated: bool,
+    /// Whether the pattern ended in `/`, so it only matches directories.
+    dir_only: bool,
+    /// Whether the pattern contains a `/`, so it matches the path relative to the directory of
+    /// the file instead of just the file name.
+    anchored: bool,
+}
+
+impl IgnoreRule {
+    /// Parse a line of an ignore file, returning None for blank lines and comments.
+    fn parse(line: &wstr) -> Option<Self> {
+        let mut line = line.as_char_slice();
+        // Trailing spaces are ignored unless escaped.
+        while let [rest @ .., ' '] = line {
+            if rest.last() == Some(&'\\') {
+                break;
+            }
+            line = rest;
+        }
+        if line.is_empty() || line[0] == '#' {
+            return None;
+        }
+        let negated = line[0] == '!';
+        if negated {
+            line = &line[1..];
+        }
+        let dir_only = line.last() == Some(&'/');
+        if dir_only {
+            line = &line[..line.len() - 1];
+        }
+        let anchored = line.contains(&'/');
+        if line.first() == Some(&'/') {
+            line = &line[1..];
+        }
+        if line.is_empty() {
+            return None;
+        }
+        Some(Self {
+            pattern: line.to_vec(),
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    /// Whether the rule matches `rel_path`, which is relative to the directory of the ignore file.
+    fn matches(&self, rel_path: &[char], is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        if self.anchored {
+            glob_match(&self.pattern, rel_path)
+        } else {
+            let name_start = rel_path
+                .iter()
+                .rposition(|&c| c == '/')
+                .map_or(0, |i| i + 1);
+            glob_match(&self.pattern, &rel_path[name_start..])
+        }
+    }
+}
+
+/// Match `s` against a gitignore glob. `*` and `?` do not match `/`, `**` does, and `**/` may also
+/// match nothing.
+fn glob_match(pattern: &[char], s: &[char]) -> bool {
+    match pattern {
+        [] => s.is_empty(),
+        ['*', '*', rest @ ..] => {
+            if let ['/', after_slash @ ..] = rest {
+                if glob_match(after_slash, s) {
+                    return true;
+                }
+            }
+            (0..=s.len()).any(|i| glob_match(rest, &s[i..]))
+        }
+        ['*', rest @ ..] => (0..=s.len())
+            .take_while(|&i| i == 0 || s[i - 1] != '/')
+            .any(|i| glob_match(rest, &s[i..])),
+        ['?', rest @ ..] => match s {
+            [c, s_rest @ ..] if *c != '/' => glob_match(rest, s_rest),
+            _ => false,
+        },
+        ['[', class @ ..] => {
+            let Some((matches_class, rest)) = match_class(class, s.first().copied()) else {
+                // An unterminated class is a literal bracket.
+                return s.first() == Some(&'[') && glob_match(class, &s[1..]);
+            };
+            matches_class && glob_match(rest, &s[1..])
+        }
+        ['\\', c, rest @ ..] => s.first() == Some(c) && glob_match(rest, &s[1..]),
+        [c, rest @ ..] => s.first() == Some(c) && glob_match(rest, &s[1..]),
+    }
+}
+
+/// Match `c` against a character class like `a-z]`, whose opening bracket has been consumed.
+/// Return whether it matched and the pattern after the class, or None if the class is not
+/// terminated.
+fn match_class(class: &[char], c: Option<char>) -> Option<(bool, &[char])> {
+    let (negated, mut class) = match class {
+        ['!' | '^', rest @ ..] => (true, rest),
+        _ => (false, class),
+    };
+    let mut matched = false;
+    let mut first = true;
+    loop {
+        match class {
+            [] => return None,
+            [']', rest @ ..] if !first => {
+                let matched = c.is_some_and(|c| c != '/' && matched != negated);
+                return Some((matched, rest));
+            }
+            [lo, '-', hi, rest @ ..] if *hi != ']' => {
+                matched |= c.is_some_and(|c| (*lo..=*hi).contains(&c));
+                class = rest;
+            }
+            [x, rest @ ..] => {
+                matched |= c == Some(*x);
+                class = rest;
+            }
+        }
+        first = false;
+    }
+}
+
+/// The rules of the ignore files in one directory.
+#[derive(Clone, Debug)]
+pub struct IgnoreFile {
+    /// The absolute path of the directory, with a trailing slash.
+    dir: WString,
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreFile {
+    /// Read the ignore files in `dir`, an absolute path. If there are none, the result has no
+    /// rules.
+    pub fn load(dir: &wstr) -> Self {
+        let mut dir = dir.to_owned();
+        if !dir.ends_with('/') {
+            dir.push('/');
+        }
+        let mut rules = vec![];
+        for name in IGNORE_FILE_NAMES {
+            let mut path = dir.clone();
+            append_path_component(&mut path, name);
+            let Ok(contents) = std::fs::read(wcs2osstring(&path)) else {
+                continue;
+            };
+            let contents = bytes2wcstring(&contents);
+            rules.extend(contents.split('\n').filter_map(IgnoreRule::parse));
+        }
+        Self { dir, rules }
+    }
+
+    pub fn dir(&self) -> &wstr {
+        &self.dir
+    }
+
+    /// Whether the file at the absolute `path` is ignored (Some(true)) or explicitly not ignored
+    /// (Some(false)) by these rules. The last matching rule decides.
+    fn check(&self, path: &wstr, is_dir: bool) -> Option<bool> {
+        let rel_path = path.strip_prefix(self.dir.as_utfstr())?;
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(rel_path.as_char_slice(), is_dir))
+            .map(|rule| !rule.negated)
+    }
+}
+
+/// Read the ignore files of the directories above `dir`, up to the root of the git repository
+/// containing it. Return them outermost first, or nothing if `dir` is not in a repository.
+pub fn load_parent_ignore_files(dir: &wstr) -> Vec<IgnoreFile> {
+    let mut result = vec![];
+    let mut dir = dir.to_owned();
+    while dir.ends_with('/') {
+        dir.pop();
+    }
+    loop {
+        if waccess(&(dir.clone() + L!("/.git")), AccessFlags::F_OK).is_ok() {
+            result.reverse();
+            return result;
+        }
+        let Some(slash) = dir.as_char_slice().iter().rposition(|&c| c == '/') else {
+            return vec![];
+        };
+        dir.truncate(slash);
+        let parent = if dir.is_empty() { L!("/") } else { &dir };
+        let file = IgnoreFile::load(parent);
+        if !file.rules.is_empty() {
+            result.push(file);
+        }
+    }
+}
+
+/// Whether the file at the absolute `path` is ignored by any of `files`, which are ordered from
+/// the outermost directory to the innermost.
+pub fn is_ignored(files: &[IgnoreFile], path: &wstr, is_dir: bool) -> bool {
+    files
+        .iter()
+        .rev()
+        .find_map(|file| file.check(path, is_dir))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IgnoreFile, IgnoreRule, glob_match, is_ignored};
+    use crate::prelude::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn test_glob_match() {
+        let matches = |pattern: &str, s: &str| glob_match(&chars(pattern), &chars(s));
+        assert!(matches("*.rs", "main.rs"));
+        assert!(!matches("*.rs", "src/main.rs"));
+        assert!(matches("**/*.rs", "src/main.rs"));
+        assert!(matches("**/*.rs", "main.rs"));
+        assert!(matches("a/**/b", "a/b"));
+        assert!(matches("a/**/b", "a/x/y/b"));
+        assert!(matches("target/**", "target/debug"));
+        assert!(matches("file?.[ch]", "file1.c"));
+        assert!(!matches("file?.[!ch]", "file1.c"));
+        assert!(matches("[a-c]x", "bx"));
+        assert!(matches("a[b", "a[b"));
+        assert!(matches("\\*", "*"));
+        assert!(!matches("\\*", "x"));
+    }
+
+    #[test]
+    fn test_ignore_rules() {
+        let file = IgnoreFile {
+            dir: L!("/repo/").to_owned(),
+            rules: [
+                "# comment",
+                "",
+                "node_modules/",
+                "/build",
+                "*.log",
+                "!keep.log",
+                "doc/*.html",
+            ]
+            .iter()
+            .filter_map(|line| IgnoreRule::parse(&WString::from_str(line)))
+            .collect(),
+        };
+        assert_eq!(file.rules.len(), 5);
+        let files = [file];
+        let ignored = |path: &str, is_dir| is_ignored(&files, &WString::from_str(path), is_dir);
+        assert!(ignored("/repo/node_modules", true));
+        assert!(ignored("/repo/web/node_modules", true));
+        assert!(!ignored("/repo/node_modules", false));
+        assert!(ignored("/repo/build", true));
+        assert!(!ignored("/repo/src/build", true));
+        assert!(ignored("/repo/src/debug.log", false));
+        assert!(!ignored("/repo/keep.log", false));
+        assert!(ignored("/repo/doc/index.html", false));
+        assert!(!ignored("/repo/doc/api/index.html", false));
+        assert!(!ignored("/other/debug.log", false));
+    }
+}
@@ -23,6 +23,7 @@
     MaybeParentheses, get_process_first_token_offset, locate_cmdsubst_range, slice_length,
 };
 use crate::path::{path_as_implicit_cd, path_get_cdpath, path_get_path, paths_are_same_file};
+use crate::suggest::suggest_commands;
 use crate::terminal::Outputter;
 use crate::text_face::{SpecifiedTextFace, TextFace, UnderlineStyle, parse_text_face};
 use crate::threads::assert_is_background_thread;
@@ -201,6 +202,10 @@ fn resolve_spec_uncached(highlight: &HighlightSpec, vars: &dyn Environment) -> T
             face.style.inject_underline(UnderlineStyle::Single);
         }
 
+        if highlight.has_suggestion {
+            face.style.inject_underline(UnderlineStyle::Curly);
+        }
+
         face
     }
 }
@@ -1061,7 +1066,17 @@ fn visit_decorated_statement(&mut self, stmt: &DecoratedStatement) {
         if is_valid_cmd {
             self.color_command(&stmt.command);
         } else {
-            self.color_node(&stmt.command, HighlightSpec::with_fg(HighlightRole::error));
+            // A misspelled command gets a hint that there is something close to it. Skip this
+            // while the command is still being typed, as it is likely just incomplete.
+            let is_prefix = self
+                .cursor
+                .is_some_and(|c| stmt.command.source_range().contains_inclusive(c));
+            let mut spec = HighlightSpec::with_fg(HighlightRole::error);
+            spec.has_suggestion = !is_prefix
+                && !expanded_cmd.is_empty()
+                && self.io_still_ok()
+                && !suggest_commands(&expanded_cmd, self.ctx.vars()).is_empty();
+            self.color_node(&stmt.command, spec);
         }
 
         // Color arguments and redirections.
@@ -1302,6 +1317,8 @@ pub struct HighlightSpec {
     pub background: HighlightRole,
     pub valid_path: bool,
     pub force_underline: bool,
+    /// Set on unknown commands that look like a misspelling of a known one.
+    pub has_suggestion: bool,
 }
 
 #[cfg(test)]
@@ -23,7 +23,7 @@
     },
     threads::ThreadPool,
 };
-use fish_wcstringutil::{subsequence_in_string, trim};
+use fish_wcstringutil::{string_prefixes_string, subsequence_in_string, trim};
 use fish_widestring::subslice_position;
 use std::{
     borrow::Cow,
@@ -1074,6 +1074,69 @@ fn string_could_be_path(potential_path: &wstr) -> bool {
     !(potential_path.is_empty() || potential_path.starts_with('-'))
 }
 
+/// What the values given to `set --secret` are replaced with in history.
+const REDACTED_VALUE: &wstr = L!("'<redacted>'");
+
+/// Return `s` with the values of every `set --secret` command replaced by [`REDACTED_VALUE`], so
+/// secrets typed on the command line do not end up in the history file.
+fn redact_secret_values(s: &wstr) -> Cow<'_, wstr> {
+    let ast = ast::parse(s, ParseTreeFlags::default(), None);
+    let mut value_ranges = Vec::new();
+    for node in ast.walk() {
+        let Kind::DecoratedStatement(stmt) = node.kind() else {
+            continue;
+        };
+        let source = stmt.command.source(s);
+        let command = unescape_string(source, UnescapeStringStyle::default());
+        if command.as_deref().unwrap_or(source) != "set" {
+            continue;
+        }
+
+        let mut is_secret = false;
+        let mut options_done = false;
+        let mut seen_name = false;
+        let mut values = Vec::new();
+        for arg in &stmt.args_or_redirs {
+            if !arg.is_argument() {
+                continue;
+            }
+            let arg = arg.argument();
+            if seen_name {
+                values.push(arg.source_range());
+                continue;
+            }
+            let source = arg.source(s);
+            let unescaped = unescape_string(source, UnescapeStringStyle::default());
+            let arg_str = unescaped.as_deref().unwrap_or(source);
+            if options_done || !arg_str.starts_with('-') || arg_str.len() == 1 {
+                seen_name = true;
+            } else if arg_str == "--" {
+                options_done = true;
+            } else if arg_str.len() >= 4 && string_prefixes_string(arg_str, L!("--secret")) {
+                // Long options may be abbreviated; "--se" is the shortest unique prefix.
+                is_secret = true;
+            }
+        }
+        if is_secret {
+            value_ranges.extend(values);
+        }
+    }
+
+    if value_ranges.is_empty() {
+        return Cow::Borrowed(s);
+    }
+    value_ranges.sort_by_key(|range| range.start());
+    let mut redacted = WString::new();
+    let mut last_end = 0;
+    for range in value_ranges {
+        redacted.push_utfstr(&s[last_end..range.start()]);
+        redacted.push_utfstr(REDACTED_VALUE);
+        last_end = range.end();
+    }
+    redacted.push_utfstr(&s[last_end..]);
+    Cow::Owned(redacted)
+}
+
 /// Perform a search of `hist` for `search_string`. Invoke a function `func` for each match. If
 /// `func` returns [`ControlFlow::Break`], stop the search.
 fn do_1_history_search(
@@ -1262,6 +1325,10 @@ pub fn add_pending_with_file_detection(
             return;
         }
 
+        // Keep the values of `set --secret` out of the history.
+        let redacted = redact_secret_values(s);
+        let s: &wstr = &redacted;
+
         // Find all arguments that look like they could be file paths.
         let mut needs_sync_write = false;
         let ast = ast::parse(s, ParseTreeFlags::default(), None);
@@ -1784,7 +1851,7 @@ pub fn in_private_mode(vars: &dyn Environment) -> bool {
 mod tests {
     use super::{
         History, HistoryItem, HistorySearch, PathList, PersistenceMode, SearchDirection,
-        SearchFlags, SearchType, VACUUM_FREQUENCY,
+        SearchFlags, SearchType, VACUUM_FREQUENCY, redact_secret_values,
     };
     use crate::common::{ESCAPE_TEST_CHAR, ScopeGuard, osstr2wcstring, wcs2bytes, wcs2osstring};
     use crate::env::{EnvMode, EnvSetMode, EnvStack};
@@ -2264,6 +2331,55 @@ fn test_history_merge() {
         everything.clear();
     }
 
+    #[test]
+    fn test_redact_secret_values() {
+        let redact = |s: &wstr| redact_secret_values(s).into_owned();
+        assert_eq!(
+            redact(L!("set --secret -gx TOKEN hunter2 'a b'")),
+            L!("set --secret -gx TOKEN '<redacted>' '<redacted>'")
+        );
+        assert_eq!(
+            redact(L!("echo hi; set -g --sec -- TOKEN hunter2 >/dev/null")),
+            L!("echo hi; set -g --sec -- TOKEN '<redacted>' >/dev/null")
+        );
+        assert_eq!(
+            redact(L!("set -gx TOKEN hunter2")),
+            L!("set -gx TOKEN hunter2")
+        );
+        assert_eq!(
+            redact(L!("echo --secret hunter2")),
+            L!("echo --secret hunter2")
+        );
+        assert_eq!(
+            redact(L!("set -g -- --secret x")),
+            L!("set -g -- --secret x")
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_history_redacts_secrets() {
+        let _cleanup = test_init();
+        let history = History::with_name(L!("redact_secrets_test"));
+        history.clear();
+        history.add_pending_with_file_detection(
+            L!("set --secret TOKEN hunter2"),
+            &EnvStack::new(),
+            PersistenceMode::Disk,
+        );
+        history.resolve_pending();
+        assert_eq!(
+            history.item_at_index(1).unwrap().str(),
+            "set --secret TOKEN '<redacted>'"
+        );
+        history.save();
+        assert_eq!(
+            history.get_history(),
+            [L!("set --secret TOKEN '<redacted>'").to_owned()]
+        );
+        history.clear();
+    }
+
     #[test]
     #[serial]
     fn test_history_path_detection() {
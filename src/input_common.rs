@@ -12,6 +12,7 @@
 };
 use crate::prelude::*;
 use crate::reader::reader_test_and_clear_interrupted;
+use crate::terminal::set_terminal_focused;
 use crate::tty_handoff::{
     SCROLL_CONTENT_UP_TERMINFO_CODE, TERMINAL_OS_NAME, XTGETTCAP_QUERY_OS_NAME, XTVERSION,
     maybe_set_kitty_keyboard_capability, maybe_set_scroll_content_up_capability,
@@ -1287,10 +1288,12 @@ fn parse_csi(&mut self, buffer: &mut Vec<u8>) -> Option<KeyEvent> {
             }
             b'Z' => KeyEvent::from(shift(key::Tab)),
             b'I' => {
+                set_terminal_focused(true);
                 self.push_front(CharEvent::Implicit(ImplicitEvent::FocusIn));
                 return None;
             }
             b'O' => {
+                set_terminal_focused(false);
                 self.push_front(CharEvent::Implicit(ImplicitEvent::FocusOut));
                 return None;
             }
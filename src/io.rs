@@ -2,7 +2,8 @@
 use crate::common::{bytes2wcstring, wcs2bytes};
 use crate::fd_monitor::{Callback, FdMonitor, FdMonitorItemId};
 use crate::fds::{
-    BorrowedFdFile, PIPE_ERROR, make_autoclose_pipes, make_fd_nonblocking, wopen_cloexec,
+    BorrowedFdFile, FIRST_HIGH_FD, PIPE_ERROR, heightenize_fd, make_autoclose_pipes,
+    make_fd_nonblocking, wopen_cloexec,
 };
 use crate::flog::{flog, flogf, should_flog};
 use crate::nix::isatty;
@@ -16,11 +17,11 @@
 use crate::wutil::{perror, perror_io, unescape_bytes_and_write_to_fd, wdirname, wstat};
 use errno::Errno;
 use libc::{EAGAIN, EINTR, ENOENT, ENOTDIR, EPIPE, EWOULDBLOCK, STDOUT_FILENO};
-use nix::fcntl::OFlag;
+use nix::fcntl::{FcntlArg, OFlag};
 use nix::sys::stat::Mode;
 use std::fs::File;
 use std::io;
-use std::os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd};
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd};
 use std::sync::{Arc, LazyLock, Mutex, MutexGuard};
 
 /// separated_buffer_t represents a buffer of output from commands, prepared to be turned into a
@@ -535,9 +536,16 @@ pub fn io_for_fd(&self, fd: RawFd) -> Option<IoDataRef> {
     }
 
     /// Attempt to resolve a list of redirection specs to IOs, appending to 'this'.
+    /// Fds allocated for redirections like {fd}>file are appended to `named_fds`, along with the
+    /// variable they should be stored in.
     /// Return true on success, false on error, in which case an error will have been printed.
     #[allow(clippy::collapsible_else_if)]
-    pub fn append_from_specs(&mut self, specs: &RedirectionSpecList, pwd: &wstr) -> bool {
+    pub fn append_from_specs(
+        &mut self,
+        specs: &RedirectionSpecList,
+        pwd: &wstr,
+        named_fds: &mut Vec<(WString, RawFd)>,
+    ) -> bool {
         let mut have_error = false;
 
         let print_error = |err, target: &wstr| {
@@ -569,6 +577,41 @@ pub fn append_from_specs(&mut self, specs: &RedirectionSpecList, pwd: &wstr) ->
         };
 
         for spec in specs {
+            if let Some(fd_var) = &spec.fd_var {
+                // Allocate an fd in the high range. Children see it under the same number.
+                let file = match spec.mode {
+                    RedirectionMode::Fd => {
+                        let target_fd = spec
+                            .get_target_as_fd()
+                            .expect("fd redirection should have been validated already");
+                        let target_fd = unsafe { BorrowedFd::borrow_raw(target_fd) };
+                        nix::fcntl::fcntl(target_fd, FcntlArg::F_DUPFD_CLOEXEC(FIRST_HIGH_FD))
+                            .map(|fd| File::from(unsafe { OwnedFd::from_raw_fd(fd) }))
+                    }
+                    _ => {
+                        let path = path_apply_working_directory(&spec.target, pwd);
+                        wopen_cloexec(&path, spec.oflags(), OPEN_MASK)
+                            .and_then(|file| heightenize_fd(OwnedFd::from(file), true))
+                            .map(File::from)
+                    }
+                };
+                match file {
+                    Ok(file) => {
+                        let fd = file.as_raw_fd();
+                        named_fds.push((fd_var.clone(), fd));
+                        self.push(Arc::new(IoFile::new(fd, file)));
+                    }
+                    Err(err) => {
+                        if spec.mode == RedirectionMode::NoClob && err == nix::Error::EEXIST {
+                            flogf!(warning, NOCLOB_ERROR, spec.target);
+                        } else if should_flog!(warning) {
+                            print_error(err as i32, &spec.target);
+                        }
+                        have_error = true;
+                    }
+                }
+                continue;
+            }
             match spec.mode {
                 RedirectionMode::Fd => {
                     if spec.is_close() {
@@ -26,9 +26,11 @@
 pub mod fds;
 pub mod flog;
 pub mod fork_exec;
+pub mod frecency;
 pub mod fs;
 pub mod function;
 pub mod future_feature_flags;
+pub mod gitignore;
 pub mod global_safety;
 pub mod highlight;
 pub mod history;
@@ -45,6 +47,7 @@
 pub mod operation_context;
 pub mod pager;
 pub mod panic;
+pub mod parallel_for;
 pub mod parse_constants;
 pub mod parse_execution;
 pub mod parse_tree;
@@ -59,9 +62,11 @@
 pub mod re;
 pub mod reader;
 pub mod redirection;
+pub mod restricted;
 pub mod screen;
 pub mod signal;
 pub mod stdx;
+pub mod suggest;
 pub mod terminal;
 pub mod termsize;
 pub mod text_face;
@@ -0,0 +1,227 @@
+//! Support for `for --parallel`, which runs the iterations of a for loop concurrently.
+//!
+//! fish cannot evaluate script on more than one thread, so each iteration runs in a child fish.
+//! The child first sources a prelude which recreates the functions and variables of the parent, so
+//! the loop body sees a copy of the same state. Nothing the body does is visible to the parent.
+//! The output of each iteration is collected, and handed back in the order of the loop items.
+
+use crate::builtins::shared::STATUS_NOT_EXECUTABLE;
+use crate::common::{bytes2wcstring, escape, wcs2bytes, wcs2osstring};
+use crate::env::config_paths::{FishPath, get_fish_path};
+use crate::env::{ElectricVar, EnvMode, Environment};
+use crate::function;
+use crate::io::{IoChain, IoMode, SeparationType};
+use crate::parser::Parser;
+use crate::prelude::*;
+use crate::signal::SigChecker;
+use crate::threads;
+use crate::wutil::write_to_fd;
+use libc::c_int;
+use nix::errno::Errno;
+use std::ffi::{OsStr, OsString};
+use std::io::Write;
+use std::os::fd::RawFd;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Command, Output, Stdio};
+use std::sync::mpsc;
+
+/// The result of running one iteration.
+pub struct IterationOutput {
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    pub status: c_int,
+}
+
+impl From<std::io::Result<Output>> for IterationOutput {
+    fn from(output: std::io::Result<Output>) -> Self {
+        match output {
+            Ok(output) => IterationOutput {
+                stdout: output.stdout,
+                stderr: output.stderr,
+                status: output
+                    .status
+                    .code()
+                    .or_else(|| output.status.signal().map(|sig| 128 + sig))
+                    .unwrap_or(STATUS_NOT_EXECUTABLE),
+            },
+            Err(err) => IterationOutput {
+                stdout: vec![],
+                stderr: format!("fish: Unable to run loop iteration: {err}\n").into_bytes(),
+                status: STATUS_NOT_EXECUTABLE,
+            },
+        }
+    }
+}
+
+/// Return a script which recreates the functions defined in this session, and the unexported
+/// variables visible to `parser`. Exported variables reach the child through its environment, and
+/// autoloaded functions will be autoloaded there as well.
+fn state_prelude(parser: &Parser) -> WString {
+    let vars = parser.vars();
+    let mut out = WString::new();
+
+    // Variables go first, so that setting them does not trigger any --on-variable handlers.
+    let mut names = vars.get_names(EnvMode::LOCAL | EnvMode::GLOBAL | EnvMode::UNEXPORT);
+    names.sort();
+    for name in names {
+        if ElectricVar::for_name(&name).is_some() {
+            continue;
+        }
+        let Some(var) = vars.get(&name) else {
+            continue;
+        };
+        if var.exports() {
+            continue;
+        }
+        out.push_str("set -g ");
+        if var.is_pathvar() {
+            out.push_str("--path ");
+        }
+        if var.is_map() {
+            out.push_str("--map ");
+        }
+        out.push_utfstr(&escape(&name));
+        let vals = if var.is_map() {
+            var.map_pairs()
+        } else {
+            var.as_list().to_vec()
+        };
+        for val in vals {
+            out.push(' ');
+            out.push_utfstr(&escape(&val));
+        }
+        out.push('\n');
+    }
+
+    let mut funcs = function::get_names(true, vars);
+    funcs.sort();
+    for name in funcs {
+        let Some(props) = function::get_props(&name) else {
+            continue;
+        };
+        if props.is_autoload.load() {
+            continue;
+        }
+        out.push_utfstr(&props.annotated_definition(&name));
+    }
+    out
+}
+
+/// Construct the command which runs `script` in a child fish, with our exported variables.
+fn child_command(parser: &Parser, script: &wstr) -> Command {
+    let fish = match get_fish_path() {
+        FishPath::Absolute(path) => path.as_os_str().to_owned(),
+        FishPath::LookUpInPath => OsString::from("fish"),
+    };
+    let mut cmd = Command::new(fish);
+    cmd.arg("--no-config")
+        .arg("-c")
+        .arg(wcs2osstring(script))
+        .env_clear()
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    for entry in parser.vars().export_array().iter() {
+        let entry = entry.as_bytes();
+        if let Some(eq) = entry.iter().position(|&c| c == b'=') {
+            cmd.env(
+                OsStr::from_bytes(&entry[..eq]),
+                OsStr::from_bytes(&entry[eq + 1..]),
+            );
+        }
+    }
+    cmd
+}
+
+/// Run each of `scripts` in a child fish, at most `max_jobs` at a time.
+/// `on_done` is invoked with the output of each script, in the order of `scripts`.
+/// If SIGINT is received, no further scripts are started, but running ones are waited for.
+pub fn run_parallel(
+    parser: &Parser,
+    scripts: Vec<WString>,
+    max_jobs: usize,
+    mut on_done: impl FnMut(IterationOutput),
+) -> std::io::Result<()> {
+    let mut prelude = fish_tempfile::new_file()?;
+    prelude
+        .get_mut()
+        .write_all(&wcs2bytes(&state_prelude(parser)))?;
+    let source_prelude = sprintf!(
+        "source %s\n",
+        escape(&bytes2wcstring(prelude.path().as_os_str().as_bytes()))
+    );
+
+    let (sender, receiver) = mpsc::channel();
+    let mut finished: Vec<Option<IterationOutput>> = scripts.iter().map(|_| None).collect();
+    let mut next_to_report = 0;
+    let mut pending = scripts.into_iter().enumerate();
+    let mut running = 0;
+    let mut cancelled = false;
+    let mut sigint = SigChecker::new_sighupint();
+    loop {
+        while running < max_jobs && !cancelled {
+            let Some((idx, script)) = pending.next() else {
+                break;
+            };
+            let mut full_script = source_prelude.clone();
+            full_script.push_utfstr(&script);
+            let mut cmd = child_command(parser, &full_script);
+            let sender = sender.clone();
+            if threads::spawn(move || {
+                let _ = sender.send((idx, IterationOutput::from(cmd.output())));
+            }) {
+                running += 1;
+            } else {
+                finished[idx] = Some(IterationOutput::from(Err(std::io::Error::from(
+                    std::io::ErrorKind::WouldBlock,
+                ))));
+            }
+        }
+
+        // Report everything which is done, in order.
+        while let Some(output) = finished.get_mut(next_to_report).and_then(Option::take) {
+            on_done(output);
+            next_to_report += 1;
+        }
+
+        if running == 0 {
+            break;
+        }
+        // We hold a sender, so this cannot fail.
+        let (idx, output) = receiver.recv().unwrap();
+        running -= 1;
+        finished[idx] = Some(output);
+        if sigint.check() {
+            cancelled = true;
+        }
+    }
+    Ok(())
+}
+
+/// Write `data` to wherever `fd` is redirected in `io_chain`.
+pub fn write_to_io_chain(io_chain: &IoChain, fd: RawFd, mut data: &[u8]) {
+    let target = match io_chain.io_for_fd(fd) {
+        None => fd,
+        Some(io) => match io.io_mode() {
+            IoMode::BufferFill => {
+                let buffer = io.as_bufferfill().unwrap().buffer();
+                buffer.append(data, SeparationType::inferred);
+                return;
+            }
+            IoMode::Close => return,
+            // Like `2>&1`: write to wherever the other fd goes.
+            IoMode::Fd if io.source_fd() != fd => {
+                return write_to_io_chain(io_chain, io.source_fd(), data);
+            }
+            IoMode::File | IoMode::Pipe | IoMode::Fd => io.source_fd(),
+        },
+    };
+    while !data.is_empty() {
+        match write_to_fd(data, target) {
+            Ok(written) => data = &data[written..],
+            Err(Errno::EINTR) => continue,
+            Err(_) => return,
+        }
+    }
+}
@@ -24,6 +24,7 @@
 use crate::io::{IoChain, IoStreams, OutputStream, StringOutputStream};
 use crate::job_group::JobGroup;
 use crate::operation_context::OperationContext;
+use crate::parallel_for::{self, write_to_io_chain};
 use crate::parse_constants::{
     CALL_STACK_LIMIT_EXCEEDED_ERR_MSG, ERROR_TIME_BACKGROUND,
     FAILED_EXPANSION_VARIABLE_NAME_ERR_MSG, ILLEGAL_FD_ERR_MSG, INFINITE_FUNC_RECURSION_ERR_MSG,
@@ -46,11 +47,15 @@
 };
 use crate::reader::fish_is_unwinding_for_exit;
 use crate::redirection::{RedirectionMode, RedirectionSpec, RedirectionSpecList};
+use crate::restricted::{
+    is_allowed_output_target, is_restricted, is_restricted_variable, restriction_violated,
+};
 use crate::signal::Signal;
 use crate::timer::push_timer;
 use crate::tokenizer::{PipeOrRedir, TokenType, variable_assignment_equals_pos};
 use crate::trace::{trace_if_enabled, trace_if_enabled_with_args};
 use crate::wildcard::wildcard_match;
+use crate::wutil::fish_wcstoi;
 use fish_common::help_section;
 use fish_widestring::WExt;
 use libc::{ENOTDIR, EXIT_SUCCESS, STDERR_FILENO, STDOUT_FILENO, c_int};
@@ -343,7 +348,7 @@ fn handle_command_not_found(
             RedirectionMode::Fd,
             L!("2").to_owned(),
         ));
-        io.append_from_specs(&list, L!(""));
+        io.append_from_specs(&list, L!(""), &mut vec![]);
 
         if function::exists(L!("fish_command_not_found"), ctx.parser()) {
             let mut buffer = L!("fish_command_not_found").to_owned();
@@ -554,6 +559,43 @@ fn expand_command(
         EndExecutionReason::Ok
     }
 
+    /// In restricted mode, refuse `exec` and commands given by path.
+    fn check_restricted_command(
+        &self,
+        ctx: &OperationContext<'_>,
+        statement: &ast::DecoratedStatement,
+        cmd: &wstr,
+    ) -> EndExecutionReason {
+        let (kind, ret) = if statement.decoration() == StatementDecoration::Exec || cmd == "exec" {
+            (
+                L!("exec"),
+                report_error!(
+                    self,
+                    ctx,
+                    STATUS_NOT_EXECUTABLE,
+                    &statement.command,
+                    "exec: Not allowed in restricted mode"
+                ),
+            )
+        } else if cmd.contains('/') {
+            (
+                L!("command"),
+                report_error!(
+                    self,
+                    ctx,
+                    STATUS_NOT_EXECUTABLE,
+                    &statement.command,
+                    "%s: Commands containing '/' are not allowed in restricted mode",
+                    cmd
+                ),
+            )
+        } else {
+            return EndExecutionReason::Ok;
+        };
+        restriction_violated(ctx.parser(), kind, cmd);
+        ret
+    }
+
     /// Indicates whether a job is a simple block (one block, no redirections).
     fn job_is_simple_block(&self, job: &ast::JobPipeline) -> bool {
         // Must be no pipes.
@@ -618,6 +660,19 @@ fn apply_variable_assignments(
             let equals_pos = variable_assignment_equals_pos(source).unwrap();
             let variable_name = &source[..equals_pos];
             let expression = &source[equals_pos + 1..];
+            // The command would otherwise be looked up in, or run with, the changed variable.
+            if is_restricted_variable(variable_name) {
+                let ret = report_error!(
+                    self,
+                    ctx,
+                    STATUS_INVALID_ARGS,
+                    variable_assignment,
+                    "Cannot change the variable '%s' in restricted mode",
+                    variable_name
+                );
+                restriction_violated(ctx.parser(), L!("variable"), variable_name);
+                return ret;
+            }
             let mut expression_expanded = vec![];
             let mut errors = ParseErrorList::new();
             // TODO this is mostly copied from expand_arguments_from_nodes, maybe extract to function
@@ -743,6 +798,13 @@ fn populate_plain_process(
             "expand_command should not produce an empty command",
         );
 
+        if is_restricted() {
+            let ret = self.check_restricted_command(ctx, statement, &cmd);
+            if ret != EndExecutionReason::Ok {
+                return ret;
+            }
+        }
+
         // Determine the process type.
         let mut process_type = self.process_type_for_command(ctx, statement, &cmd);
         let external_cmd = if matches!(process_type, ProcessType::External | ProcessType::Exec) {
@@ -819,9 +881,17 @@ fn populate_plain_process(
                 return arg_result;
             }
 
-            // The set of IO redirections that we construct for the process.
-            let reason =
-                self.determine_redirections(ctx, &statement.args_or_redirs, &mut redirections);
+            // The set of IO redirections that we construct for the process. Only an `exec` without
+            // arguments keeps its redirections, so only it may store new fds in variables.
+            let is_bare_exec = matches!(process_type, ProcessType::Builtin)
+                && cmd_args.len() == 1
+                && cmd_args[0] == "exec";
+            let reason = self.determine_redirections(
+                ctx,
+                &statement.args_or_redirs,
+                is_bare_exec,
+                &mut redirections,
+            );
             if reason != EndExecutionReason::Ok {
                 return reason;
             }
@@ -854,7 +924,7 @@ fn populate_block_process(
         };
 
         let mut redirections = RedirectionSpecList::new();
-        let reason = self.determine_redirections(ctx, args_or_redirs, &mut redirections);
+        let reason = self.determine_redirections(ctx, args_or_redirs, false, &mut redirections);
         if reason == EndExecutionReason::Ok {
             proc.typ = ProcessType::BlockNode(NodeRef::new(Arc::clone(self.pstree()), statement));
             proc.set_redirection_specs(redirections);
@@ -932,6 +1002,34 @@ fn run_for_statement(
                 for_var_name
             );
         }
+        if is_restricted_variable(&for_var_name) {
+            let ret = report_error!(
+                self,
+                ctx,
+                STATUS_INVALID_ARGS,
+                header.var_name,
+                "%s: Cannot change the variable '%s' in restricted mode",
+                "for",
+                for_var_name
+            );
+            restriction_violated(ctx.parser(), L!("variable"), &for_var_name);
+            return ret;
+        }
+
+        if let Some(parallel) = &header.parallel {
+            let max_jobs = match self.parse_for_parallel(ctx, parallel) {
+                Ok(max_jobs) => max_jobs,
+                Err(reason) => return reason,
+            };
+            return self.run_parallel_for_statement(
+                ctx,
+                header,
+                &for_var_name,
+                arguments,
+                max_jobs,
+                block_contents,
+            );
+        }
 
         let retval = ctx.parser().set_var(
             &for_var_name,
@@ -986,6 +1084,112 @@ fn run_for_statement(
         ret
     }
 
+    /// Validate the `--parallel N` option of a for loop, returning N.
+    fn parse_for_parallel(
+        &mut self,
+        ctx: &OperationContext<'_>,
+        parallel: &'a ast::ForParallel,
+    ) -> Result<usize, EndExecutionReason> {
+        let option = self.node_source_owned(&parallel.option);
+        if option != "--parallel" {
+            return Err(report_error!(
+                self,
+                ctx,
+                STATUS_INVALID_ARGS,
+                &parallel.option,
+                "%s: %s: unknown option",
+                "for",
+                option
+            ));
+        }
+        let mut count = self.node_source_owned(&parallel.count);
+        if !expand_one(&mut count, ExpandFlags::default(), ctx, None) {
+            return Err(report_error!(
+                self,
+                ctx,
+                STATUS_EXPAND_ERROR,
+                &parallel.count,
+                "%s: %s: could not expand",
+                "for",
+                count
+            ));
+        }
+        match fish_wcstoi(&count) {
+            Ok(max_jobs @ 1..) => Ok(usize::try_from(max_jobs).unwrap()),
+            _ => Err(report_error!(
+                self,
+                ctx,
+                STATUS_INVALID_ARGS,
+                &parallel.count,
+                "%s: %s: --parallel expects a positive integer",
+                "for",
+                count
+            )),
+        }
+    }
+
+    /// Run a `for --parallel` loop. Each iteration runs in a child fish, see [`parallel_for`].
+    /// The status is that of the last failed iteration, or 0 if all of them succeeded.
+    fn run_parallel_for_statement(
+        &mut self,
+        ctx: &OperationContext<'_>,
+        header: &'a ast::ForHeader,
+        for_var_name: &wstr,
+        arguments: Vec<WString>,
+        max_jobs: usize,
+        block_contents: &'a ast::JobList,
+    ) -> EndExecutionReason {
+        // The iterations run in child shells, which are not restricted.
+        if is_restricted() {
+            let parallel = header.parallel.as_ref().unwrap();
+            let ret = report_error!(
+                self,
+                ctx,
+                STATUS_INVALID_ARGS,
+                &parallel.option,
+                "%s: --parallel is not allowed in restricted mode",
+                "for"
+            );
+            restriction_violated(ctx.parser(), L!("parallel"), L!("for --parallel"));
+            return ret;
+        }
+
+        trace_if_enabled_with_args(ctx.parser(), L!("for"), &arguments);
+
+        // Every iteration is a loop of its own, so that break and continue work in the body.
+        let body = self.node_source(block_contents);
+        let scripts = arguments
+            .iter()
+            .map(|val| sprintf!("for %s in %s\n%s\nend\n", for_var_name, escape(val), body))
+            .collect();
+
+        let mut status = STATUS_CMD_OK;
+        let block_io = self.block_io.clone();
+        let result = parallel_for::run_parallel(ctx.parser(), scripts, max_jobs, |output| {
+            write_to_io_chain(&block_io, STDOUT_FILENO, &output.stdout);
+            write_to_io_chain(&block_io, STDERR_FILENO, &output.stderr);
+            if output.status != STATUS_CMD_OK {
+                status = output.status;
+            }
+        });
+        if let Err(err) = result {
+            return report_error!(
+                self,
+                ctx,
+                STATUS_CMD_ERROR,
+                &header.kw_for,
+                "%s: Unable to run loop in parallel: %s",
+                "for",
+                err.to_string()
+            );
+        }
+        ctx.parser().set_last_statuses(Statuses::just(status));
+
+        trace_if_enabled(ctx.parser(), L!("end for"));
+        self.check_end_execution(ctx)
+            .unwrap_or(EndExecutionReason::Ok)
+    }
+
     fn run_if_statement(
         &mut self,
         ctx: &OperationContext<'_>,
@@ -1410,11 +1614,13 @@ fn expand_arguments_from_nodes(
         EndExecutionReason::Ok
     }
 
-    // Determines the list of redirections for a node.
+    // Determines the list of redirections for a node. Redirections like {fd}>file are only allowed
+    // if `allow_fd_vars` is set.
     fn determine_redirections(
         &self,
         ctx: &OperationContext<'_>,
         list: &ast::ArgumentOrRedirectionList,
+        allow_fd_vars: bool,
         out_redirections: &mut RedirectionSpecList,
     ) -> EndExecutionReason {
         // Get all redirection nodes underneath the statement.
@@ -1484,9 +1690,63 @@ fn determine_redirections(
                 return error_ret;
             }
 
+            // A restricted shell may not write to files.
+            if is_restricted()
+                && matches!(
+                    oper.mode,
+                    RedirectionMode::Overwrite | RedirectionMode::Append | RedirectionMode::NoClob
+                )
+                && !is_allowed_output_target(&target)
+            {
+                let ret = report_error!(
+                    self,
+                    ctx,
+                    STATUS_INVALID_ARGS,
+                    redir_node,
+                    "%s: Redirecting output to a file is not allowed in restricted mode",
+                    target
+                );
+                restriction_violated(ctx.parser(), L!("redirection"), &target);
+                return ret;
+            }
+
             // Make a redirection spec from the redirect token.
             assert!(oper.is_valid(), "expected to have a valid redirection");
-            let spec = RedirectionSpec::new(oper.fd, oper.mode, target);
+            let mut spec = RedirectionSpec::new(oper.fd, oper.mode, target);
+            if spec.is_close() {
+                if let Some(fd_var) = oper.fd_var {
+                    // Like {fd}>&-, which closes the fd stored in the variable.
+                    let fd = ctx
+                        .vars()
+                        .get(&fd_var)
+                        .and_then(|var| fish_wcstoi(&var.as_string()).ok())
+                        .filter(|fd| *fd >= 0);
+                    let Some(fd) = fd else {
+                        return report_error!(
+                            self,
+                            ctx,
+                            STATUS_INVALID_ARGS,
+                            redir_node,
+                            "Variable '%s' does not hold a file descriptor",
+                            &fd_var
+                        );
+                    };
+                    spec.fd = fd;
+                }
+            } else if let Some(fd_var) = oper.fd_var {
+                // The fd would be closed once the command finishes, leaving a stale number behind.
+                if !allow_fd_vars {
+                    return report_error!(
+                        self,
+                        ctx,
+                        STATUS_INVALID_ARGS,
+                        redir_node,
+                        "A file descriptor can only be stored in '%s' by 'exec' without a command",
+                        &fd_var
+                    );
+                }
+                spec.fd_var = Some(fd_var);
+            }
 
             // Validate this spec.
             if spec.mode == RedirectionMode::Fd
@@ -1556,7 +1816,7 @@ fn run_1_job(
                     ERROR_TIME_BACKGROUND
                 );
             }
-            wants_timing.then(push_timer)
+            wants_timing.then(|| push_timer(ctx.parser().vars()))
         };
 
         // When we encounter a block construct (e.g. while loop) in the general case, we create a "block
@@ -1681,6 +1941,7 @@ fn run_1_job(
         }
 
         job_reap(ctx.parser(), false, Some(&self.block_io)); // clean up jobs
+        event::fire_expired_timers(ctx.parser());
         pop_result
     }
 
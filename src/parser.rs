@@ -9,7 +9,7 @@
 use crate::complete::CompletionList;
 use crate::env::{
     EnvMode, EnvSetMode, EnvStack, EnvStackSetResult, Environment, FISH_TERMINAL_COLOR_THEME_VAR,
-    Statuses,
+    Statuses, redact_secrets, secret_values,
 };
 use crate::event::{self, Event};
 use crate::expand::{
@@ -18,7 +18,7 @@
 use crate::fds::{BEST_O_SEARCH, open_dir};
 use crate::global_safety::RelaxedAtomicBool;
 use crate::input_common::TerminalQuery;
-use crate::io::IoChain;
+use crate::io::{IoChain, IoMode};
 use crate::job_group::MaybeJobId;
 use crate::operation_context::{EXPANSION_LIMIT_DEFAULT, OperationContext};
 use crate::parse_constants::{
@@ -414,6 +414,10 @@ pub struct Parser {
     /// Set of variables for the parser.
     pub variables: EnvStack,
 
+    /// Redirections installed by a redirection-only `exec`, like `exec 3>log`.
+    /// These apply to every job run afterwards.
+    persistent_ios: RefCell<IoChain>,
+
     /// Data managed in a scoped fashion.
     scoped_data: ScopedCell<ScopedData>,
 
@@ -464,6 +468,7 @@ pub fn new(variables: EnvStack, cancel_behavior: CancelBehavior) -> Parser {
             wait_handles: RefCell::default(),
             block_list: RefCell::default(),
             variables,
+            persistent_ios: RefCell::default(),
             scoped_data: ScopedCell::new(ScopedData::default()),
             library_data: ScopedRefCell::new(LibraryData::new()),
             syncs_uvars: RelaxedAtomicBool::new(false),
@@ -486,6 +491,33 @@ pub fn new(variables: EnvStack, cancel_behavior: CancelBehavior) -> Parser {
         result
     }
 
+    /// Return `block_io`, preceded by the redirections installed by `exec`.
+    pub fn with_persistent_ios(&self, block_io: IoChain) -> IoChain {
+        let persistent = self.persistent_ios.borrow();
+        if persistent.0.is_empty() {
+            return block_io;
+        }
+        let mut ios = persistent.clone();
+        ios.append(&block_io);
+        ios
+    }
+
+    /// Install redirections for all jobs run afterwards, replacing earlier ones for the same fds.
+    pub fn add_persistent_ios(&self, ios: &IoChain) {
+        let mut persistent = self.persistent_ios.borrow_mut();
+        for io in &ios.0 {
+            // Closing an fd that was allocated for a named fd like {fd}>file just forgets it.
+            let closes_named_fd = io.io_mode() == IoMode::Close
+                && persistent
+                    .io_for_fd(io.fd())
+                    .is_some_and(|old| old.source_fd() == old.fd());
+            persistent.0.retain(|old| old.fd() != io.fd());
+            if !closes_named_fd {
+                persistent.push(Arc::clone(io));
+            }
+        }
+    }
+
     /// Adds a job to the beginning of the job list.
     pub fn job_add(&self, job: JobRef) {
         assert!(!job.processes().is_empty());
@@ -1381,7 +1413,8 @@ fn append_block_description_to_stack_trace(
                 unreachable!()
             };
             trace.push_utfstr(&wgettext_fmt!("in function '%s'", name));
-            // Print arguments on the same line.
+            // Print arguments on the same line, without the values of secret variables.
+            let secrets = secret_values(parser.vars());
             let mut args_str = WString::new();
             for arg in args {
                 if !args_str.is_empty() {
@@ -1391,7 +1424,7 @@ fn append_block_description_to_stack_trace(
                 // As a special-case, add the empty argument as "".
                 if !arg.is_empty() {
                     args_str.push_utfstr(&escape_string(
-                        arg,
+                        &redact_secrets(arg, &secrets),
                         EscapeStringStyle::Script(EscapeFlags::NO_QUOTED),
                     ));
                 } else {
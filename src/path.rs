@@ -2,7 +2,7 @@
 //! for testing if a command with a given name can be found in the PATH, and various other
 //! path-related issues.
 
-use crate::common::{wcs2osstring, wcs2zstring};
+use crate::common::{osstr2wcstring, wcs2osstring, wcs2zstring};
 use crate::env::{EnvMode, EnvSetMode, EnvStack, Environment, FALLBACK_PATH};
 use crate::expand::{HOME_DIRECTORY, expand_tilde};
 use crate::flog::{flog, flogf};
@@ -11,11 +11,14 @@
 use errno::{Errno, errno, set_errno};
 use libc::{EACCES, ENOENT, ENOTDIR, X_OK};
 use nix::unistd::AccessFlags;
+use std::collections::{BTreeMap, HashSet};
 use std::ffi::OsStr;
 use std::io::ErrorKind;
 use std::mem::MaybeUninit;
 use std::os::unix::prelude::*;
-use std::sync::LazyLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
 /// Returns the user configuration directory for fish. If the directory or one of its parents
 /// doesn't exist, they are first created.
@@ -189,7 +192,7 @@ fn new(err: Option<Errno>, path: WString) -> Self {
 
 pub fn path_try_get_path(cmd: &wstr, vars: &dyn Environment) -> GetPathResult {
     if let Some(path) = vars.get(L!("PATH")) {
-        path_get_path_core(cmd, path.as_list())
+        path_get_path_hashed(cmd, path.as_list())
     } else {
         path_get_path_core(cmd, &FALLBACK_PATH)
     }
@@ -301,6 +304,191 @@ fn path_get_path_core<S: AsRef<wstr>>(cmd: &wstr, pathsv: &[S]) -> GetPathResult
     best
 }
 
+/// How long a check of the $PATH directories' modification times stays good. Lookups in quick
+/// succession, like those for highlighting, then do not stat every directory again.
+const COMMAND_HASH_RECHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Incremented whenever $PATH is set, to invalidate the command hash table.
+static PATH_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Forget all remembered command locations. Called when $PATH changes.
+pub fn invalidate_command_hash() {
+    PATH_GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+/// A table of command lookups in $PATH, like bash's `hash`.
+///
+/// Adding or removing a file changes the modification time of its directory, so the entries stay
+/// valid as long as $PATH and the modification times of its directories are unchanged. Like bash,
+/// we don't remember commands that were not found: modification times may be too coarse, or
+/// cached on network file systems, to notice that a missing command was installed.
+#[derive(Default)]
+struct CommandHashTable {
+    /// The value of [`PATH_GENERATION`] when the table was last reset.
+    generation: u64,
+    /// Bumped whenever the entries are cleared, so a lookup that raced with that does not
+    /// store a stale result.
+    epoch: u64,
+    /// The $PATH the entries were found in.
+    path: Vec<WString>,
+    /// The modification times of the directories in `path`.
+    dir_mtimes: Vec<Option<SystemTime>>,
+    /// When `dir_mtimes` was last compared with the file system.
+    last_checked: Option<Instant>,
+    /// Commands and where they were found.
+    entries: BTreeMap<WString, WString>,
+    /// The names of all executables in the directories in `path`, once they were listed.
+    listing: Option<Arc<Vec<WString>>>,
+}
+
+impl CommandHashTable {
+    fn dir_mtimes<S: AsRef<wstr>>(pathsv: &[S]) -> Vec<Option<SystemTime>> {
+        pathsv
+            .iter()
+            .map(|dir| wstat(dir.as_ref()).ok().and_then(|md| md.modified().ok()))
+            .collect()
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.listing = None;
+        self.epoch += 1;
+    }
+
+    /// Drop the entries if they might be out of date for a lookup in `pathsv`.
+    fn validate<S: AsRef<wstr>>(&mut self, pathsv: &[S]) {
+        let generation = PATH_GENERATION.load(Ordering::Relaxed);
+        let same_path = self.path.len() == pathsv.len()
+            && self.path.iter().zip(pathsv).all(|(a, b)| a == b.as_ref());
+        if self.generation != generation || !same_path {
+            self.clear();
+            self.generation = generation;
+            self.path = pathsv.iter().map(|dir| dir.as_ref().to_owned()).collect();
+            self.dir_mtimes = Self::dir_mtimes(pathsv);
+            self.last_checked = Some(Instant::now());
+            return;
+        }
+        if self
+            .last_checked
+            .is_some_and(|checked| checked.elapsed() < COMMAND_HASH_RECHECK_INTERVAL)
+        {
+            return;
+        }
+        let dir_mtimes = Self::dir_mtimes(pathsv);
+        if dir_mtimes != self.dir_mtimes {
+            flog!(
+                path,
+                "A $PATH directory changed, clearing the command hash table"
+            );
+            self.clear();
+            self.dir_mtimes = dir_mtimes;
+        }
+        self.last_checked = Some(Instant::now());
+    }
+}
+
+static COMMAND_HASH: LazyLock<Mutex<CommandHashTable>> =
+    LazyLock::new(|| Mutex::new(CommandHashTable::default()));
+
+/// Like [`path_get_path_core`], but remembers the result in the command hash table.
+fn path_get_path_hashed(cmd: &wstr, pathsv: &[WString]) -> GetPathResult {
+    if cmd.is_empty() || cmd.contains('/') || cmd.contains('\0') {
+        return path_get_path_core(cmd, pathsv);
+    }
+    let epoch = {
+        let mut table = COMMAND_HASH.lock().unwrap();
+        table.validate(pathsv);
+        if let Some(path) = table.entries.get(cmd) {
+            return GetPathResult::new(None, path.clone());
+        }
+        table.epoch
+    };
+
+    // Search without holding the lock; this may be slow on network file systems.
+    let result = path_get_path_core(cmd, pathsv);
+
+    // Only remember commands that were found. Errors may go away without us noticing.
+    if result.err.is_none() {
+        let mut table = COMMAND_HASH.lock().unwrap();
+        if table.epoch == epoch {
+            table.entries.insert(cmd.to_owned(), result.path.clone());
+        }
+    }
+    result
+}
+
+/// Return the names of all executables in $PATH. The listing is kept until the command hash table
+/// is cleared, so it is redone once $PATH or one of its directories changes.
+pub fn path_list_commands(vars: &dyn Environment) -> Arc<Vec<WString>> {
+    let path = vars.get(L!("PATH"));
+    let pathsv = match &path {
+        Some(path) => path.as_list(),
+        None => *FALLBACK_PATH,
+    };
+    let epoch = {
+        let mut table = COMMAND_HASH.lock().unwrap();
+        table.validate(pathsv);
+        if let Some(listing) = &table.listing {
+            return Arc::clone(listing);
+        }
+        table.epoch
+    };
+
+    // List the directories without holding the lock, like in path_get_path_hashed().
+    let mut names = HashSet::new();
+    for dir in pathsv {
+        let Ok(entries) = std::fs::read_dir(wcs2osstring(dir)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            // Follow symlinks, like command lookup does.
+            let Ok(md) = std::fs::metadata(entry.path()) else {
+                continue;
+            };
+            if md.is_file() && md.permissions().mode() & 0o111 != 0 {
+                names.insert(osstr2wcstring(entry.file_name()));
+            }
+        }
+    }
+    let names = Arc::new(names.into_iter().collect::<Vec<_>>());
+    let mut table = COMMAND_HASH.lock().unwrap();
+    if table.epoch == epoch {
+        table.listing = Some(Arc::clone(&names));
+    }
+    names
+}
+
+/// Look up `cmd` in $PATH and remember where it is, even if it was already known.
+/// Return the path, or None if it was not found.
+pub fn command_hash_add(cmd: &wstr, vars: &dyn Environment) -> Option<WString> {
+    command_hash_remove(cmd);
+    path_get_path(cmd, vars)
+}
+
+/// Forget where `cmd` is. Return whether it was known.
+pub fn command_hash_remove(cmd: &wstr) -> bool {
+    COMMAND_HASH.lock().unwrap().entries.remove(cmd).is_some()
+}
+
+/// Forget all remembered commands.
+pub fn command_hash_clear() {
+    COMMAND_HASH.lock().unwrap().clear();
+}
+
+/// Return the remembered commands with their paths, sorted by name.
+pub fn command_hash_list(vars: &dyn Environment) -> Vec<(WString, WString)> {
+    let mut table = COMMAND_HASH.lock().unwrap();
+    match vars.get(L!("PATH")) {
+        Some(path) => table.validate(path.as_list()),
+        None => table.validate(*FALLBACK_PATH),
+    }
+    table
+        .entries
+        .iter()
+        .map(|(cmd, path)| (cmd.clone(), path.clone()))
+        .collect()
+}
+
 /// Returns the full path of the specified directory, using the CDPATH variable as a list of base
 /// directories for relative paths.
 ///
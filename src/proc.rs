@@ -14,9 +14,13 @@
 use crate::parser::{Block, Parser};
 use crate::portable_atomic::AtomicU64;
 use crate::prelude::*;
-use crate::reader::{fish_is_unwinding_for_exit, reader_schedule_prompt_repaint};
+use crate::reader::{
+    fish_is_unwinding_for_exit, notify_command_finished, notify_duration,
+    reader_schedule_prompt_repaint,
+};
 use crate::redirection::RedirectionSpecList;
 use crate::signal::{Signal, signal_set_handlers_once};
+use crate::terminal::terminal_is_focused;
 use crate::topic_monitor::{GenerationsList, Topic, topic_monitor_principal};
 use crate::wait_handle::{InternalJobId, WaitHandle, WaitHandleRef, WaitHandleStore};
 use crate::wutil::{wbasename, wperror};
@@ -583,6 +587,16 @@ pub struct JobFlags {
 
     // Indicates that we are the "group root." Any other jobs using this tree are nested.
     pub is_group_root: bool,
+
+    /// Whether a job_start event has been posted for this job.
+    pub posted_job_start: bool,
+
+    /// Whether the last job_stop or job_continue event posted for this job was job_stop.
+    pub posted_job_stop: bool,
+
+    /// Whether the job was resumed since the last job_stop event. The job_continue event is
+    /// posted on the next reap, even if the job has stopped again by then.
+    pub resumed_since_stop: bool,
 }
 
 /// A struct representing a job. A job is a pipeline of one or more processes.
@@ -607,6 +621,9 @@ pub struct Job {
 
     /// Flags associated with the job.
     pub job_flags: RefCell<JobFlags>,
+
+    /// The time at which this job was created, per timef.
+    pub start_time: Timepoint,
 }
 
 impl Job {
@@ -616,6 +633,7 @@ pub fn new(properties: JobProperties, command_str: WString) -> Self {
             properties,
             command_str,
             internal_job_id: NEXT_INTERNAL_JOB_ID.fetch_add(1, Ordering::Relaxed),
+            start_time: timef(),
             ..Default::default()
         }
     }
@@ -792,6 +810,13 @@ pub fn posts_job_exit_events(&self) -> bool {
         self.has_external_proc()
     }
 
+    /// Return whether we should post job_start, job_stop and job_continue events.
+    pub fn posts_job_state_events(&self) -> bool {
+        // As with exit events, skip foreground jobs originating in event handlers, as the handler
+        // would otherwise trigger itself.
+        self.posts_job_exit_events() && (!self.from_event_handler() || !self.is_foreground())
+    }
+
     /// Run ourselves. Returning once we complete or stop.
     pub fn continue_job(&self, parser: &Parser, block_io: Option<&IoChain>) {
         flogf!(
@@ -832,7 +857,11 @@ pub fn continue_job(&self, parser: &Parser, block_io: Option<&IoChain>) {
     /// Prepare to resume a stopped job by sending SIGCONT and clearing the stopped flag.
     /// Return true on success, false if we failed to send the signal.
     pub fn resume(&self) -> bool {
-        self.mut_flags().notified_of_stop = false;
+        {
+            let mut flags = self.mut_flags();
+            flags.notified_of_stop = false;
+            flags.resumed_since_stop = flags.posted_job_stop;
+        }
         if !self.signal(NixSignal::SIGCONT) {
             flogf!(
                 proc_pgroup,
@@ -1351,6 +1380,33 @@ fn generate_process_exit_events(j: &Job, out_evts: &mut Vec<Event>) {
     }
 }
 
+/// Generate job_start, job_stop and job_continue events for any state changes of `j` which have
+/// not been posted yet.
+fn generate_job_state_events(j: &Job, out_evts: &mut Vec<Event>) {
+    if !j.is_constructed() || !j.posts_job_state_events() {
+        return;
+    }
+    // Jobs in fish's own pgroup have no pgid, so report the pid of the first process instead.
+    let pgid = j
+        .get_pgid()
+        .or_else(|| j.external_procs().next().and_then(|p| p.pid()));
+    let stopped = j.is_stopped();
+    let mut flags = j.mut_flags();
+    if !flags.posted_job_start {
+        flags.posted_job_start = true;
+        out_evts.push(Event::job_start(j.job_id(), pgid, j.command()));
+    }
+    if flags.posted_job_stop && (!stopped || flags.resumed_since_stop) {
+        flags.posted_job_stop = false;
+        out_evts.push(Event::job_continue(j.job_id(), pgid, j.command()));
+    }
+    flags.resumed_since_stop = false;
+    if stopped && !flags.posted_job_stop {
+        flags.posted_job_stop = true;
+        out_evts.push(Event::job_stop(j.job_id(), pgid, j.command()));
+    }
+}
+
 /// Given a job that has completed, generate job_exit and caller_exit events.
 fn generate_job_exit_events(j: &Job, out_evts: &mut Vec<Event>) {
     // Generate proc and job exit events, except for foreground jobs originating in event handlers.
@@ -1574,8 +1630,9 @@ fn process_clean_after_marking(parser: &Parser, interactive: bool) -> bool {
         }
     }
 
-    // Generate process_exit events for finished processes.
+    // Generate job state events, and process_exit events for finished processes.
     for j in parser.jobs().iter() {
+        generate_job_state_events(j, &mut exit_events);
         generate_process_exit_events(j, &mut exit_events);
     }
 
@@ -1597,6 +1654,16 @@ fn process_clean_after_marking(parser: &Parser, interactive: bool) -> bool {
     });
     for j in completed_jobs {
         save_wait_handle_for_completed_job(&j, &mut parser.mut_wait_handles());
+        // Tell the user about background jobs that finish while they are looking elsewhere.
+        if interactive
+            && !j.is_foreground()
+            && !j.skip_notification()
+            && !terminal_is_focused()
+            && notify_duration(parser.vars()).is_some()
+        {
+            let status = j.get_statuses().map_or(0, |s| s.status);
+            notify_command_finished(j.command(), status);
+        }
     }
 
     // Emit calls to fish_job_summary.
@@ -55,9 +55,11 @@ fn uvar_change_notified(&mut self) {
     }
 
     fn ioport_notified(&mut self) {
-        // Our iothread signaller was posted, indicating some debouncer has a new result.
+        // Our iothread signaller was posted, indicating some debouncer has a new result
+        // or some timer has expired.
         self.debouncers.event_signaller.try_consume();
         self.service_debounced_results();
+        event::fire_expired_timers(self.parser);
     }
 
     fn paste_start_buffering(&mut self) {
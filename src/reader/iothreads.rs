@@ -2,6 +2,7 @@
 //! which might block, such as file I/O or completions.
 
 use super::{Reader, reader};
+use crate::event;
 use crate::fd_monitor::FdEventSignaller;
 use crate::threads::ThreadPool;
 use crate::threads::debounce::Debounce;
@@ -31,9 +32,10 @@ pub(super) struct Debouncers {
 impl Debouncers {
     pub fn new() -> Self {
         let pool = ThreadPool::new(1, IO_MAX_THREADS);
+        // This is shared with timer events, so that an expired timer also wakes up the reader.
+        let event_signaller = Arc::clone(event::timer_signaller());
         // These timeouts control how long until a thread is considered abandoned and
         // any queued work is assigned to a new thread.
-        let event_signaller = Arc::new(FdEventSignaller::new());
         const HIGHLIGHT_TIMEOUT: Duration = Duration::from_millis(500);
         const HISTORY_PAGER_TIMEOUT: Duration = Duration::from_millis(500);
         const AUTOSUGGEST_TIMEOUT: Duration = Duration::from_millis(500);
@@ -95,11 +95,13 @@
 };
 use crate::terminal::TerminalCommand::{
     self, ClearScreen, DecrstAlternateScreenBuffer, DecsetAlternateScreenBuffer, DecsetShowCursor,
-    Osc0WindowTitle, Osc1TabTitle, Osc133CommandFinished, Osc133CommandStart, QueryBackgroundColor,
-    QueryCursorPosition, QueryKittyKeyboardProgressiveEnhancements, QueryPrimaryDeviceAttribute,
-    QueryXtgettcap, QueryXtversion,
+    DesktopNotification, Osc0WindowTitle, Osc1TabTitle, Osc133CommandFinished, Osc133CommandStart,
+    QueryBackgroundColor, QueryCursorPosition, QueryKittyKeyboardProgressiveEnhancements,
+    QueryPrimaryDeviceAttribute, QueryXtgettcap, QueryXtversion,
+};
+use crate::terminal::{
+    BufferedOutputter, NotificationSupport, Output, Outputter, notification_support,
 };
-use crate::terminal::{BufferedOutputter, Output, Outputter};
 use crate::termsize::{safe_termsize_invalidate_tty, termsize_last, termsize_update};
 use crate::text_face::{TextFace, parse_text_face};
 use crate::threads::{assert_is_background_thread, assert_is_main_thread};
@@ -113,7 +115,7 @@
     TtyHandoff, get_tty_protocols_active, initialize_tty_protocols, safe_deactivate_tty_protocols,
 };
 use crate::wildcard::wildcard_has;
-use crate::wutil::{fstat, perror, write_to_fd, wstat};
+use crate::wutil::{fish_wcstoul, fstat, perror, write_to_fd, wstat};
 use crate::{abbrs, event, function};
 use assert_matches::assert_matches;
 use errno::{Errno, errno};
@@ -1269,6 +1271,34 @@ pub fn reader_status_count() -> u64 {
 // interactive command to complete.
 const ENV_CMD_DURATION: &wstr = L!("CMD_DURATION");
 
+// Name of the variable that tells after how many milliseconds a finished command sends a desktop
+// notification.
+const ENV_NOTIFY_DURATION: &wstr = L!("fish_notify_duration");
+
+/// Return how long a command must run before its completion sends a desktop notification, or None
+/// if notifications are disabled or the terminal does not support them.
+pub(crate) fn notify_duration(vars: &dyn Environment) -> Option<Duration> {
+    if notification_support() == NotificationSupport::None {
+        return None;
+    }
+    let var = vars.get(ENV_NOTIFY_DURATION)?;
+    let millis = fish_wcstoul(&var.as_string()).ok()?;
+    Some(Duration::from_millis(millis))
+}
+
+/// Send a desktop notification that the given command has finished.
+pub(crate) fn notify_command_finished(command: &wstr, status: libc::c_int) {
+    let body = if status == 0 {
+        wgettext_fmt!("'%s' has finished", command)
+    } else {
+        wgettext_fmt!("'%s' has failed with status %d", command, status)
+    };
+    BufferedOutputter::new(Outputter::stdoutput()).write_command(DesktopNotification {
+        title: L!("fish"),
+        body: &body,
+    });
+}
+
 /// Maximum length of prefix string when printing completion list. Longer prefixes will be
 /// ellipsized.
 const PREFIX_MAX_LEN: usize = 9;
@@ -6331,6 +6361,9 @@ fn reader_run_command(parser: &Parser, cmd: &wstr) -> EvalRes {
             ParserEnvSetMode::new(EnvMode::UNEXPORT),
             duration.as_millis().to_wstring(),
         );
+        if notify_duration(parser.vars()).is_some_and(|min| duration >= min) {
+            notify_command_finished(cmd, parser.get_last_status());
+        }
     }
 
     term_steal(eval_res.status.is_success());
@@ -61,11 +61,20 @@ pub struct RedirectionSpec {
     /// For example in "3>&1", this will be "1".
     /// In "< file.txt" this will be "file.txt".
     pub target: WString,
+
+    /// For a redirection like "{fd}>file.txt", the variable that receives the newly allocated
+    /// fd. In that case `fd` is ignored.
+    pub fd_var: Option<WString>,
 }
 
 impl RedirectionSpec {
     pub fn new(fd: RawFd, mode: RedirectionMode, target: WString) -> Self {
-        Self { fd, mode, target }
+        Self {
+            fd,
+            mode,
+            target,
+            fd_var: None,
+        }
     }
     /// Return if this is a close-type redirection.
     pub fn is_close(&self) -> bool {
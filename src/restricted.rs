@@ -0,0 +1,101 @@
+//! Restricted mode, enabled with `fish --restricted`.
+//!
+//! Like a restricted bash, a restricted fish does not let the user leave the environment an
+//! administrator set up for them: it refuses to change directory, to change the variables that
+//! decide which programs and scripts get run, to redirect output to files, to `exec`, to run
+//! commands by path, to run loops in parallel and to source files outside of an allow-list. The restrictions are enabled
+//! after the configuration files have been read.
+//!
+//! Every refused attempt fires the `fish_restricted` event, so it can be logged.
+
+use crate::env::Environment;
+use crate::event;
+use crate::global_safety::RelaxedAtomicBool;
+use crate::parser::Parser;
+use crate::prelude::*;
+use crate::wutil::wrealpath;
+
+/// The variable holding the directories from which a restricted shell may source files.
+pub const ENV_RESTRICTED_SOURCE_PATH: &wstr = L!("fish_restricted_source_path");
+
+/// The name of the generic event fired when a restriction is violated.
+pub const RESTRICTED_EVENT_NAME: &wstr = L!("fish_restricted");
+
+/// Variables that may not be changed in restricted mode.
+const RESTRICTED_VARIABLES: &[&wstr] = &[
+    L!("PATH"),
+    L!("SHELL"),
+    L!("ENV"),
+    L!("BASH_ENV"),
+    L!("CDPATH"),
+    L!("LD_PRELOAD"),
+    L!("LD_LIBRARY_PATH"),
+    L!("fish_function_path"),
+    L!("fish_complete_path"),
+    ENV_RESTRICTED_SOURCE_PATH,
+];
+
+static IS_RESTRICTED: RelaxedAtomicBool = RelaxedAtomicBool::new(false);
+
+/// Set while the handlers of the `fish_restricted` event run, so a handler that itself violates a
+/// restriction does not fire the event again.
+static IN_RESTRICTED_EVENT: RelaxedAtomicBool = RelaxedAtomicBool::new(false);
+
+/// Whether fish runs in restricted mode.
+pub fn is_restricted() -> bool {
+    IS_RESTRICTED.load()
+}
+
+/// Enable restricted mode. There is no way back.
+pub fn mark_restricted() {
+    IS_RESTRICTED.store(true);
+}
+
+/// Whether `name` is a variable that may not be changed because we are in restricted mode.
+pub fn is_restricted_variable(name: &wstr) -> bool {
+    is_restricted() && RESTRICTED_VARIABLES.contains(&name)
+}
+
+/// Whether a restricted shell may send output to the file `target`. Only /dev/null is allowed,
+/// which so many functions redirect to that forbidding it would break them.
+pub fn is_allowed_output_target(target: &wstr) -> bool {
+    target == "/dev/null"
+}
+
+/// Whether a restricted shell may source the file at `path`. That is the case for files in
+/// $fish_restricted_source_path and, so autoloading keeps working, in $fish_function_path and
+/// $fish_complete_path.
+pub fn is_allowed_source(path: &wstr, vars: &dyn Environment) -> bool {
+    let Some(real_path) = wrealpath(path) else {
+        return false;
+    };
+    [
+        ENV_RESTRICTED_SOURCE_PATH,
+        L!("fish_function_path"),
+        L!("fish_complete_path"),
+    ]
+    .into_iter()
+    .filter_map(|name| vars.get(name))
+    .flat_map(|var| var.as_list().to_vec())
+    .filter_map(|dir| wrealpath(&dir))
+    .any(|mut dir| {
+        if !dir.ends_with('/') {
+            dir.push('/');
+        }
+        real_path.starts_with(&dir)
+    })
+}
+
+/// Fire the `fish_restricted` event for a refused attempt. `kind` is one of "command", "exec",
+/// "redirection", "cd", "variable", "parallel" or "source", and `detail` names what was attempted.
+pub fn restriction_violated(parser: &Parser, kind: &wstr, detail: &wstr) {
+    if IN_RESTRICTED_EVENT.swap(true) {
+        return;
+    }
+    event::fire_generic(
+        parser,
+        RESTRICTED_EVENT_NAME.to_owned(),
+        vec![kind.to_owned(), detail.to_owned()],
+    );
+    IN_RESTRICTED_EVENT.store(false);
+}
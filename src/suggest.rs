@@ -0,0 +1,123 @@
+//! Suggestions for misspelled command names.
+//!
+//! Candidates are the builtins, functions, command-position abbreviations and executables in
+//! $PATH whose Damerau-Levenshtein distance to the unknown name is small. Listing $PATH is the
+//! expensive part, so it is kept with the command hash table, see [`path_list_commands`].
+
+use crate::abbrs::{self, with_abbrs};
+use crate::builtins::shared::builtin_get_names;
+use crate::env::Environment;
+use crate::function;
+use crate::path::path_list_commands;
+use crate::prelude::*;
+
+/// The Damerau-Levenshtein distance between `a` and `b`, in its "optimal string alignment" form:
+/// the number of insertions, deletions, substitutions and transpositions of adjacent characters
+/// needed to turn one into the other, without editing any substring twice.
+pub fn damerau_levenshtein(a: &[char], b: &[char]) -> usize {
+    // Three rows of the usual dynamic programming matrix suffice.
+    let mut two_ago: Vec<usize> = vec![0; b.len() + 1];
+    let mut one_ago: Vec<usize> = (0..=b.len()).collect();
+    let mut current: Vec<usize> = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (one_ago[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(one_ago[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(two_ago[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut two_ago, &mut one_ago);
+        std::mem::swap(&mut one_ago, &mut current);
+    }
+    one_ago[b.len()]
+}
+
+/// How many edits we allow for a name of the given length. Short names get fewer, or every
+/// two-letter command would look like a typo of every other one.
+fn max_distance(len: usize) -> usize {
+    match len {
+        0..=1 => 0,
+        2..=4 => 1,
+        5..=12 => 2,
+        _ => 3,
+    }
+}
+
+/// Return the commands that `name` might be a misspelling of, closest first.
+pub fn suggest_commands(name: &wstr, vars: &dyn Environment) -> Vec<WString> {
+    let max = max_distance(name.len());
+    if max == 0 || name.contains('/') {
+        return vec![];
+    }
+
+    let mut suggestions: Vec<(usize, WString)> = vec![];
+    let mut consider = |candidate: &wstr| {
+        if candidate == name || candidate.len().abs_diff(name.len()) > max {
+            return;
+        }
+        let distance = damerau_levenshtein(name.as_char_slice(), candidate.as_char_slice());
+        if distance <= max {
+            suggestions.push((distance, candidate.to_owned()));
+        }
+    };
+
+    for builtin in builtin_get_names() {
+        consider(builtin);
+    }
+    for func in function::get_names(false, vars) {
+        consider(&func);
+    }
+    with_abbrs(|set| {
+        for abbr in set.list() {
+            if !abbr.is_regex()
+                && abbr.commands.is_empty()
+                && matches!(
+                    abbr.position,
+                    abbrs::Position::Command | abbrs::Position::Anywhere
+                )
+            {
+                consider(&abbr.key);
+            }
+        }
+    });
+    for cmd in path_list_commands(vars).iter() {
+        consider(cmd);
+    }
+
+    suggestions.sort();
+    suggestions.dedup_by(|a, b| a.1 == b.1);
+    suggestions.into_iter().map(|(_, name)| name).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{damerau_levenshtein, max_distance};
+    use crate::prelude::*;
+
+    #[test]
+    fn test_damerau_levenshtein() {
+        let dl = |a: &wstr, b: &wstr| damerau_levenshtein(a.as_char_slice(), b.as_char_slice());
+        assert_eq!(dl(L!(""), L!("")), 0);
+        assert_eq!(dl(L!("git"), L!("git")), 0);
+        assert_eq!(dl(L!(""), L!("git")), 3);
+        assert_eq!(dl(L!("gti"), L!("git")), 1);
+        assert_eq!(dl(L!("sl"), L!("ls")), 1);
+        assert_eq!(dl(L!("gut"), L!("git")), 1);
+        assert_eq!(dl(L!("gitt"), L!("git")), 1);
+        assert_eq!(dl(L!("kitten"), L!("sitting")), 3);
+        // Optimal string alignment does not edit a transposed pair again.
+        assert_eq!(dl(L!("ca"), L!("abc")), 3);
+    }
+
+    #[test]
+    fn test_max_distance() {
+        assert_eq!(max_distance(1), 0);
+        assert_eq!(max_distance(3), 1);
+        assert_eq!(max_distance(6), 2);
+        assert_eq!(max_distance(20), 3);
+    }
+}
@@ -1,8 +1,10 @@
 // Generic output functions.
 use crate::common::ToCString;
 use crate::common::{self, EscapeStringStyle, escape_string, wcs2bytes, wcs2bytes_appending};
+use crate::env::Environment;
 use crate::flogf;
 use crate::future_feature_flags::{self, FeatureFlag};
+use crate::global_safety::RelaxedAtomicBool;
 use crate::prelude::*;
 use crate::screen::{is_dumb, only_grayscale};
 use crate::text_face::{TextFace, TextStyling, UnderlineStyle};
@@ -39,6 +41,67 @@ pub fn set_color_support(val: ColorSupport) {
     COLOR_SUPPORT.store(val.bits(), Ordering::Relaxed);
 }
 
+/// Which escape sequence, if any, the terminal understands for desktop notifications.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NotificationSupport {
+    None,
+    /// OSC 9, as introduced by iTerm2.
+    Osc9,
+    /// OSC 777, as introduced by rxvt-unicode.
+    Osc777,
+}
+
+static NOTIFICATION_SUPPORT: OnceLock<NotificationSupport> = OnceLock::new();
+
+/// Decide whether the terminal supports desktop notifications, based on its XTVERSION response
+/// and the environment. Terminal multiplexers are not supported, since they would need the
+/// sequence wrapped in a passthrough sequence.
+pub fn detect_notification_support(
+    vars: &dyn Environment,
+    xtversion: &wstr,
+) -> NotificationSupport {
+    let get = |name: &wstr| vars.get(name).map(|v| v.as_string()).unwrap_or_default();
+    let term = get(L!("TERM"));
+    let term_program = get(L!("TERM_PROGRAM"));
+    if ["iTerm2 ", "kitty(", "WezTerm ", "ghostty "]
+        .iter()
+        .any(|prefix| xtversion.starts_with(*prefix))
+        || (xtversion.is_empty()
+            && [L!("iTerm.app"), L!("WezTerm"), L!("ghostty")].contains(&term_program.as_utfstr()))
+    {
+        NotificationSupport::Osc9
+    } else if xtversion.starts_with("foot(")
+        || (xtversion.is_empty() && term.starts_with("rxvt-unicode"))
+    {
+        NotificationSupport::Osc777
+    } else {
+        NotificationSupport::None
+    }
+}
+
+pub fn set_notification_support(val: NotificationSupport) {
+    NOTIFICATION_SUPPORT.get_or_init(|| val);
+}
+
+pub fn notification_support() -> NotificationSupport {
+    NOTIFICATION_SUPPORT
+        .get()
+        .copied()
+        .unwrap_or(NotificationSupport::None)
+}
+
+/// Whether the terminal window has focus. Only tracked while focus reporting is enabled, so this
+/// defaults to true.
+static TERMINAL_FOCUSED: RelaxedAtomicBool = RelaxedAtomicBool::new(true);
+
+pub fn set_terminal_focused(focused: bool) {
+    TERMINAL_FOCUSED.store(focused);
+}
+
+pub fn terminal_is_focused() -> bool {
+    TERMINAL_FOCUSED.load()
+}
+
 #[derive(Debug, Clone, Copy)]
 pub(crate) enum Paintable {
     Foreground,
@@ -106,6 +169,7 @@ pub(crate) enum TerminalCommand<'a> {
     Osc133PromptEnd,
     Osc133CommandStart(&'a wstr),
     Osc133CommandFinished { exit_status: libc::c_int },
+    DesktopNotification { title: &'a wstr, body: &'a wstr },
 
     // Other terminal features
     QueryCursorPosition,
@@ -185,6 +249,7 @@ fn write(out: &mut impl Output, sequence: &'static [u8]) -> bool {
             Osc133PromptEnd => osc_133_prompt_end(self),
             Osc133CommandStart(command) => osc_133_command_start(self, command),
             Osc133CommandFinished { exit_status } => osc_133_command_finished(self, exit_status),
+            DesktopNotification { title, body } => desktop_notification(self, title, body),
             QueryCursorPosition => write(self, b"\x1b[6n"),
             QueryBackgroundColor => write(self, b"\x1b]11;?\x1b\\"),
             ScrollContentUp { lines } => scroll_content_up(self, lines),
@@ -432,6 +497,48 @@ fn osc_133_command_finished(out: &mut impl Output, exit_status: libc::c_int) ->
     true
 }
 
+/// Return the escape sequence that shows a desktop notification with the given `support`, or None
+/// if there is none.
+fn desktop_notification_sequence(
+    support: NotificationSupport,
+    title: &wstr,
+    body: &wstr,
+) -> Option<Vec<u8>> {
+    // Control characters would end the sequence early, and OSC 777 separates fields by ';'.
+    let sanitize = |s: &wstr| -> Vec<u8> {
+        let s: WString = s
+            .chars()
+            .filter(|c| !c.is_control())
+            .map(|c| if c == ';' { ',' } else { c })
+            .collect();
+        wcs2bytes(&s)
+    };
+    let mut seq = Vec::new();
+    match support {
+        NotificationSupport::None => return None,
+        NotificationSupport::Osc9 => {
+            seq.extend_from_slice(b"\x1b]9;");
+            seq.extend(sanitize(body));
+        }
+        NotificationSupport::Osc777 => {
+            seq.extend_from_slice(b"\x1b]777;notify;");
+            seq.extend(sanitize(title));
+            seq.push(b';');
+            seq.extend(sanitize(body));
+        }
+    }
+    seq.push(b'\x07'); // BEL
+    Some(seq)
+}
+
+fn desktop_notification(out: &mut impl Output, title: &wstr, body: &wstr) -> bool {
+    let Some(seq) = desktop_notification_sequence(notification_support(), title, body) else {
+        return false;
+    };
+    out.write_bytes(&seq);
+    true
+}
+
 fn scroll_content_up(out: &mut impl Output, lines: usize) -> bool {
     write_to_output!(out, "\x1b[{}S", lines);
     true
@@ -936,3 +1043,59 @@ pub fn tparm1(cap: &CStr, param1: i32) -> Option<CString> {
     let cap = cap.to_bytes();
     terminfo::expand!(cap; param1).ok().map(|x| x.to_cstring())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{NotificationSupport, desktop_notification_sequence, detect_notification_support};
+    use crate::prelude::*;
+    use crate::tests::prelude::TestEnvironment;
+
+    #[test]
+    fn test_detect_notification_support() {
+        let mut vars = TestEnvironment::new();
+        let detect = |vars: &TestEnvironment, xtversion: &str| {
+            detect_notification_support(vars, &WString::from_str(xtversion))
+        };
+        assert_eq!(detect(&vars, ""), NotificationSupport::None);
+        assert_eq!(detect(&vars, "kitty(0.36.4)"), NotificationSupport::Osc9);
+        assert_eq!(detect(&vars, "WezTerm 20240203"), NotificationSupport::Osc9);
+        assert_eq!(detect(&vars, "foot(1.18.1)"), NotificationSupport::Osc777);
+        assert_eq!(detect(&vars, "tmux 3.4"), NotificationSupport::None);
+
+        vars.vars
+            .insert(L!("TERM_PROGRAM").to_owned(), L!("iTerm.app").to_owned());
+        assert_eq!(detect(&vars, ""), NotificationSupport::Osc9);
+        // A response to XTVERSION takes precedence over the environment, which may be inherited.
+        assert_eq!(detect(&vars, "tmux 3.4"), NotificationSupport::None);
+
+        vars.vars.clear();
+        vars.vars.insert(
+            L!("TERM").to_owned(),
+            L!("rxvt-unicode-256color").to_owned(),
+        );
+        assert_eq!(detect(&vars, ""), NotificationSupport::Osc777);
+    }
+
+    #[test]
+    fn test_desktop_notification_sequence() {
+        let title = L!("fish");
+        let body = L!("'a; b' has\tfinished");
+        assert_eq!(
+            desktop_notification_sequence(NotificationSupport::None, title, body),
+            None
+        );
+        assert_eq!(
+            desktop_notification_sequence(NotificationSupport::Osc9, title, body).unwrap(),
+            b"\x1b]9;'a, b' hasfinished\x07"
+        );
+        assert_eq!(
+            desktop_notification_sequence(NotificationSupport::Osc777, title, body).unwrap(),
+            b"\x1b]777;notify;fish;'a, b' hasfinished\x07"
+        );
+        assert_eq!(
+            desktop_notification_sequence(NotificationSupport::Osc9, title, L!("gr\u{fc}n"))
+                .unwrap(),
+            "\x1b]9;gr\u{fc}n\x07".as_bytes()
+        );
+    }
+}
@@ -18,7 +18,9 @@
 use std::io::Write;
 use std::time::{Duration, Instant};
 
+use crate::env::Environment;
 use crate::nix::{RUsage, getrusage};
+use crate::prelude::*;
 
 enum Unit {
     Minutes,
@@ -27,17 +29,39 @@ enum Unit {
     Micros,
 }
 
-struct TimerSnapshot {
+pub struct TimerSnapshot {
     wall_time: Instant,
     cpu_fish: libc::rusage,
     cpu_children: libc::rusage,
 }
 
+/// The resources used between two `TimerSnapshot` instances.
+#[derive(Clone, Copy, Default)]
+pub struct TimerDelta {
+    pub wall: Duration,
+    pub fish_usr: Duration,
+    pub fish_sys: Duration,
+    pub child_usr: Duration,
+    pub child_sys: Duration,
+    /// The maximum resident set size of the children waited for in between, in KiB. getrusage()
+    /// only reports the maximum over all children fish has waited for, so this is only known if
+    /// that maximum grew in between.
+    pub max_rss_kib: Option<i64>,
+    pub major_faults: i64,
+    pub minor_faults: i64,
+    pub voluntary_switches: i64,
+    pub involuntary_switches: i64,
+}
+
 /// Create a `TimerSnapshot` and return a `PrintElapsedOnDrop` object that will print upon
 /// being dropped the delta between now and the time that it is dropped at.
-pub fn push_timer() -> PrintElapsedOnDrop {
+/// If `$fish_time_format` is set, it is used to format the output.
+pub fn push_timer(vars: &dyn Environment) -> PrintElapsedOnDrop {
     PrintElapsedOnDrop {
         start: TimerSnapshot::take(),
+        format: vars
+            .get(L!("fish_time_format"))
+            .map(|format| format.as_string()),
     }
 }
 
@@ -50,10 +74,8 @@ pub fn take() -> TimerSnapshot {
         }
     }
 
-    /// Returns a formatted string containing the detailed difference between two `TimerSnapshot`
-    /// instances. The returned string can take one of two formats, depending on the value of the
-    /// `verbose` parameter.
-    pub fn get_delta(t1: &TimerSnapshot, t2: &TimerSnapshot, verbose: bool) -> String {
+    /// Returns the resources used between two `TimerSnapshot` instances.
+    pub fn delta(t1: &TimerSnapshot, t2: &TimerSnapshot) -> TimerDelta {
         use crate::nix::timeval_to_duration as from;
 
         let mut fish_sys = from(&t2.cpu_fish.ru_stime) - from(&t1.cpu_fish.ru_stime);
@@ -69,10 +91,66 @@ pub fn get_delta(t1: &TimerSnapshot, t2: &TimerSnapshot, verbose: bool) -> Strin
         fish_usr = fish_usr.max(Duration::ZERO);
         child_sys = child_sys.max(Duration::ZERO);
         child_usr = child_usr.max(Duration::ZERO);
-        // As `Instant` is strictly monotonic, this can't be negative so we don't need to clamp.
-        let net_wall_micros = (t2.wall_time - t1.wall_time).as_micros() as i64;
-        let net_sys_micros = (fish_sys + child_sys).as_micros() as i64;
-        let net_usr_micros = (fish_usr + child_usr).as_micros() as i64;
+
+        // Counters are summed over fish and its children.
+        let counter = |f: fn(&libc::rusage) -> libc::c_long| -> i64 {
+            let fish = f(&t2.cpu_fish) - f(&t1.cpu_fish);
+            let children = f(&t2.cpu_children) - f(&t1.cpu_children);
+            (fish.max(0) + children.max(0)) as i64
+        };
+
+        // ru_maxrss is in bytes on macOS, and in KiB elsewhere.
+        let max_rss_kib = |usage: &libc::rusage| {
+            let max_rss = usage.ru_maxrss as i64;
+            if cfg!(apple) { max_rss / 1024 } else { max_rss }
+        };
+        let max_rss_kib = Some(max_rss_kib(&t2.cpu_children))
+            .filter(|&max_rss| max_rss > max_rss_kib(&t1.cpu_children));
+
+        TimerDelta {
+            // As `Instant` is strictly monotonic, this can't be negative so we don't need to clamp.
+            wall: t2.wall_time - t1.wall_time,
+            fish_usr,
+            fish_sys,
+            child_usr,
+            child_sys,
+            max_rss_kib,
+            major_faults: counter(|r| r.ru_majflt),
+            minor_faults: counter(|r| r.ru_minflt),
+            voluntary_switches: counter(|r| r.ru_nvcsw),
+            involuntary_switches: counter(|r| r.ru_nivcsw),
+        }
+    }
+
+    /// Returns a formatted string containing the detailed difference between two `TimerSnapshot`
+    /// instances. The returned string can take one of two formats, depending on the value of the
+    /// `verbose` parameter.
+    pub fn get_delta(t1: &TimerSnapshot, t2: &TimerSnapshot, verbose: bool) -> String {
+        TimerSnapshot::delta(t1, t2).describe(verbose)
+    }
+}
+
+impl TimerDelta {
+    pub fn usr(&self) -> Duration {
+        self.fish_usr + self.child_usr
+    }
+
+    pub fn sys(&self) -> Duration {
+        self.fish_sys + self.child_sys
+    }
+
+    /// Returns a human-readable table of the used resources.
+    pub fn describe(&self, verbose: bool) -> String {
+        let TimerDelta {
+            fish_sys,
+            fish_usr,
+            child_sys,
+            child_usr,
+            ..
+        } = *self;
+        let net_wall_micros = self.wall.as_micros() as i64;
+        let net_sys_micros = self.sys().as_micros() as i64;
+        let net_usr_micros = self.usr().as_micros() as i64;
 
         let wall_unit = Unit::for_micros(net_wall_micros);
         // Make sure we share the same unit for the various CPU times
@@ -118,29 +196,202 @@ pub fn get_delta(t1: &TimerSnapshot, t2: &TimerSnapshot, verbose: bool) -> Strin
             write!(output, "\n   usr time  {usr_time:6.2} {cpu_unit:<column2_unit_len$}  {fish_usr_time:6.2} {fish_unit}  {child_usr_time:6.2} {child_unit}").unwrap();
             write!(output, "\n   sys time  {sys_time:6.2} {cpu_unit:<column2_unit_len$}  {fish_sys_time:6.2} {fish_unit}  {child_sys_time:6.2} {child_unit}").unwrap();
         };
+        let (major, minor) = (self.major_faults, self.minor_faults);
+        let (voluntary, involuntary) = (self.voluntary_switches, self.involuntary_switches);
+        write!(output, "\npage faults  {major:6} major  {minor} minor").unwrap();
+        write!(
+            output,
+            "\n   switches  {voluntary:6} voluntary  {involuntary} involuntary"
+        )
+        .unwrap();
         output += "\n";
 
         output
     }
+
+    /// Formats the used resources according to `format`, which uses the `%` sequences of GNU
+    /// time(1): `%e`, `%U` and `%S` for the wall, user and system time in seconds, `%P` for the
+    /// CPU percentage, `%M` for the maximum resident set size in KiB or `-` if it is not known,
+    /// `%F` and `%R` for major and minor page faults, `%w` and `%c` for voluntary and involuntary
+    /// context switches and `%%` for a literal percent sign.
+    pub fn format(&self, format: &wstr) -> WString {
+        let mut output = WString::new();
+        let mut chars = format.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                output.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('e') => sprintf!(=> &mut output, "%.2f", self.wall.as_secs_f64()),
+                Some('U') => sprintf!(=> &mut output, "%.2f", self.usr().as_secs_f64()),
+                Some('S') => sprintf!(=> &mut output, "%.2f", self.sys().as_secs_f64()),
+                Some('P') => {
+                    let cpu = (self.usr() + self.sys()).as_secs_f64();
+                    let wall = self.wall.as_secs_f64();
+                    let percent = if wall > 0.0 { cpu / wall * 100.0 } else { 0.0 };
+                    sprintf!(=> &mut output, "%.0f%%", percent);
+                }
+                Some('M') => match self.max_rss_kib {
+                    Some(max_rss) => sprintf!(=> &mut output, "%d", max_rss),
+                    None => output.push('-'),
+                },
+                Some('F') => sprintf!(=> &mut output, "%d", self.major_faults),
+                Some('R') => sprintf!(=> &mut output, "%d", self.minor_faults),
+                Some('w') => sprintf!(=> &mut output, "%d", self.voluntary_switches),
+                Some('c') => sprintf!(=> &mut output, "%d", self.involuntary_switches),
+                Some('%') => output.push('%'),
+                Some(other) => {
+                    output.push('%');
+                    output.push(other);
+                }
+                None => output.push('%'),
+            }
+        }
+        output
+    }
+}
+
+/// Statistics over the wall time of repeated runs, in seconds.
+struct WallStats {
+    mean: f64,
+    stddev: f64,
+    min: f64,
+    max: f64,
+}
+
+impl WallStats {
+    fn new(runs: &[TimerDelta]) -> WallStats {
+        let n = runs.len() as f64;
+        let secs = || runs.iter().map(|run| run.wall.as_secs_f64());
+        let mean = secs().sum::<f64>() / n;
+        // This is the sample standard deviation, which is undefined for a single run.
+        let stddev = if runs.len() > 1 {
+            (secs().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+        } else {
+            0.0
+        };
+        WallStats {
+            mean,
+            stddev,
+            min: secs().fold(f64::INFINITY, f64::min),
+            max: secs().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+/// Returns a human-readable summary of repeated runs of a command.
+pub fn describe_runs(runs: &[TimerDelta], warmup: usize) -> String {
+    assert!(!runs.is_empty(), "No runs to describe");
+    let stats = WallStats::new(runs);
+    let n = runs.len() as u32;
+    let usr = runs.iter().map(TimerDelta::usr).sum::<Duration>() / n;
+    let sys = runs.iter().map(TimerDelta::sys).sum::<Duration>() / n;
+
+    let micros = |secs: f64| (secs * 1E6) as i64;
+    let wall_unit = Unit::for_micros(micros(stats.max));
+    let cpu_unit = Unit::for_micros(usr.max(sys).as_micros() as i64);
+    let wall = |secs: f64| wall_unit.convert_micros(micros(secs));
+    let cpu = |time: Duration| cpu_unit.convert_micros(time.as_micros() as i64);
+    let wall_unit = wall_unit.short_name();
+    let cpu_unit = cpu_unit.short_name();
+
+    let mut output = String::new();
+    output += "\n________________________________________________________";
+    write!(output, "\nExecuted {n} times").unwrap();
+    if warmup > 0 {
+        write!(output, " after {warmup} warmup runs").unwrap();
+    }
+    #[rustfmt::skip]
+    {
+        write!(output, "\n       mean  {:6.2} {wall_unit} ± {:.2} {wall_unit}", wall(stats.mean), wall(stats.stddev)).unwrap();
+        write!(output, "\n        min  {:6.2} {wall_unit}", wall(stats.min)).unwrap();
+        write!(output, "\n        max  {:6.2} {wall_unit}", wall(stats.max)).unwrap();
+        write!(output, "\n   usr time  {:6.2} {cpu_unit} (mean)", cpu(usr)).unwrap();
+        write!(output, "\n   sys time  {:6.2} {cpu_unit} (mean)", cpu(sys)).unwrap();
+    };
+    output += "\n";
+    output
+}
+
+/// Returns a JSON object describing runs of a command. Times are in seconds, the CPU times are
+/// means and the counters are summed over all runs.
+pub fn runs_to_json(runs: &[TimerDelta], warmup: usize) -> String {
+    assert!(!runs.is_empty(), "No runs to describe");
+    let stats = WallStats::new(runs);
+    let n = runs.len() as u32;
+    let usr = runs.iter().map(TimerDelta::usr).sum::<Duration>() / n;
+    let sys = runs.iter().map(TimerDelta::sys).sum::<Duration>() / n;
+    let sum = |f: fn(&TimerDelta) -> i64| runs.iter().map(f).sum::<i64>();
+    let max_rss_kib = match runs.iter().filter_map(|run| run.max_rss_kib).max() {
+        Some(max_rss) => max_rss.to_string(),
+        None => "null".to_owned(),
+    };
+    let major_faults = sum(|run| run.major_faults);
+    let minor_faults = sum(|run| run.minor_faults);
+    let voluntary_switches = sum(|run| run.voluntary_switches);
+    let involuntary_switches = sum(|run| run.involuntary_switches);
+    let times: Vec<String> = runs
+        .iter()
+        .map(|run| format!("{:.6}", run.wall.as_secs_f64()))
+        .collect();
+
+    let mut output = format!(
+        concat!(
+            "{{\"runs\": {}, \"warmup\": {}, ",
+            "\"mean\": {:.6}, \"stddev\": {:.6}, \"min\": {:.6}, \"max\": {:.6}, ",
+            "\"user\": {:.6}, \"system\": {:.6}, \"max_rss_kib\": {}, ",
+            "\"major_faults\": {}, \"minor_faults\": {}, ",
+            "\"voluntary_switches\": {}, \"involuntary_switches\": {}, ",
+            "\"times\": [{}]}}"
+        ),
+        n,
+        warmup,
+        stats.mean,
+        stats.stddev,
+        stats.min,
+        stats.max,
+        usr.as_secs_f64(),
+        sys.as_secs_f64(),
+        max_rss_kib,
+        major_faults,
+        minor_faults,
+        voluntary_switches,
+        involuntary_switches,
+        times.join(", "),
+    );
+    output += "\n";
+    output
 }
 
 /// When dropped, prints to stderr the time that has elapsed since it was initialized.
 pub struct PrintElapsedOnDrop {
     start: TimerSnapshot,
+    /// The value of `$fish_time_format`, if set.
+    format: Option<WString>,
 }
 
 impl Drop for PrintElapsedOnDrop {
     fn drop(&mut self) {
         let end = TimerSnapshot::take();
+        let delta = TimerSnapshot::delta(&self.start, &end);
 
         // Well, this is awkward. By defining `time` as a decorator and not a built-in, there's
         // no associated stream for its output!
-        let output = TimerSnapshot::get_delta(&self.start, &end, true);
+        let output = match &self.format {
+            // Like bash's TIMEFORMAT, an empty format disables the output.
+            Some(format) if format.is_empty() => return,
+            Some(format) => {
+                let mut output = delta.format(format).to_string();
+                output.push('\n');
+                output
+            }
+            None => delta.describe(true) + "\n",
+        };
         let mut stderr = std::io::stderr().lock();
         // There is no bubbling up of errors in a Drop implementation, and it's absolutely forbidden
         // to panic.
         let _ = stderr.write_all(output.as_bytes());
-        let _ = stderr.write_all(b"\n");
     }
 }
 
@@ -187,6 +438,7 @@ fn convert_micros(&self, micros: i64) -> f64 {
 #[cfg(test)]
 mod tests {
     use super::TimerSnapshot;
+    use crate::prelude::*;
     use std::time::Duration;
 
     #[test]
@@ -203,12 +455,29 @@ fn timer_format_and_alignment() {
         t2.cpu_children.ru_utime.tv_usec = 1000;
         t2.cpu_children.ru_stime.tv_usec = 500;
         t2.wall_time = t1.wall_time + Duration::from_micros(500);
+        for t in [&mut t1, &mut t2] {
+            for usage in [&mut t.cpu_fish, &mut t.cpu_children] {
+                usage.ru_majflt = 0;
+                usage.ru_minflt = 0;
+                usage.ru_nvcsw = 0;
+                usage.ru_nivcsw = 0;
+                usage.ru_maxrss = 0;
+            }
+        }
+        t2.cpu_children.ru_maxrss = if cfg!(apple) { 2048 * 1024 } else { 2048 };
+        t2.cpu_children.ru_majflt = 1;
+        t2.cpu_fish.ru_minflt = 20;
+        t2.cpu_children.ru_minflt = 22;
+        t2.cpu_children.ru_nvcsw = 3;
+        t2.cpu_children.ru_nivcsw = 4;
 
         let expected = r#"
 ________________________________________________________
 Executed in  500.00 micros    fish         external
    usr time    1.00 secs      1.00 secs    1.00 millis
    sys time    1.00 secs      1.00 secs    0.50 millis
+page faults       1 major  42 minor
+   switches       3 voluntary  4 involuntary
 "#;
         //        (a)            (b)            (c)
         // (a) remaining columns should align even if there are different units
@@ -216,5 +485,12 @@ fn timer_format_and_alignment() {
         // (c) carry to the next unit when the larger one exceeds 1000
         let actual = TimerSnapshot::get_delta(&t1, &t2, true);
         assert_eq!(actual, expected);
+
+        // The maximum resident set size is only known if it grew while timing.
+        let delta = TimerSnapshot::delta(&t1, &t2);
+        assert_eq!(delta.format(L!("%M KiB")), L!("2048 KiB"));
+        t1.cpu_children.ru_maxrss = t2.cpu_children.ru_maxrss;
+        let delta = TimerSnapshot::delta(&t1, &t2);
+        assert_eq!(delta.format(L!("%M KiB")), L!("- KiB"));
     }
 }
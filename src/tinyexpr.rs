@@ -37,42 +37,63 @@
 use fish_common::assert_sorted_by_name;
 
 #[derive(Clone, Copy)]
-enum Function {
-    Constant(f64),
-    Fn1(fn(f64) -> f64),
-    Fn2(fn(f64, f64) -> f64),
-    FnN(fn(&[f64]) -> f64),
+enum Function<N: Number> {
+    Constant(N),
+    Fn1(fn(N) -> N::Output),
+    Fn2(fn(N, N) -> N::Output),
+    Fn3(fn(N, N, N) -> N::Output),
+    FnN(fn(&[N]) -> N::Output),
+    /// An aggregate like `sum`, which also accepts arguments separated by whitespace.
+    List(fn(&[N]) -> N::Output),
+    /// A function defined with `math --define`, by its index in the list of user functions.
+    User {
+        index: usize,
+        arity: usize,
+    },
 }
 
-impl Debug for Function {
+impl<N: Number> Debug for Function<N> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let variant = match self {
             Function::Constant(n) => return f.debug_tuple("Function::Constant").field(n).finish(),
             Function::Fn1(_) => "Fn1",
             Function::Fn2(_) => "Fn2",
+            Function::Fn3(_) => "Fn3",
             Function::FnN(_) => "FnN",
+            Function::List(_) => "List",
+            Function::User { index, arity } => {
+                return f
+                    .debug_struct("Function::User")
+                    .field("index", index)
+                    .field("arity", arity)
+                    .finish();
+            }
         };
 
         write!(f, "Function::{variant}(_)")
     }
 }
 
-impl Function {
+impl<N: Number> Function<N> {
     pub fn arity(&self) -> Option<usize> {
         match self {
             Function::Constant(_) => Some(0),
             Function::Fn1(_) => Some(1),
             Function::Fn2(_) => Some(2),
-            Function::FnN(_) => None,
+            Function::Fn3(_) => Some(3),
+            Function::FnN(_) | Function::List(_) => None,
+            Function::User { arity, .. } => Some(*arity),
         }
     }
 
-    pub fn call(&self, args: &[f64]) -> f64 {
+    pub fn call(&self, args: &[N]) -> Result<N, ErrorKind> {
         match (self, args) {
-            (Function::Constant(n), []) => *n,
-            (Function::Fn1(f), [a]) => f(*a),
-            (Function::Fn2(f), [a, b]) => f(*a, *b),
-            (Function::FnN(f), args) => f(args),
+            (Function::Constant(n), []) => Ok(*n),
+            (Function::Fn1(f), [a]) => N::check(f(*a)),
+            (Function::Fn2(f), [a, b]) => N::check(f(*a, *b)),
+            (Function::Fn3(f), [a, b, c]) => N::check(f(*a, *b, *c)),
+            (Function::FnN(f) | Function::List(f), args) => N::check(f(args)),
+            (Function::User { .. }, _) => unreachable!("user functions are called by the parser"),
             (_, _) => panic!("Incorrect number of arguments for function call"),
         }
     }
@@ -89,6 +110,13 @@ pub enum ErrorKind {
     LogicalOperator,
     DivByZero,
     NumberTooLarge,
+    NotAnInteger,
+    IntegerOverflow,
+    InvalidShift,
+    ReservedName,
+    InvalidDefinition,
+    Recursion,
+    TooDeep,
     Unknown,
 }
 
@@ -106,6 +134,15 @@ pub fn describe_wstr(&self) -> &'static wstr {
             }
             ErrorKind::DivByZero => wgettext!("Division by zero"),
             ErrorKind::NumberTooLarge => wgettext!("Number is too large"),
+            ErrorKind::NotAnInteger => wgettext!("Only integers are supported with --integer"),
+            ErrorKind::IntegerOverflow => wgettext!("Integer overflow"),
+            ErrorKind::InvalidShift => wgettext!("Shift amount must be between 0 and 127"),
+            ErrorKind::ReservedName => wgettext!("Name is already used by a function or constant"),
+            ErrorKind::InvalidDefinition => {
+                wgettext!("Invalid definition, expected NAME(PARAMETERS) = EXPRESSION")
+            }
+            ErrorKind::Recursion => wgettext!("Function would call itself"),
+            ErrorKind::TooDeep => wgettext!("Function calls are nested too deeply"),
             ErrorKind::Unknown => wgettext!("Expression is bogus"),
         }
     }
@@ -126,38 +163,72 @@ pub enum Operator {
     Div,
     Pow,
     Rem,
-}
-
-impl Operator {
-    pub fn eval(&self, a: f64, b: f64) -> f64 {
-        match self {
-            Operator::Add => a + b,
-            Operator::Sub => a - b,
-            Operator::Mul => a * b,
-            Operator::Div => a / b,
-            Operator::Pow => a.powf(b),
-            Operator::Rem => a % b,
-        }
-    }
+    /// `<<`, only with integers.
+    Shl,
+    /// `>>`, only with integers.
+    Shr,
 }
 
 #[derive(Debug, Clone, Copy)]
-enum Token {
+enum Token<N: Number> {
     Error,
     End,
     Sep,
+    Semicolon,
     Open,
     Close,
-    Number(f64),
-    Function(Function),
+    Number(N),
+    Function(Function<N>),
     Infix(Operator),
 }
 
-struct State<'s> {
+/// The type math computes with: `f64`, or `i128` for `math --integer`.
+trait Number: Copy + Debug + 'static {
+    /// What the builtin functions return. Integer functions can fail, for example on overflow.
+    type Output: Copy;
+    /// The builtin functions and constants, in alphabetical order.
+    const BUILTINS: &'static [(&'static wstr, Function<Self>)];
+    /// Whether the `<<` and `>>` operators exist.
+    const HAS_SHIFT: bool;
+    /// The value of an expression that failed. It is thrown away.
+    const INVALID: Self;
+
+    fn check(output: Self::Output) -> Result<Self, ErrorKind>;
+
+    /// Read the number at the start of `s`. Return how many characters it takes, and the number
+    /// or an error with its position and length in `s`.
+    fn parse(s: &[char]) -> (usize, Result<Self, (ErrorKind, usize, usize)>);
+
+    fn apply(op: Operator, a: Self, b: Self) -> Result<Self, ErrorKind>;
+
+    fn negate(self) -> Result<Self, ErrorKind>;
+}
+
+/// A function defined with `math --define`.
+#[derive(Debug, Clone)]
+pub struct UserFunction {
+    pub name: WString,
+    params: Vec<WString>,
+    body: WString,
+}
+
+/// How deeply user functions may call each other.
+const MAX_CALL_DEPTH: usize = 64;
+
+struct State<'s, N: Number> {
     start: &'s wstr,
     pos: usize,
-    current: Token,
+    /// Where the current token starts.
+    token_start: usize,
+    current: Token<N>,
     error: Option<Error>,
+    variables: &'s mut Vec<(WString, N)>,
+    functions: &'s [UserFunction],
+    /// How many user function calls we are nested in.
+    depth: usize,
+    /// Whether we are directly in the parenthesized arguments of an aggregate function like `sum`,
+    /// where they may also be separated by whitespace.
+    in_list: bool,
 }
 
 fn bitwise_op(a: f64, b: f64, f: fn(u64, u64) -> u64) -> f64 {
@@ -260,7 +331,81 @@ fn npr(n: f64, r: f64) -> f64 {
     ncr(n, r) * fac(r)
 }
 
-const BUILTINS: &[(&wstr, Function)] = &[
+fn clamp(x: f64, lo: f64, hi: f64) -> f64 {
+    if lo > hi {
+        return f64::NAN;
+    }
+    x.clamp(lo, hi)
+}
+
+fn sum(n: &[f64]) -> f64 {
+    // Neumaier's compensated summation, so rounding errors don't add up.
+    let mut total = 0.0;
+    let mut compensation = 0.0;
+    for &x in n {
+        let t = total + x;
+        if f64::abs(total) >= f64::abs(x) {
+            compensation += (total - t) + x;
+        } else {
+            compensation += (x - t) + total;
+        }
+        total = t;
+    }
+    total + compensation
+}
+
+fn mean(n: &[f64]) -> f64 {
+    sum(n) / n.len() as f64
+}
+
+/// Sort the values, or return None if one is NaN.
+fn sorted(n: &[f64]) -> Option<Vec<f64>> {
+    if n.iter().any(|x| x.is_nan()) {
+        return None;
+    }
+    let mut n = n.to_vec();
+    n.sort_by(f64::total_cmp);
+    Some(n)
+}
+
+fn median(n: &[f64]) -> f64 {
+    let Some(n) = sorted(n) else {
+        return f64::NAN;
+    };
+    let mid = n.len() / 2;
+    if n.len() % 2 == 0 {
+        (n[mid - 1] + n[mid]) / 2.0
+    } else {
+        n[mid]
+    }
+}
+
+/// The population standard deviation.
+fn stddev(n: &[f64]) -> f64 {
+    let mean = mean(n);
+    let squares: Vec<f64> = n.iter().map(|x| (x - mean).powi(2)).collect();
+    (sum(&squares) / n.len() as f64).sqrt()
+}
+
+/// The percentile given by the first argument of the rest, interpolating linearly between the
+/// closest values.
+fn percentile(n: &[f64]) -> f64 {
+    let [p, values @ ..] = n else {
+        return f64::NAN;
+    };
+    if values.is_empty() || !(0.0..=100.0).contains(p) {
+        return f64::NAN;
+    }
+    let Some(values) = sorted(values) else {
+        return f64::NAN;
+    };
+    let rank = p / 100.0 * (values.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    values[lower] + (values[upper] - values[lower]) * (rank - lower as f64)
+}
+
+const BUILTINS: &[(&wstr, Function<f64>)] = &[
     // must be in alphabetical order
     (L!("abs"), Function::Fn1(f64::abs)),
     (L!("acos"), Function::Fn1(f64::acos)),
@@ -281,6 +426,7 @@ fn npr(n: f64, r: f64) -> f64 {
     ),
     #[allow(clippy::incompatible_msrv)]
     (L!("ceil"), Function::Fn1(f64::ceil)),
+    (L!("clamp"), Function::Fn3(clamp)),
     (L!("cos"), Function::Fn1(f64::cos)),
     (L!("cosh"), Function::Fn1(f64::cosh)),
     (L!("e"), Function::Constant(E)),
@@ -293,9 +439,12 @@ fn npr(n: f64, r: f64) -> f64 {
     (L!("log10"), Function::Fn1(f64::log10)),
     (L!("log2"), Function::Fn1(f64::log2)),
     (L!("max"), Function::FnN(maximum)),
+    (L!("mean"), Function::List(mean)),
+    (L!("median"), Function::List(median)),
     (L!("min"), Function::FnN(minimum)),
     (L!("ncr"), Function::Fn2(ncr)),
     (L!("npr"), Function::Fn2(npr)),
+    (L!("percentile"), Function::List(percentile)),
     (L!("pi"), Function::Constant(PI)),
     (L!("pow"), Function::Fn2(f64::powf)),
     #[allow(clippy::incompatible_msrv)]
@@ -303,6 +452,8 @@ fn npr(n: f64, r: f64) -> f64 {
     (L!("sin"), Function::Fn1(f64::sin)),
     (L!("sinh"), Function::Fn1(f64::sinh)),
     (L!("sqrt"), Function::Fn1(f64::sqrt)),
+    (L!("stddev"), Function::List(stddev)),
+    (L!("sum"), Function::List(sum)),
     (L!("tan"), Function::Fn1(f64::tan)),
     (L!("tanh"), Function::Fn1(f64::tanh)),
     (L!("tau"), Function::Constant(TAU)),
@@ -310,24 +461,111 @@ fn npr(n: f64, r: f64) -> f64 {
 
 assert_sorted_by_name!(BUILTINS, 0);
 
-fn find_builtin(name: &wstr) -> Option<Function> {
-    let idx = BUILTINS
+impl Number for f64 {
+    type Output = f64;
+    const BUILTINS: &'static [(&'static wstr, Function<f64>)] = BUILTINS;
+    const HAS_SHIFT: bool = false;
+    const INVALID: f64 = f64::NAN;
+
+    fn check(output: f64) -> Result<f64, ErrorKind> {
+        Ok(output)
+    }
+
+    fn parse(s: &[char]) -> (usize, Result<f64, (ErrorKind, usize, usize)>) {
+        let mut consumed = 0;
+        let result = match wcstod_underscores(s, &mut consumed) {
+            Ok(num) => Ok(num),
+            Err(wcstodError::InvalidChar) => Err((ErrorKind::Unknown, consumed, 1)),
+            Err(wcstodError::Overflow) => Err((ErrorKind::NumberTooLarge, 0, consumed)),
+            Err(wcstodError::Empty) => unreachable!("numbers start with a digit or a dot"),
+        };
+        (consumed, result)
+    }
+
+    fn apply(op: Operator, a: f64, b: f64) -> Result<f64, ErrorKind> {
+        Ok(match op {
+            Operator::Add => a + b,
+            Operator::Sub => a - b,
+            Operator::Mul => a * b,
+            // Division by zero (also for modulo)
+            Operator::Div | Operator::Rem if b == 0.0 => return Err(ErrorKind::DivByZero),
+            Operator::Div => a / b,
+            Operator::Pow => a.powf(b),
+            Operator::Rem => a % b,
+            Operator::Shl | Operator::Shr => unreachable!("there are no shifts for floats"),
+        })
+    }
+
+    fn negate(self) -> Result<f64, ErrorKind> {
+        Ok(-self)
+    }
+}
+
+fn find_builtin<N: Number>(name: &wstr) -> Option<Function<N>> {
+    let idx = N::BUILTINS
         .binary_search_by_key(&name, |(name, _expr)| name)
         .ok()?;
 
-    Some(BUILTINS[idx].1)
+    Some(N::BUILTINS[idx].1)
+}
+
+/// Whether `name` may not be used for a variable or a new function, because it names a builtin
+/// function or constant.
+pub fn is_reserved_name(name: &wstr) -> bool {
+    find_builtin::<f64>(name).is_some()
 }
 
-impl<'s> State<'s> {
-    pub fn new(input: &'s wstr) -> Self {
-        let mut state = Self {
+/// The length of the identifier at the start of `s`, or 0 if there is none.
+fn ident_len(s: &[char]) -> usize {
+    // We look for alphabetic here even tho all our function names are ASCII,
+    // in order to give a nicer error.
+    if !s.first().is_some_and(|c| c.is_alphabetic()) {
+        return 0;
+    }
+    s.iter()
+        .position(|&c| !(c.is_alphabetic() || c.is_ascii_digit() || c == '_'))
+        .unwrap_or(s.len())
+}
+
+/// Whether `name` can be used for a variable or function.
+pub fn is_valid_name(name: &wstr) -> bool {
+    !name.is_empty() && ident_len(name.as_char_slice()) == name.len()
+}
+
+fn is_space(c: char) -> bool {
+    " \t\n\r".contains(c)
+}
+
+/// If the statement `s` starts with `NAME =`, return the start and end of the name and the
+/// position of the `=`.
+fn find_assignment(s: &[char]) -> Option<(usize, usize, usize)> {
+    let name_start = s.iter().take_while(|&&c| is_space(c)).count();
+    let name_end = name_start + ident_len(&s[name_start..]);
+    let eq = name_end + s[name_end..].iter().take_while(|&&c| is_space(c)).count();
+    if name_end == name_start || s.get(eq) != Some(&'=') || s.get(eq + 1) == Some(&'=') {
+        return None;
+    }
+    Some((name_start, name_end, eq))
+}
+
+impl<'s, N: Number> State<'s, N> {
+    fn new(
+        input: &'s wstr,
+        variables: &'s mut Vec<(WString, N)>,
+        functions: &'s [UserFunction],
+        depth: usize,
+    ) -> Self {
+        Self {
             start: input,
             pos: 0,
+            token_start: 0,
             current: Token::End,
             error: None,
-        };
-        state.next_token();
-        state
+            variables,
+            functions,
+            depth,
+            in_list: false,
+        }
     }
 
     pub fn error(&self) -> Result<(), Error> {
@@ -346,8 +584,132 @@ pub fn error(&self) -> Result<(), Error> {
         }
     }
 
-    pub fn eval(&mut self) -> f64 {
-        self.expr()
+    /// ```text
+    /// <list>   = <statement> {";" <statement>} [";"]
+    /// <statement> = [<name> "="] <shift>
+    /// ```
+    /// The value is that of the last statement.
+    pub fn eval(&mut self) -> N {
+        loop {
+            let target = self.assignment_target();
+            if self.error.is_some() {
+                return N::INVALID;
+            }
+            self.next_token();
+            let ret = self.shift();
+            if let Some(name) = target {
+                self.set_variable(name, ret);
+            }
+            if !matches!(self.current, Token::Semicolon) {
+                return ret;
+            }
+            if self.start[self.pos..].chars().all(is_space) {
+                // A trailing semicolon is fine.
+                self.current = Token::End;
+                return ret;
+            }
+        }
+    }
+
+    /// If the statement at the current position starts with `NAME =`, consume that and return the
+    /// name.
+    fn assignment_target(&mut self) -> Option<WString> {
+        let rest = &self.start.as_char_slice()[self.pos..];
+        let (name_start, name_end, eq) = find_assignment(rest)?;
+        let name_len = name_end - name_start;
+        let name = wstr::from_char_slice(&rest[name_start..name_end]);
+        if is_reserved_name(name) || self.functions.iter().any(|f| f.name == name) {
+            self.set_error(
+                ErrorKind::ReservedName,
+                Some((self.pos + name_start, name_len)),
+            );
+            return None;
+        }
+        self.pos += eq + 1;
+        Some(name.to_owned())
+    }
+
+    fn set_variable(&mut self, name: WString, value: N) {
+        if let Some(var) = self.variables.iter_mut().find(|(var, _)| *var == name) {
+            var.1 = value;
+        } else {
+            self.variables.push((name, value));
+        }
+    }
+
+    /// Look up a variable, a user function or a builtin, in that order.
+    fn lookup(&self, name: &wstr) -> Option<Function<N>> {
+        if let Some((_, value)) = self.variables.iter().find(|(var, _)| var == name) {
+            return Some(Function::Constant(*value));
+        }
+        if let Some(index) = self.functions.iter().position(|f| f.name == name) {
+            let arity = self.functions[index].params.len();
+            return Some(Function::User { index, arity });
+        }
+        find_builtin(name)
+    }
+
+    /// Whether the current `+` or `-` token is a sign rather than an operator, because it follows
+    /// whitespace but does not precede it.
+    fn is_sign(&self) -> bool {
+        let chars = self.start.as_char_slice();
+        self.token_start > 0
+            && is_space(chars[self.token_start - 1])
+            && chars.get(self.pos).is_some_and(|&c| !is_space(c))
+    }
+
+    /// Call `f`. Its errors, also those in the body of a user function, are reported at its
+    /// `call_site`.
+    fn call(&mut self, f: Function<N>, args: &[N], call_site: (usize, usize)) -> N {
+        if self.error.is_some() {
+            // The result is thrown away anyway, don't evaluate the body of a user function.
+            return N::INVALID;
+        }
+        let Function::User { index, .. } = f else {
+            return self.checked(f.call(args), call_site);
+        };
+        if self.depth >= MAX_CALL_DEPTH {
+            self.set_error(ErrorKind::TooDeep, Some(call_site));
+            return N::INVALID;
+        }
+        let functions = self.functions;
+        let function = &functions[index];
+        let mut variables = function
+            .params
+            .iter()
+            .cloned()
+            .zip(args.iter().copied())
+            .collect();
+        let mut state = State::new(&function.body, &mut variables, functions, self.depth + 1);
+        let ret = state.eval();
+        match state.error() {
+            Ok(()) => ret,
+            Err(err) => {
+                self.set_error(err.kind, Some(call_site));
+                N::INVALID
+            }
+        }
+    }
+
+    /// Return the value of `result`, or report its error at `pos_len`.
+    fn checked(&mut self, result: Result<N, ErrorKind>, pos_len: (usize, usize)) -> N {
+        if self.error.is_some() {
+            return N::INVALID;
+        }
+        result.unwrap_or_else(|kind| {
+            self.set_error(kind, Some(pos_len));
+            N::INVALID
+        })
+    }
+
+    /// Apply `op`, reporting errors at the operator at `op_pos`.
+    fn apply(&mut self, op: Operator, a: N, b: N, op_pos: usize) -> N {
+        let len = if matches!(op, Operator::Shl | Operator::Shr) {
+            2
+        } else {
+            1
+        };
+        self.checked(N::apply(op, a, b), (op_pos, len))
     }
 
     fn set_error(&mut self, kind: ErrorKind, pos_len: Option<(usize, usize)>) {
@@ -374,53 +736,56 @@ fn no_specific_error(&self) -> bool {
     /// Tries to get the next token from the input. If the input does not contain enough data for
     /// another token, `None` is returned. Otherwise, the number of consumed characters is returned
     /// along with either the token, or `None` in case of ignored (whitespace) input.
-    fn get_token(&mut self) -> Option<(usize, Option<Token>)> {
+    fn get_token(&mut self) -> Option<(usize, Option<Token<N>>)> {
         debug_assert!(!matches!(self.current, Token::Error));
 
         let next = &self.start.as_char_slice().get(self.pos..)?;
 
         // Try reading a number.
         if matches!(next.first(), Some('0'..='9') | Some('.')) {
-            let mut consumed = 0;
-            match wcstod_underscores(*next, &mut consumed) {
+            let (consumed, result) = N::parse(next);
+            match result {
                 Ok(num) => Some((consumed, Some(Token::Number(num)))),
-                Err(wcstodError::InvalidChar) => {
-                    self.set_error(ErrorKind::Unknown, Some((self.pos + consumed, 1)));
-                    Some((consumed, Some(Token::Error)))
-                }
-                Err(wcstodError::Overflow) => {
-                    self.set_error(ErrorKind::NumberTooLarge, Some((self.pos, consumed)));
+                Err((kind, position, len)) => {
+                    self.set_error(kind, Some((self.pos + position, len)));
                     Some((consumed, Some(Token::Error)))
                 }
-                Err(wcstodError::Empty) => {
-                    // We have a matches! above, this can't be?
-                    unreachable!()
-                }
             }
         } else {
-            // Look for a function call.
+            // Look for a function call or variable.
             // But not when it's an "x" followed by whitespace
-            // - that's the alternative multiplication operator.
-            // We look for alphabetic here even tho all our function names are ASCII,
-            // in order to give a nicer error.
-            if next.first()?.is_alphabetic()
-                && !(*next.first()? == 'x' && next.len() > 1 && next[1].is_whitespace())
+            // - that's the alternative multiplication operator,
+            // unless there is a variable called "x".
+            let ident_len = ident_len(next);
+            if ident_len != 0
+                && !(*next.first()? == 'x'
+                    && next.len() > 1
+                    && next[1].is_whitespace()
+                    && !self.variables.iter().any(|(var, _)| var == "x"))
             {
-                let ident_len = next
-                    .iter()
-                    .position(|&c| !(c.is_alphabetic() || c.is_ascii_digit() || c == '_'))
-                    .unwrap_or(next.len());
-
-                let ident = &next[..ident_len];
-                if let Some(var) = find_builtin(wstr::from_char_slice(ident)) {
+                let ident = wstr::from_char_slice(&next[..ident_len]);
+                if let Some(var) = self.lookup(ident) {
                     return Some((ident_len, Some(Token::Function(var))));
                 } else if self.no_specific_error() {
                     // Our error is more specific, so it takes precedence.
-                    self.set_error(ErrorKind::UnknownFunction, Some((self.pos, ident_len)));
+                    // A function that is missing with integers only makes sense for floats.
+                    let kind = if is_reserved_name(ident) {
+                        ErrorKind::NotAnInteger
+                    } else {
+                        ErrorKind::UnknownFunction
+                    };
+                    self.set_error(kind, Some((self.pos, ident_len)));
                 }
 
                 Some((ident_len, Some(Token::Error)))
             } else {
+                if N::HAS_SHIFT {
+                    match **next {
+                        ['<', '<', ..] => return Some((2, Some(Token::Infix(Operator::Shl)))),
+                        ['>', '>', ..] => return Some((2, Some(Token::Infix(Operator::Shr)))),
+                        _ => (),
+                    }
+                }
                 // Look for an operator or special character.
                 let tok = match next.first()? {
                     '+' => Token::Infix(Operator::Add),
@@ -432,6 +797,7 @@ fn get_token(&mut self) -> Option<(usize, Option<Token>)> {
                     '(' => Token::Open,
                     ')' => Token::Close,
                     ',' => Token::Sep,
+                    ';' => Token::Semicolon,
                     ' ' | '\t' | '\n' | '\r' => return Some((1, None)),
                     '=' | '>' | '<' | '&' | '|' | '!' => {
                         self.set_error(ErrorKind::LogicalOperator, None);
@@ -451,11 +817,14 @@ fn get_token(&mut self) -> Option<(usize, Option<Token>)> {
     fn next_token(&mut self) {
         self.current = loop {
             let Some((consumed, token)) = self.get_token() else {
+                self.token_start = self.pos;
                 break Token::End;
             };
 
+            let start = self.pos;
             self.pos += consumed;
             if let Some(token) = token {
+                self.token_start = start;
                 break token;
             }
         };
@@ -465,16 +834,16 @@ fn next_token(&mut self) {
     /// <base>   = <constant> |
     ///            <function-0> {"(" ")"} |
     ///            <function-1> <power> |
-    ///            <function-X> "(" <expr> {"," <expr>} ")" |
-    ///            "(" <list> ")"
+    ///            <function-X> "(" <shift> {"," <shift>} ")" |
+    ///            "(" <shift> ")"
     /// ```
-    fn base(&mut self) -> f64 {
+    fn base(&mut self) -> N {
         match self.current {
             Token::Number(n) => {
                 let after_first = self.pos;
 
                 self.next_token();
-                if let Token::Number(_) | Token::Function(_) = self.current {
+                if !self.in_list && matches!(self.current, Token::Number(_) | Token::Function(_)) {
                     // Two numbers after each other:
                     // math '5 2'
                     // math '3 pi'
@@ -496,102 +865,27 @@ fn base(&mut self) -> f64 {
                 n
             }
             Token::Function(f) => {
+                let call_site = (self.token_start, self.pos - self.token_start);
                 self.next_token();
                 let have_open = matches!(self.current, Token::Open);
+                let outer_in_list = self.in_list;
                 if have_open {
                     // If we *have* an opening parenthesis,
                     // we need to consume it and
                     // expect a closing one.
+                    self.in_list = matches!(f, Function::List(_));
                     self.next_token();
                 }
-
-                if f.arity() == Some(0) {
-                    if have_open {
-                        if let Token::Close = self.current {
-                            self.next_token();
-                        } else if self.no_specific_error() {
-                            self.set_error(ErrorKind::MissingClosingParen, None);
-                        }
-                    }
-
-                    return match f {
-                        Function::Constant(n) => n,
-                        _ => unreachable!("unhandled function type with arity 0"),
-                    };
-                }
-
-                let mut parameters = vec![];
-                let mut i = 0;
-                let mut first_err = None;
-                for j in 0.. {
-                    if f.arity() == Some(j) {
-                        first_err = Some(self.pos - 1);
-                    }
-                    parameters.push(self.expr());
-                    if !matches!(self.current, Token::Sep) {
-                        break;
-                    }
-                    self.next_token();
-                    i += 1;
-                }
-
-                if f.arity().is_none() || f.arity() == Some(i + 1) {
-                    if !have_open {
-                        return f.call(&parameters);
-                    }
-                    if let Token::Close = self.current {
-                        // We have an opening and a closing paren, consume the closing one and done.
-                        self.next_token();
-                        return f.call(&parameters);
-                    }
-                    if !matches!(self.current, Token::Error) {
-                        // If we had the right number of arguments, we're missing a closing paren.
-                        self.set_error(ErrorKind::MissingClosingParen, None);
-                    }
-                }
-
-                if !matches!(self.current, Token::Error)
-                    || matches!(
-                        self.error,
-                        Some(Error {
-                            kind: ErrorKind::UnexpectedToken,
-                            ..
-                        })
-                    )
-                {
-                    // Otherwise we complain about the number of arguments *first*,
-                    // a closing parenthesis should be more obvious.
-                    //
-                    // Vararg functions need at least one argument.
-                    let err = if f.arity().map_or(i == 0, |arity| i < arity) {
-                        ErrorKind::TooFewArgs
-                    } else {
-                        ErrorKind::TooManyArgs
-                    };
-
-                    let mut err_pos_len = None;
-                    if let Some(first_err) = first_err {
-                        let mut len = self.pos - first_err;
-                        if !matches!(self.current, Token::Close) {
-                            // TODO: Rationalize where we put the cursor exactly.
-                            // If we have a closing paren it's on it, if we don't it's before the number.
-                            len += 1;
-                        }
-                        if let Token::End = self.current {
-                            // Don't place a caret after the end of string
-                            len -= 1;
-                        }
-                        err_pos_len = Some((first_err, len));
-                    }
-
-                    self.set_error(err, err_pos_len);
-                }
-
-                f64::NAN
+                let ret = self.function_call(f, have_open, call_site);
+                self.in_list = outer_in_list;
+                ret
             }
             Token::Open => {
+                let outer_in_list = self.in_list;
+                self.in_list = false;
                 self.next_token();
-                let ret = self.expr();
+                let ret = self.shift();
+                self.in_list = outer_in_list;
                 if let Token::Close = self.current {
                     self.next_token();
                     return ret;
@@ -603,7 +897,7 @@ fn base(&mut self) -> f64 {
                     self.set_error(ErrorKind::MissingClosingParen, None);
                 }
 
-                f64::NAN
+                N::INVALID
             }
             Token::End => {
                 // The expression ended before we expected it.
@@ -613,25 +907,122 @@ fn base(&mut self) -> f64 {
                 // "too few args".
                 self.set_error(ErrorKind::TooFewArgs, None);
 
-                f64::NAN
+                N::INVALID
             }
 
-            Token::Error | Token::Sep | Token::Close | Token::Infix(_) => {
+            Token::Error | Token::Sep | Token::Semicolon | Token::Close | Token::Infix(_) => {
                 if self.no_specific_error() {
                     self.set_error(ErrorKind::UnexpectedToken, None);
                 }
 
-                f64::NAN
+                N::INVALID
             }
         }
     }
 
+    /// Parse the arguments of a call to `f`, after the opening parenthesis if there is one, and
+    /// call it.
+    fn function_call(&mut self, f: Function<N>, have_open: bool, call_site: (usize, usize)) -> N {
+        if f.arity() == Some(0) {
+            if have_open {
+                if let Token::Close = self.current {
+                    self.next_token();
+                } else if self.no_specific_error() {
+                    self.set_error(ErrorKind::MissingClosingParen, None);
+                }
+            }
+
+            return match f {
+                Function::Constant(n) => n,
+                Function::User { .. } => self.call(f, &[], call_site),
+                _ => unreachable!("unhandled function type with arity 0"),
+            };
+        }
+
+        let mut parameters = vec![];
+        let mut i = 0;
+        let mut first_err = None;
+        for j in 0.. {
+            if f.arity() == Some(j) {
+                first_err = Some(self.pos - 1);
+            }
+            parameters.push(self.shift());
+            match self.current {
+                Token::Sep => self.next_token(),
+                // In a list, arguments may also be separated by whitespace.
+                Token::Number(_)
+                | Token::Function(_)
+                | Token::Open
+                | Token::Infix(Operator::Add | Operator::Sub)
+                    if self.in_list && have_open => {}
+                _ => break,
+            }
+            i += 1;
+        }
+
+        if f.arity().is_none() || f.arity() == Some(i + 1) {
+            if !have_open {
+                return self.call(f, &parameters, call_site);
+            }
+            if let Token::Close = self.current {
+                // We have an opening and a closing paren, consume the closing one and done.
+                self.next_token();
+                return self.call(f, &parameters, call_site);
+            }
+            if !matches!(self.current, Token::Error) {
+                // If we had the right number of arguments, we're missing a closing paren.
+                self.set_error(ErrorKind::MissingClosingParen, None);
+            }
+        }
+
+        if !matches!(self.current, Token::Error)
+            || matches!(
+                self.error,
+                Some(Error {
+                    kind: ErrorKind::UnexpectedToken,
+                    ..
+                })
+            )
+        {
+            // Otherwise we complain about the number of arguments *first*,
+            // a closing parenthesis should be more obvious.
+            //
+            // Vararg functions need at least one argument.
+            let err = if f.arity().map_or(i == 0, |arity| i < arity) {
+                ErrorKind::TooFewArgs
+            } else {
+                ErrorKind::TooManyArgs
+            };
+
+            let mut err_pos_len = None;
+            if let Some(first_err) = first_err {
+                let mut len = self.pos - first_err;
+                if !matches!(self.current, Token::Close) {
+                    // TODO: Rationalize where we put the cursor exactly.
+                    // If we have a closing paren it's on it, if we don't it's before the number.
+                    len += 1;
+                }
+                if let Token::End = self.current {
+                    // Don't place a caret after the end of string
+                    len -= 1;
+                }
+                err_pos_len = Some((first_err, len));
+            }
+
+            self.set_error(err, err_pos_len);
+        }
+
+        N::INVALID
+    }
+
     /// \<power\>  = {("-" | "+")} \<base\>
-    fn power(&mut self) -> f64 {
-        let mut sign = 1.0;
+    fn power(&mut self) -> N {
+        let mut negative = false;
+        let mut sign_pos = self.token_start;
         while let Token::Infix(op) = self.current {
             if op == Operator::Sub {
-                sign = -sign;
+                negative = !negative;
+                sign_pos = self.token_start;
                 self.next_token();
             } else if op == Operator::Add {
                 self.next_token();
@@ -640,46 +1031,68 @@ fn power(&mut self) -> f64 {
             }
         }
 
-        sign * self.base()
+        let ret = self.base();
+        if !negative {
+            return ret;
+        }
+        self.checked(ret.negate(), (sign_pos, 1))
     }
 
     /// \<factor\> = \<power\> {"^" \<power\>}
-    fn factor(&mut self) -> f64 {
-        let mut ret = self.power();
+    fn factor(&mut self) -> N {
+        let ret = self.power();
 
         if let Token::Infix(Operator::Pow) = self.current {
+            let op_pos = self.token_start;
             self.next_token();
-            ret = ret.powf(self.factor());
+            let rhs = self.factor();
+            return self.apply(Operator::Pow, ret, rhs, op_pos);
         }
 
         ret
     }
 
     /// \<term\>   = \<factor\> {("*" | "/" | "%") \<factor\>}
-    fn term(&mut self) -> f64 {
+    fn term(&mut self) -> N {
         let mut ret = self.factor();
         while let Token::Infix(op @ (Operator::Mul | Operator::Div | Operator::Rem)) = self.current
         {
-            let op_pos = self.pos - 1;
+            let op_pos = self.token_start;
             self.next_token();
-            let ret2 = self.factor();
-            if ret2 == 0.0 && [Operator::Div, Operator::Rem].contains(&op) {
-                // Division by zero (also for modulo)
-                // Error position is the "/" or "%" sign for now
-                self.set_error(ErrorKind::DivByZero, Some((op_pos, 1)));
-            }
-            ret = op.eval(ret, ret2);
+            let rhs = self.factor();
+            ret = self.apply(op, ret, rhs, op_pos);
         }
 
         ret
     }
 
     /// \<expr\>   = \<term\> {("+" | "-") \<term\>}
-    fn expr(&mut self) -> f64 {
+    fn expr(&mut self) -> N {
         let mut ret = self.term();
         while let Token::Infix(op @ (Operator::Add | Operator::Sub)) = self.current {
+            if self.in_list && self.is_sign() {
+                // In a list, `1 -2` is two arguments.
+                break;
+            }
+            let op_pos = self.token_start;
+            self.next_token();
+            let rhs = self.term();
+            ret = self.apply(op, ret, rhs, op_pos);
+        }
+
+        ret
+    }
+
+    /// \<shift\>  = \<expr\> {("<<" | ">>") \<expr\>}
+    ///
+    /// Only integers have these operators.
+    fn shift(&mut self) -> N {
+        let mut ret = self.expr();
+        while let Token::Infix(op @ (Operator::Shl | Operator::Shr)) = self.current {
+            let op_pos = self.token_start;
             self.next_token();
-            ret = op.eval(ret, self.term());
+            let rhs = self.expr();
+            ret = self.apply(op, ret, rhs, op_pos);
         }
 
         ret
@@ -687,7 +1100,17 @@ fn expr(&mut self) -> f64 {
 }
 
 pub fn te_interp(expression: &wstr) -> Result<f64, Error> {
-    let mut s = State::new(expression);
+    te_interp_with(expression, &mut vec![], &[])
+}
+
+/// Evaluate `expression` with the given variables, which assignments in the expression change,
+/// and user functions.
+pub fn te_interp_with(
+    expression: &wstr,
+    variables: &mut Vec<(WString, f64)>,
+    functions: &[UserFunction],
+) -> Result<f64, Error> {
+    let mut s = State::new(expression, variables, functions, 0);
     let ret = s.eval();
 
     match s.error() {
@@ -695,3 +1118,283 @@ pub fn te_interp(expression: &wstr) -> Result<f64, Error> {
         Err(e) => Err(e),
     }
 }
+
+/// The identifiers in `body` that are not `params`, with their positions and lengths. Numbers are
+/// skipped, so the `e` of `1e5` is not one.
+fn identifiers<'a>(body: &'a wstr, params: &'a [WString]) -> Vec<(&'a wstr, usize, usize)> {
+    let chars = body.as_char_slice();
+    let mut result = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_ascii_digit() || c == '.' {
+            i += chars[i..]
+                .iter()
+                .take_while(|&&c| c.is_alphanumeric() || c == '.' || c == '_')
+                .count();
+            continue;
+        }
+        let len = ident_len(&chars[i..]);
+        if len == 0 {
+            i += 1;
+            continue;
+        }
+        let ident = wstr::from_char_slice(&chars[i..i + len]);
+        if !params.iter().any(|p| p == ident) {
+            result.push((ident, i, len));
+        }
+        i += len;
+    }
+    result
+}
+
+/// Whether calling the function `from` ends up calling `target`, through the bodies of
+/// `functions`.
+fn calls_function<'a>(from: &'a wstr, target: &wstr, functions: &'a [UserFunction]) -> bool {
+    let mut pending = vec![from];
+    let mut seen: Vec<&wstr> = vec![];
+    while let Some(name) = pending.pop() {
+        if name == target {
+            return true;
+        }
+        if seen.contains(&name) {
+            continue;
+        }
+        seen.push(name);
+        if let Some(function) = functions.iter().find(|f| f.name == name) {
+            pending.extend(
+                identifiers(&function.body, &function.params)
+                    .into_iter()
+                    .map(|(ident, _, _)| ident),
+            );
+        }
+    }
+    false
+}
+
+/// Parse a function definition like `hyp(a, b) = sqrt(a^2 + b^2)`. Its body may call the
+/// `functions` defined before, as long as that does not end up calling the new function.
+pub fn parse_definition(
+    definition: &wstr,
+    functions: &[UserFunction],
+) -> Result<UserFunction, Error> {
+    let chars = definition.as_char_slice();
+    let error = |kind, position, len| Error {
+        kind,
+        position,
+        len,
+    };
+    let invalid = |position| error(ErrorKind::InvalidDefinition, position, 1);
+    let skip_space = |i: usize| i + chars[i..].iter().take_while(|&&c| is_space(c)).count();
+
+    let name_start = skip_space(0);
+    let name_len = ident_len(&chars[name_start..]);
+    if name_len == 0 {
+        return Err(invalid(name_start));
+    }
+    let name = wstr::from_char_slice(&chars[name_start..name_start + name_len]);
+    if is_reserved_name(name) {
+        return Err(error(ErrorKind::ReservedName, name_start, name_len));
+    }
+
+    let mut i = skip_space(name_start + name_len);
+    if chars.get(i) != Some(&'(') {
+        return Err(invalid(i));
+    }
+    i = skip_space(i + 1);
+    let mut params: Vec<WString> = vec![];
+    if chars.get(i) == Some(&')') {
+        i += 1;
+    } else {
+        loop {
+            let len = ident_len(&chars[i..]);
+            if len == 0 {
+                return Err(invalid(i));
+            }
+            let param = wstr::from_char_slice(&chars[i..i + len]);
+            if is_reserved_name(param) {
+                return Err(error(ErrorKind::ReservedName, i, len));
+            }
+            if params.iter().any(|p| p == param) {
+                return Err(error(ErrorKind::InvalidDefinition, i, len));
+            }
+            params.push(param.to_owned());
+            i = skip_space(i + len);
+            match chars.get(i) {
+                Some(',') => i = skip_space(i + 1),
+                Some(')') => {
+                    i += 1;
+                    break;
+                }
+                _ => return Err(invalid(i)),
+            }
+        }
+    }
+    i = skip_space(i);
+    if chars.get(i) != Some(&'=') {
+        return Err(invalid(i));
+    }
+    let body_start = i + 1;
+    let body = definition[body_start..].to_owned();
+
+    // Reject calls of the function itself, also through other functions, like `f` calling `g`
+    // calling `f`.
+    for (ident, position, len) in identifiers(&body, &params) {
+        if calls_function(ident, name, functions) {
+            return Err(error(ErrorKind::Recursion, body_start + position, len));
+        }
+    }
+
+    // Check the body, with the parameters bound to NaN so it cannot divide by zero.
+    let others: Vec<UserFunction> = functions
+        .iter()
+        .filter(|f| f.name != name)
+        .cloned()
+        .collect();
+    let mut variables = params.iter().map(|p| (p.clone(), f64::NAN)).collect();
+    te_interp_with(&body, &mut variables, &others).map_err(|err| Error {
+        position: body_start + err.position,
+        ..err
+    })?;
+
+    Ok(UserFunction {
+        name: name.to_owned(),
+        params,
+        body,
+    })
+}
+
+// Integer evaluation, for `math --integer`.
+//
+// This computes with 128-bit integers instead of doubles, so large integers stay exact. It adds
+// the `<<` and `>>` operators and `0o` and `0b` numbers, and only knows the builtin functions that
+// make sense for integers. User functions are evaluated with integers too.
+
+fn int_pow(a: i128, b: i128) -> Result<i128, ErrorKind> {
+    if b < 0 {
+        return Err(ErrorKind::NotAnInteger);
+    }
+    u32::try_from(b)
+        .ok()
+        .and_then(|b| a.checked_pow(b))
+        .ok_or(ErrorKind::IntegerOverflow)
+}
+
+const INT_BUILTINS: &[(&wstr, Function<i128>)] = &[
+    // must be in alphabetical order
+    (
+        L!("abs"),
+        Function::Fn1(|n: i128| n.checked_abs().ok_or(ErrorKind::IntegerOverflow)),
+    ),
+    (L!("bitand"), Function::Fn2(|a: i128, b: i128| Ok(a & b))),
+    (L!("bitor"), Function::Fn2(|a: i128, b: i128| Ok(a | b))),
+    (L!("bitxor"), Function::Fn2(|a: i128, b: i128| Ok(a ^ b))),
+    (
+        L!("max"),
+        Function::FnN(|n: &[i128]| Ok(n.iter().copied().max().unwrap())),
+    ),
+    (
+        L!("min"),
+        Function::FnN(|n: &[i128]| Ok(n.iter().copied().min().unwrap())),
+    ),
+    (L!("pow"), Function::Fn2(int_pow)),
+    (
+        L!("sum"),
+        Function::List(|n: &[i128]| {
+            n.iter()
+                .try_fold(0_i128, |acc, &x| acc.checked_add(x))
+                .ok_or(ErrorKind::IntegerOverflow)
+        }),
+    ),
+];
+
+assert_sorted_by_name!(INT_BUILTINS, 0);
+
+impl Number for i128 {
+    type Output = Result<i128, ErrorKind>;
+    const BUILTINS: &'static [(&'static wstr, Function<i128>)] = INT_BUILTINS;
+    const HAS_SHIFT: bool = true;
+    const INVALID: i128 = 0;
+
+    fn check(output: Result<i128, ErrorKind>) -> Result<i128, ErrorKind> {
+        output
+    }
+
+    /// Read a number like `42`, `1_000`, `0xFF`, `0o17` or `0b101`.
+    fn parse(s: &[char]) -> (usize, Result<i128, (ErrorKind, usize, usize)>) {
+        let radix = match s {
+            ['0', 'x' | 'X', ..] => 16,
+            ['0', 'o' | 'O', ..] => 8,
+            ['0', 'b' | 'B', ..] => 2,
+            _ => 10,
+        };
+        let prefix_len = if radix == 10 { 0 } else { 2 };
+        let end = prefix_len
+            + s[prefix_len..]
+                .iter()
+                .take_while(|&&c| c == '_' || c.is_digit(radix))
+                .count();
+        let is_fraction = s.get(end) == Some(&'.');
+        let is_exponent = matches!(s.get(end), Some('e' | 'E'))
+            && s.get(end + 1).is_some_and(|c| c.is_ascii_digit());
+        if radix == 10 && (is_fraction || is_exponent) {
+            let len = end
+                + s[end..]
+                    .iter()
+                    .take_while(|&&c| c.is_ascii_alphanumeric() || c == '.' || c == '_')
+                    .count();
+            return (len, Err((ErrorKind::NotAnInteger, 0, len)));
+        }
+        let digits: String = s[prefix_len..end].iter().filter(|&&c| c != '_').collect();
+        if digits.is_empty() {
+            return (end, Err((ErrorKind::Unknown, 0, end)));
+        }
+        match i128::from_str_radix(&digits, radix) {
+            Ok(n) => (end, Ok(n)),
+            Err(_) => (end, Err((ErrorKind::NumberTooLarge, 0, end))),
+        }
+    }
+
+    fn apply(op: Operator, a: i128, b: i128) -> Result<i128, ErrorKind> {
+        let shift_amount = || {
+            u32::try_from(b)
+                .ok()
+                .filter(|&b| b < i128::BITS)
+                .ok_or(ErrorKind::InvalidShift)
+        };
+        match op {
+            Operator::Add => a.checked_add(b).ok_or(ErrorKind::IntegerOverflow),
+            Operator::Sub => a.checked_sub(b).ok_or(ErrorKind::IntegerOverflow),
+            Operator::Mul => a.checked_mul(b).ok_or(ErrorKind::IntegerOverflow),
+            Operator::Div | Operator::Rem if b == 0 => Err(ErrorKind::DivByZero),
+            Operator::Div => a.checked_div(b).ok_or(ErrorKind::IntegerOverflow),
+            Operator::Rem => a.checked_rem(b).ok_or(ErrorKind::IntegerOverflow),
+            Operator::Pow => int_pow(a, b),
+            Operator::Shl => {
+                let b = shift_amount()?;
+                let result = a << b;
+                if result >> b != a {
+                    return Err(ErrorKind::IntegerOverflow);
+                }
+                Ok(result)
+            }
+            Operator::Shr => Ok(a >> shift_amount()?),
+        }
+    }
+
+    fn negate(self) -> Result<i128, ErrorKind> {
+        self.checked_neg().ok_or(ErrorKind::IntegerOverflow)
+    }
+}
+
+/// Evaluate `expression` with 128-bit integers, for `math --integer`. Assignments in the
+/// expression change `variables`. User `functions` compute with integers too.
+pub fn te_interp_integer(
+    expression: &wstr,
+    variables: &mut Vec<(WString, i128)>,
+    functions: &[UserFunction],
+) -> Result<i128, Error> {
+    let mut s = State::new(expression, variables, functions, 0);
+    let ret = s.eval();
+    s.error().map(|()| ret)
+}
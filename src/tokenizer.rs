@@ -85,8 +85,13 @@ pub struct PipeOrRedir {
     // The redirected fd, or -1 on overflow.
     // In the common case of a pipe, this is 1 (STDOUT_FILENO).
     // For example, in the case of "3>&1" this will be 3.
+    // For a named fd like {fd}>file, this is the default fd for the operator, and the real fd
+    // is only allocated when the redirection is applied.
     pub fd: i32,
 
+    // The variable to store an allocated fd in, for a redirection like {fd}>file.
+    pub fd_var: Option<WString>,
+
     // Whether we are a pipe (true) or redirection (false).
     pub is_pipe: bool,
 
@@ -406,6 +411,15 @@ fn next(&mut self) -> Option<Self::Item> {
                 }
                 Some(result)
             }
+            '{' if PipeOrRedir::try_from(buff).is_ok() => {
+                // A redirection with a named fd, like {fd}>file.
+                let redir = PipeOrRedir::try_from(buff).unwrap();
+                let mut result = Tok::new(redir.token_type());
+                result.offset = start_pos as u32;
+                result.length = redir.consumed as u32;
+                self.token_cursor += redir.consumed;
+                Some(result)
+            }
             '{' if self.brace_statement_parser.as_ref()
     				.is_some_and(|parser| parser.at_command_position) =>
 			{
@@ -978,12 +992,28 @@ impl TryFrom<&wstr> for PipeOrRedir {
     ///     cmd &> file      redirection with stderr merge
     ///     cmd ^ file       caret (stderr) redirection, perhaps disabled via feature flags
     ///     cmd ^^ file      caret (stderr) redirection, perhaps disabled via feature flags
+    ///     cmd {fd}> file   redirection to a newly allocated fd, stored in $fd
+    ///     cmd {fd}>&-      closing the fd stored in $fd
     /// ```
     fn try_from(buff: &wstr) -> Result<PipeOrRedir, ()> {
-        // Extract a range of leading fd.
-        let mut cursor = buff.chars().take_while(|c| c.is_ascii_digit()).count();
+        // Extract a range of leading fd, or a variable name in braces.
+        let (mut cursor, fd_var) = if buff.char_at(0) == '{' {
+            let name_len = buff[1..]
+                .chars()
+                .take_while(|&c| valid_var_name_char(c))
+                .count();
+            if name_len == 0 || buff.char_at(name_len + 1) != '}' {
+                return Err(());
+            }
+            (name_len + 2, Some(buff[1..name_len + 1].to_owned()))
+        } else {
+            (
+                buff.chars().take_while(|c| c.is_ascii_digit()).count(),
+                None,
+            )
+        };
         let fd_buff = &buff[..cursor];
-        let has_fd = !fd_buff.is_empty();
+        let has_fd = fd_var.is_none() && !fd_buff.is_empty();
 
         // Try consuming a given character.
         // Return true if consumed. On success, advances cursor.
@@ -1003,8 +1033,13 @@ fn try_from(buff: &wstr) -> Result<PipeOrRedir, ()> {
         };
 
         let c = buff.char_at(cursor);
+        if fd_var.is_some() && c != '>' && c != '<' {
+            // Named fds cannot be piped.
+            return Err(());
+        }
         let mut result = PipeOrRedir {
             fd: -1,
+            fd_var,
             is_pipe: false,
             mode: RedirectionMode::Overwrite,
             stderr_merge: false,
@@ -1114,6 +1149,11 @@ fn try_from(buff: &wstr) -> Result<PipeOrRedir, ()> {
             }
         }
 
+        if result.is_pipe && result.fd_var.is_some() {
+            // Like {fd}>|
+            return Err(());
+        }
+
         result.consumed = cursor;
         assert!(
             result.consumed > 0,
@@ -1361,6 +1401,14 @@ macro_rules! pipe_or_redir {
         assert!(pipe_or_redir!("&>>").stderr_merge);
         assert!(pipe_or_redir!("&>?").stderr_merge);
 
+        assert_eq!(pipe_or_redir!("{fd}>").fd_var.as_deref(), Some(L!("fd")));
+        assert_eq!(pipe_or_redir!("{fd}<&").fd_var.as_deref(), Some(L!("fd")));
+        assert!(pipe_or_redir!("2>").fd_var.is_none());
+        assert!(PipeOrRedir::try_from(L!("{fd}|")).is_err());
+        assert!(PipeOrRedir::try_from(L!("{fd}>|")).is_err());
+        assert!(PipeOrRedir::try_from(L!("{}>")).is_err());
+        assert!(PipeOrRedir::try_from(L!("{a,b}>")).is_err());
+
         macro_rules! get_redir_mode {
             ($s:literal) => {
                 pipe_or_redir!($s).mode
@@ -1,3 +1,4 @@
+use crate::env::{REDACTED, redact_secrets, secret_values};
 use crate::flog::log_extra_to_flog_file;
 use crate::parser::Parser;
 use crate::{common::escape, global_safety::RelaxedAtomicBool, prelude::*};
@@ -29,13 +30,35 @@ pub fn trace_argv<S: AsRef<wstr>>(parser: &Parser, command: &wstr, args: &[S]) {
     let mut trace_text = L!("-").repeat(parser.blocks_size() - 1);
     trace_text.push('>');
 
+    // Values of secret variables are redacted. The values given to `set --secret` are not secret
+    // yet when it is traced, so they are redacted too. The command is either given separately
+    // or is the first argument.
+    let secrets = secret_values(parser.vars());
+    let args: Vec<&wstr> = args.iter().map(|arg| arg.as_ref()).collect();
+    let first_option = usize::from(command.is_empty());
+    let is_set = if command.is_empty() {
+        args.first().is_some_and(|cmd| *cmd == "set")
+    } else {
+        command == "set"
+    };
+    let set_secret_name = args
+        .iter()
+        .skip(first_option)
+        .position(|arg| !arg.starts_with('-'))
+        .map(|pos| pos + first_option)
+        .filter(|&name| is_set && args[first_option..name].contains(&L!("--secret")));
+
     if !command.is_empty() {
         trace_text.push(' ');
         trace_text.push_utfstr(command);
     }
-    for arg in args {
+    for (i, arg) in args.iter().enumerate() {
         trace_text.push(' ');
-        trace_text.push_utfstr(&escape(arg.as_ref()));
+        if set_secret_name.is_some_and(|name| i > name) {
+            trace_text.push_utfstr(REDACTED);
+        } else {
+            trace_text.push_utfstr(&escape(&redact_secrets(arg, &secrets)));
+        }
     }
     trace_text.push('\n');
     log_extra_to_flog_file(&trace_text);
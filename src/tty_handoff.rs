@@ -15,7 +15,10 @@
     DecsetColorThemeReporting, DecsetFocusReporting, KittyKeyboardProgressiveEnhancementsDisable,
     KittyKeyboardProgressiveEnhancementsEnable, ModifyOtherKeysDisable, ModifyOtherKeysEnable,
 };
-use crate::terminal::{Output, Outputter};
+use crate::terminal::{
+    NotificationSupport, Output, Outputter, detect_notification_support, notification_support,
+    set_notification_support,
+};
 use crate::threads::assert_is_main_thread;
 use crate::wutil::{perror, wcstoi};
 use fish_widestring::ToWString;
@@ -182,8 +185,9 @@ fn get_protocols(self) -> TtyProtocolsSet {
         let mut on_chain = vec![];
         let mut off_chain = vec![];
 
-        // Enable focus reporting under tmux
-        if matches!(self, TtyQuirks::Tmux(_)) {
+        // Enable focus reporting under tmux, and to learn whether to send desktop notifications.
+        if matches!(self, TtyQuirks::Tmux(_)) || notification_support() != NotificationSupport::None
+        {
             on_chain.push(DecsetFocusReporting);
             off_chain.push(DecrstFocusReporting);
         }
@@ -247,6 +251,7 @@ pub fn initialize_tty_protocols(vars: &dyn Environment) {
     SCROLL_CONTENT_UP_SUPPORTED.get_or_init(|| false);
     TERMINAL_OS_NAME.get_or_init(|| None);
     let xtversion = XTVERSION.get_or_init(WString::new);
+    set_notification_support(detect_notification_support(vars, xtversion));
 
     use std::sync::atomic::Ordering::{Acquire, Release};
     // Standard lazy-init pattern from rust-atomics-and-locks.
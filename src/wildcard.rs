@@ -2,11 +2,12 @@
 
 use fish_common::WILDCARD_RESERVED_BASE;
 use fish_widestring::char_offset;
-use nix::unistd::AccessFlags;
+use nix::unistd::{AccessFlags, Group, User};
 use std::cell::LazyCell;
 use std::cmp::Ordering;
 use std::collections::HashSet;
 use std::os::unix::fs::MetadataExt;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::common::{
     UnescapeFlags, UnescapeStringStyle, WSL, is_windows_subsystem_for_linux, unescape_string,
@@ -15,9 +16,11 @@
 use crate::expand::ExpandFlags;
 use crate::future_feature_flags::FeatureFlag;
 use crate::future_feature_flags::feature_test;
+use crate::gitignore::{IgnoreFile, is_ignored, load_parent_ignore_files};
+use crate::path::append_path_component;
 use crate::prelude::*;
 use crate::wutil::dir_iter::DirEntryType;
-use crate::wutil::{dir_iter::DirEntry, lwstat, waccess};
+use crate::wutil::{dir_iter::DirEntry, lwstat, waccess, wstat};
 use fish_fallback::wcscasecmp;
 use fish_wcstringutil::{
     CaseSensitivity, string_fuzzy_match_string, string_suffixes_string_case_insensitive,
@@ -39,10 +42,13 @@
 pub const ANY_STRING: char = char_offset(WILDCARD_RESERVED_BASE, 1);
 /// Character representing any character string.
 pub const ANY_STRING_RECURSIVE: char = char_offset(WILDCARD_RESERVED_BASE, 2);
+/// Character replacing the unquoted '[' that starts the qualifiers of a wildcard, like in
+/// `**.log[file,mtime-1]`.
+pub const QUALIFIERS_BEGIN: char = char_offset(WILDCARD_RESERVED_BASE, 3);
 /// This is a special pseudo-char that is not used other than to mark the
 /// end of the special characters so we can sanity check the enum range.
 #[allow(dead_code)]
-pub const ANY_SENTINEL: char = char_offset(WILDCARD_RESERVED_BASE, 3);
+pub const ANY_SENTINEL: char = char_offset(WILDCARD_RESERVED_BASE, 4);
 
 #[derive(PartialEq)]
 pub enum WildcardResult {
@@ -56,6 +62,325 @@ pub enum WildcardResult {
     Overflow,
 }
 
+/// How a file's size or age compares to the number given in a qualifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum QualifierCmp {
+    Less,
+    Equal,
+    Greater,
+}
+
+/// A size or age range in a qualifier, like the `+10k` in `size+10k`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct QualifierRange {
+    cmp: QualifierCmp,
+    value: u64,
+    /// The size of the unit `value` is given in, in bytes or seconds.
+    unit: u64,
+}
+
+impl QualifierRange {
+    /// Parse `[+-]NUMBER[UNIT]`, where the unit is looked up in `units`.
+    fn parse(s: &wstr, units: &[(char, u64)], default_unit: u64) -> Option<Self> {
+        let (cmp, s) = match s.chars().next() {
+            Some('+') => (QualifierCmp::Greater, &s[1..]),
+            Some('-') => (QualifierCmp::Less, &s[1..]),
+            _ => (QualifierCmp::Equal, s),
+        };
+        let digits = s.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 {
+            return None;
+        }
+        let value = s[..digits].to_string().parse().ok()?;
+        let unit = match s[digits..].as_char_slice() {
+            [] => default_unit,
+            [c] => units.iter().find(|(u, _)| u == c)?.1,
+            _ => return None,
+        };
+        Some(Self { cmp, value, unit })
+    }
+
+    /// Whether `amount`, in bytes or seconds, is in the range. Equality is checked in whole
+    /// units, so `mtime1` matches anything modified between one and two days ago.
+    fn contains(&self, amount: u64) -> bool {
+        let limit = self.value.saturating_mul(self.unit);
+        match self.cmp {
+            QualifierCmp::Less => amount < limit,
+            QualifierCmp::Equal => amount / self.unit == self.value,
+            QualifierCmp::Greater => amount > limit,
+        }
+    }
+}
+
+/// A user or group in a qualifier, given by name or number.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum QualifierOwner {
+    Id(u32),
+    Name(WString),
+}
+
+/// The key that matches of a wildcard with a `sort:` qualifier are sorted by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QualifierSortKey {
+    Name,
+    Size,
+    Mtime,
+}
+
+/// Qualifiers select the matches of a wildcard by the type, size, age and owner of the file, and
+/// change their order. They are written in brackets after the wildcard, separated by commas:
+/// `**.log[file,mtime-1]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WildcardQualifiers {
+    /// Accept regular files, directories, symlinks and executables. If none are set, accept any.
+    file: bool,
+    dir: bool,
+    link: bool,
+    exec: bool,
+    size: Option<QualifierRange>,
+    mtime: Option<QualifierRange>,
+    user: Option<QualifierOwner>,
+    group: Option<QualifierOwner>,
+    /// The sort key, and whether to sort in descending order.
+    pub sort: Option<(QualifierSortKey, bool)>,
+    /// Skip files ignored by `.gitignore` and `.ignore` files.
+    pub gitignore: bool,
+    /// With `gitignore`, follow symlinks to directories, which is otherwise not done.
+    pub follow: bool,
+    /// How many levels of directories `**` may descend into.
+    pub depth: Option<usize>,
+    /// Patterns from $fish_glob_ignore: files and directories with matching names are skipped by
+    /// `**`. These use internal wildcard characters.
+    pub ignore_patterns: Vec<WString>,
+}
+
+const SIZE_UNITS: &[(char, u64)] = &[
+    ('k', 1 << 10),
+    ('M', 1 << 20),
+    ('G', 1 << 30),
+    ('T', 1 << 40),
+];
+
+const TIME_UNITS: &[(char, u64)] = &[
+    ('s', 1),
+    ('m', 60),
+    ('h', 60 * 60),
+    ('d', 24 * 60 * 60),
+    ('w', 7 * 24 * 60 * 60),
+];
+
+impl WildcardQualifiers {
+    /// Parse the text between the brackets, or return None if it isn't a list of qualifiers.
+    pub fn parse(s: &wstr) -> Option<Self> {
+        if s.is_empty() {
+            return None;
+        }
+        let mut result = Self::default();
+        for qualifier in s.split(',') {
+            let parse_owner = |name: &wstr| {
+                if name.is_empty()
+                    || !name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "_-.".contains(c))
+                {
+                    return None;
+                }
+                Some(match name.to_string().parse() {
+                    Ok(id) => QualifierOwner::Id(id),
+                    Err(_) => QualifierOwner::Name(name.to_owned()),
+                })
+            };
+            let parse_sort_key = |key: &wstr| match key.to_string().as_str() {
+                "name" => Some(QualifierSortKey::Name),
+                "size" => Some(QualifierSortKey::Size),
+                "mtime" => Some(QualifierSortKey::Mtime),
+                _ => None,
+            };
+            if qualifier == "file" {
+                result.file = true;
+            } else if qualifier == "dir" {
+                result.dir = true;
+            } else if qualifier == "link" {
+                result.link = true;
+            } else if qualifier == "exec" {
+                result.exec = true;
+            } else if qualifier == "gitignore" {
+                result.gitignore = true;
+            } else if qualifier == "follow" {
+                result.follow = true;
+            } else if let Some(depth) = qualifier.strip_prefix("depth:") {
+                if depth.is_empty() || !depth.chars().all(|c| c.is_ascii_digit()) {
+                    return None;
+                }
+                result.depth = Some(depth.to_string().parse().ok()?);
+            } else if let Some(range) = qualifier.strip_prefix("size") {
+                result.size = Some(QualifierRange::parse(range, SIZE_UNITS, 1)?);
+            } else if let Some(range) = qualifier.strip_prefix("mtime") {
+                result.mtime = Some(QualifierRange::parse(range, TIME_UNITS, 24 * 60 * 60)?);
+            } else if let Some(name) = qualifier.strip_prefix("user:") {
+                result.user = Some(parse_owner(name)?);
+            } else if let Some(name) = qualifier.strip_prefix("group:") {
+                result.group = Some(parse_owner(name)?);
+            } else if let Some(key) = qualifier.strip_prefix("sort:") {
+                result.sort = Some((parse_sort_key(key)?, false));
+            } else if let Some(key) = qualifier.strip_prefix("rsort:") {
+                result.sort = Some((parse_sort_key(key)?, true));
+            } else {
+                return None;
+            }
+        }
+        Some(result)
+    }
+
+    /// Split a wildcard into the wildcard itself and its qualifiers, if it has any.
+    pub fn split(wc: &wstr) -> (&wstr, Option<Self>) {
+        let Some(begin) = wc.find_char(QUALIFIERS_BEGIN) else {
+            return (wc, None);
+        };
+        let rest = &wc[begin + 1..];
+        let qualifiers = rest
+            .ends_with(']')
+            .then(|| WildcardQualifiers::parse(&rest[..rest.len() - 1]))
+            .flatten();
+        (&wc[..begin], qualifiers)
+    }
+
+    /// Look up the users and groups given by name. On failure, return the qualifier that names an
+    /// unknown one.
+    pub fn resolve_owners(&mut self) -> Result<(), WString> {
+        if let Some(QualifierOwner::Name(name)) = &self.user {
+            match User::from_name(&name.to_string()) {
+                Ok(Some(user)) => self.user = Some(QualifierOwner::Id(user.uid.as_raw())),
+                _ => return Err(L!("user:").to_owned() + name.as_utfstr()),
+            }
+        }
+        if let Some(QualifierOwner::Name(name)) = &self.group {
+            match Group::from_name(&name.to_string()) {
+                Ok(Some(group)) => self.group = Some(QualifierOwner::Id(group.gid.as_raw())),
+                _ => return Err(L!("group:").to_owned() + name.as_utfstr()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether any qualifier needs more than the type of a file, which only stat() can tell.
+    fn needs_stat(&self) -> bool {
+        self.exec
+            || self.size.is_some()
+            || self.mtime.is_some()
+            || self.user.is_some()
+            || self.group.is_some()
+    }
+
+    /// Whether any qualifier selects matches, rather than changing how they are found or sorted.
+    fn has_filters(&self) -> bool {
+        self.file || self.dir || self.link || self.needs_stat()
+    }
+
+    /// Whether `entry`, whose path is `path`, passes the qualifiers. If only the type matters and
+    /// readdir() already told us what it is, this avoids a stat() call.
+    fn matches_entry(&self, path: &wstr, entry: &DirEntry) -> bool {
+        if !self.has_filters() {
+            return true;
+        }
+        if !self.needs_stat() {
+            match entry.is_possible_link() {
+                // A symlink; only its target can tell whether it is a file or a directory.
+                Some(true) if self.link => return true,
+                Some(false) => {
+                    let typ = entry.check_type();
+                    return (self.file && typ == Some(DirEntryType::Reg))
+                        || (self.dir && typ == Some(DirEntryType::Dir));
+                }
+                _ => (),
+            }
+        }
+        self.matches(path)
+    }
+
+    /// Whether the file at `path` passes the qualifiers. Symlinks are followed, except to check
+    /// if the file is one.
+    pub fn matches(&self, path: &wstr) -> bool {
+        if !self.has_filters() {
+            return true;
+        }
+        let Ok(lmd) = lwstat(path) else {
+            return false;
+        };
+        let is_link = lmd.file_type().is_symlink();
+        let md = if is_link {
+            wstat(path).unwrap_or(lmd)
+        } else {
+            lmd
+        };
+
+        if self.file || self.dir || self.link || self.exec {
+            let is_exec = md.is_file() && md.mode() & 0o111 != 0;
+            if !((self.file && md.is_file())
+                || (self.dir && md.is_dir())
+                || (self.link && is_link)
+                || (self.exec && is_exec))
+            {
+                return false;
+            }
+        }
+        if let Some(size) = &self.size {
+            if !size.contains(md.size()) {
+                return false;
+            }
+        }
+        if let Some(mtime) = &self.mtime {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            let age = u64::try_from(md.mtime()).map_or(0, |mtime| now.saturating_sub(mtime));
+            if !mtime.contains(age) {
+                return false;
+            }
+        }
+        if let Some(QualifierOwner::Id(uid)) = &self.user {
+            if md.uid() != *uid {
+                return false;
+            }
+        }
+        if let Some(QualifierOwner::Id(gid)) = &self.group {
+            if md.gid() != *gid {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Sort the matches of a wildcard by the key of its `sort:` qualifier. Relative paths are resolved
+/// against `working_directory`. The sort is stable, so matches with the same key stay sorted by
+/// name.
+pub fn wildcard_sort_by_qualifier(
+    matches: &mut [Completion],
+    working_directory: &wstr,
+    key: QualifierSortKey,
+    descending: bool,
+) {
+    let metadata_key = |c: &Completion| {
+        let mut path = working_directory.to_owned();
+        append_path_component(&mut path, &c.completion);
+        let md = wstat(&path).or_else(|_| lwstat(&path)).ok();
+        match key {
+            QualifierSortKey::Name => 0,
+            QualifierSortKey::Size => md.map_or(0, |md| i128::from(md.size())),
+            QualifierSortKey::Mtime => md.map_or(0, |md| {
+                i128::from(md.mtime()) * 1_000_000_000 + i128::from(md.mtime_nsec())
+            }),
+        }
+    };
+    match (key, descending) {
+        (QualifierSortKey::Name, false) => (),
+        (QualifierSortKey::Name, true) => matches.reverse(),
+        (_, false) => matches.sort_by_cached_key(metadata_key),
+        (_, true) => matches.sort_by_cached_key(|c| std::cmp::Reverse(metadata_key(c))),
+    }
+}
+
 // This does something horrible refactored from an even more horrible function.
 fn resolve_description(
     full_completion: &wstr,
@@ -451,10 +776,7 @@ fn wildcard_test_flags_then_complete(
 
 mod expander {
 
-    use crate::{
-        path::append_path_component,
-        wutil::{DevInode, dir_iter::DirIter, normalize_path},
-    };
+    use crate::wutil::{DevInode, dir_iter::DirIter, normalize_path};
 
     use super::*;
 
@@ -465,6 +787,8 @@ mod expander {
     pub(super) struct ParentInfo {
         // Whether some parent expansion is fuzzy, and therefore completions always prepend their prefix.
         has_fuzzy_ancestor: bool,
+        // How many directories deep a recursive wildcard has descended.
+        recursive_depth: usize,
     }
 
     // A stateful object for expanding wildcards.
@@ -479,6 +803,11 @@ pub(super) struct WildCardExpander<'e> {
         visited_files: HashSet<DevInode>,
         /// Flags controlling expansion.
         flags: ExpandFlags,
+        /// The qualifiers that matches must pass, if any.
+        qualifiers: Option<&'e WildcardQualifiers>,
+        /// With the `gitignore` qualifier, the ignore files of the directories from the root of
+        /// the repository to the one being expanded.
+        ignore_files: Vec<IgnoreFile>,
         /// Resolved items get inserted into here. This is transient of course.
         resolved_completions: &'e mut CompletionReceiver,
         /// Whether we have been interrupted.
@@ -493,6 +822,7 @@ impl<'e> WildCardExpander<'e> {
         pub fn new(
             working_directory: &'e wstr,
             flags: ExpandFlags,
+            qualifiers: Option<&'e WildcardQualifiers>,
             cancel_checker: &'e mut dyn FnMut() -> bool,
             resolved_completions: &'e mut CompletionReceiver,
         ) -> Self {
@@ -505,6 +835,8 @@ pub fn new(
                     .collect(),
                 visited_files: HashSet::new(),
                 flags,
+                qualifiers,
+                ignore_files: vec![],
                 resolved_completions,
                 did_add: false,
                 did_interrupt: false,
@@ -531,6 +863,42 @@ pub fn expand(
             wc: &wstr,
             effective_prefix: &wstr,
             info: ParentInfo,
+        ) {
+            // Read the ignore files of base_dir for the entries below it, unless this is a nested
+            // call for the same directory.
+            let mut pushed_ignore_file = false;
+            if self.qualifiers.is_some_and(|q| q.gitignore) {
+                let dir = self.absolute_dir(base_dir);
+                if self
+                    .ignore_files
+                    .last()
+                    .is_none_or(|f| f.dir() != dir.as_utfstr())
+                {
+                    self.ignore_files.push(IgnoreFile::load(&dir));
+                    pushed_ignore_file = true;
+                }
+            }
+            self.expand_in_dir(base_dir, wc, effective_prefix, info);
+            if pushed_ignore_file {
+                self.ignore_files.pop();
+            }
+        }
+
+        /// Read the ignore files of the directories above base_dir, up to the root of its git
+        /// repository.
+        pub fn load_parent_ignore_files(&mut self, base_dir: &wstr) {
+            if self.qualifiers.is_some_and(|q| q.gitignore) {
+                self.ignore_files = load_parent_ignore_files(&self.absolute_dir(base_dir));
+            }
+        }
+
+        /// The implementation of expand(), after reading ignore files.
+        fn expand_in_dir(
+            &mut self,
+            base_dir: &wstr,
+            wc: &wstr,
+            effective_prefix: &wstr,
+            info: ParentInfo,
         ) {
             if self.interrupted_or_overflowed() {
                 return;
@@ -686,7 +1054,11 @@ fn expand_trailing_slash(&mut self, base_dir: &wstr, prefix: &wstr, info: Parent
 
             if !self.flags.contains(ExpandFlags::FOR_COMPLETIONS) {
                 // Trailing slash and not accepting incomplete, e.g. `echo /xyz/`. Insert this file after checking it exists.
-                if waccess(base_dir, AccessFlags::F_OK).is_ok() {
+                if waccess(base_dir, AccessFlags::F_OK).is_ok()
+                    && self
+                        .qualifiers
+                        .is_none_or(|q| q.matches(&self.absolute_dir(base_dir)))
+                {
                     self.add_expansion_result(base_dir.to_owned());
                 }
                 return;
@@ -736,7 +1108,19 @@ fn expand_intermediate_segment(
             prefix: &wstr,
             info: ParentInfo,
         ) {
-            let is_final = wc_remainder.is_empty() && !wc_segment.contains(ANY_STRING_RECURSIVE);
+            let is_recursive = wc_segment.contains(ANY_STRING_RECURSIVE);
+            let is_final = wc_remainder.is_empty() && !is_recursive;
+            let mut info = info;
+            if is_recursive {
+                info.recursive_depth += 1;
+                if self
+                    .qualifiers
+                    .and_then(|q| q.depth)
+                    .is_some_and(|max_depth| info.recursive_depth > max_depth)
+                {
+                    return;
+                }
+            }
             while !self.interrupted_or_overflowed() {
                 let Some(Ok(entry)) = base_dir_iter.next() else {
                     break;
@@ -749,6 +1133,11 @@ fn expand_intermediate_segment(
                 if !entry.is_dir() {
                     continue;
                 }
+                if self.is_ignored(base_dir, entry, is_recursive)
+                    || self.is_unfollowed_link(base_dir, entry)
+                {
+                    continue;
+                }
 
                 // Fast path: If this entry can't be a link (we know via d_type),
                 // we don't need to protect against symlink loops.
@@ -872,7 +1261,11 @@ fn expand_last_segment(
                     continue;
                 }
 
+                let is_recursive = wc.contains(ANY_STRING_RECURSIVE);
                 if self.flags.contains(ExpandFlags::FOR_COMPLETIONS) {
+                    if self.is_ignored(base_dir, entry, is_recursive) {
+                        continue;
+                    }
                     self.try_add_completion_result(
                         &(base_dir.to_owned() + entry.name.as_utfstr()),
                         &entry.name,
@@ -887,13 +1280,63 @@ fn expand_last_segment(
                         &entry.name,
                         wc,
                         true, /* skip files with leading dots */
-                    ) {
+                    ) && !self.is_ignored(base_dir, entry, is_recursive)
+                        && self.qualifiers.is_none_or(|q| {
+                            q.matches_entry(
+                                &(self.absolute_dir(base_dir) + entry.name.as_utfstr()),
+                                entry,
+                            )
+                        })
+                    {
                         self.add_expansion_result(base_dir.to_owned() + entry.name.as_utfstr());
                     }
                 }
             }
         }
 
+        /// The absolute path of base_dir, with a trailing slash.
+        fn absolute_dir(&self, base_dir: &wstr) -> WString {
+            let mut dir = self.working_directory.to_owned();
+            append_path_component(&mut dir, base_dir);
+            if !dir.ends_with('/') {
+                dir.push('/');
+            }
+            dir
+        }
+
+        /// Whether entry, found in base_dir, is skipped because it matches $fish_glob_ignore and
+        /// was found by a recursive wildcard, or because an ignore file ignores it.
+        fn is_ignored(&self, base_dir: &wstr, entry: &DirEntry, is_recursive: bool) -> bool {
+            let Some(qualifiers) = self.qualifiers else {
+                return false;
+            };
+            if is_recursive
+                && qualifiers
+                    .ignore_patterns
+                    .iter()
+                    .any(|pattern| wildcard_match(&entry.name, pattern, false))
+            {
+                return true;
+            }
+            if !qualifiers.gitignore {
+                return false;
+            }
+            let path = self.absolute_dir(base_dir) + entry.name.as_utfstr();
+            is_ignored(&self.ignore_files, &path, entry.is_dir())
+        }
+
+        /// Whether entry, found in base_dir, is a symlink that should not be followed. With the
+        /// `gitignore` qualifier, they are only followed if `follow` is given too.
+        fn is_unfollowed_link(&self, base_dir: &wstr, entry: &DirEntry) -> bool {
+            if !self.qualifiers.is_some_and(|q| q.gitignore && !q.follow)
+                || entry.is_possible_link() == Some(false)
+            {
+                return false;
+            }
+            let path = self.absolute_dir(base_dir) + entry.name.as_utfstr();
+            lwstat(&path).is_ok_and(|md| md.file_type().is_symlink())
+        }
+
         /// Indicate whether we should cancel wildcard expansion. This latches 'interrupt'.
         fn interrupted_or_overflowed(&mut self) -> bool {
             self.did_interrupt |= (self.cancel_checker)();
@@ -1063,6 +1506,7 @@ fn open_dir(&self, base_dir: &wstr, dotdot: bool) -> std::io::Result<DirIter> {
 /// \param working_directory The working directory
 /// \param flags flags for the search. Can be any combination of for_completions and
 /// executables_only
+/// \param qualifiers The qualifiers each match must pass. Completions only use its ignore patterns.
 /// \param cancel_checker A function to call to check for cancellation
 /// \param output The completion receiver to receive expanded wildcards
 ///
@@ -1070,6 +1514,7 @@ pub fn wildcard_expand_string<'closure>(
     wc: &wstr,
     working_directory: &wstr,
     flags: ExpandFlags,
+    qualifiers: Option<&WildcardQualifiers>,
     mut cancel_checker: impl FnMut() -> bool + 'closure,
     output: &mut CompletionReceiver,
 ) -> WildcardResult {
@@ -1114,7 +1559,13 @@ pub fn wildcard_expand_string<'closure>(
         (working_directory, L!(""), wc)
     };
 
-    let mut expander = WildCardExpander::new(prefix, flags, &mut cancel_checker, output);
+    assert!(
+        qualifiers.is_none_or(|q| !q.has_filters() && !q.gitignore && q.sort.is_none())
+            || !flags.contains(ExpandFlags::FOR_COMPLETIONS)
+    );
+    let mut expander =
+        WildCardExpander::new(prefix, flags, qualifiers, &mut cancel_checker, output);
+    expander.load_parent_ignore_files(base_dir);
     expander.expand(base_dir, effective_wc, base_dir, ParentInfo::default());
     expander.status_code()
 }
@@ -1254,4 +1705,48 @@ fn test_wildcards() {
             assert!(!wildcard_has(L!("\\?")));
         });
     }
+
+    #[test]
+    fn test_wildcard_qualifiers() {
+        let q = WildcardQualifiers::parse(L!("file,size+10k,mtime-2h,rsort:size")).unwrap();
+        assert!(q.file && !q.dir);
+        assert_eq!(
+            q.size,
+            Some(QualifierRange {
+                cmp: QualifierCmp::Greater,
+                value: 10,
+                unit: 1024
+            })
+        );
+        assert!(q.mtime.unwrap().contains(3600));
+        assert!(!q.mtime.unwrap().contains(2 * 3600));
+        assert_eq!(q.sort, Some((QualifierSortKey::Size, true)));
+        assert_eq!(
+            WildcardQualifiers::parse(L!("user:0")).unwrap().user,
+            Some(QualifierOwner::Id(0))
+        );
+
+        for invalid in [
+            "", "1", "file,", "size", "size+1x", "mtime--1", "user:", "sort:age",
+        ] {
+            assert_eq!(WildcardQualifiers::parse(&WString::from_str(invalid)), None);
+        }
+
+        let wc = unescape_string(
+            L!("*.txt[dir]"),
+            UnescapeStringStyle::Script(UnescapeFlags::SPECIAL),
+        )
+        .unwrap();
+        let (wc, q) = WildcardQualifiers::split(&wc);
+        assert_eq!(wc.as_char_slice(), &[ANY_STRING, '.', 't', 'x', 't']);
+        assert!(q.unwrap().dir);
+        for literal in ["*.txt'[dir]'", "file[dir]", "*$var[dir]", "*[dir]x"] {
+            let wc = unescape_string(
+                &WString::from_str(literal),
+                UnescapeStringStyle::Script(UnescapeFlags::SPECIAL),
+            )
+            .unwrap();
+            assert!(!wc.contains(QUALIFIERS_BEGIN), "{literal}");
+        }
+    }
 }
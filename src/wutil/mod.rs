@@ -4,6 +4,7 @@
 mod hex_float;
 #[macro_use]
 pub mod printf;
+pub mod timefmt;
 pub mod wcstod;
 pub mod wcstoi;
 
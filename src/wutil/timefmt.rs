@@ -0,0 +1,189 @@
+//! Formatting and parsing of times through the C library, so `TZ` and `LC_TIME` are respected.
+
+use crate::common::{bytes2wcstring, wcs2zstring};
+use fish_widestring::{WString, wstr};
+use std::mem::MaybeUninit;
+
+/// Convert `time`, in seconds since the epoch, to broken-down time in the local time zone or in
+/// UTC.
+fn to_tm(time: i64, utc: bool) -> Option<libc::tm> {
+    // This warns for musl, but the warning is useless to us - there is nothing we can or should do.
+    #[allow(deprecated)]
+    let time = libc::time_t::try_from(time).ok()?;
+    let mut tm = MaybeUninit::uninit();
+    let result = if utc {
+        unsafe { libc::gmtime_r(&time, tm.as_mut_ptr()) }
+    } else {
+        unsafe { libc::localtime_r(&time, tm.as_mut_ptr()) }
+    };
+    if result.is_null() {
+        return None;
+    }
+    Some(unsafe { tm.assume_init() })
+}
+
+/// Wide character version of strftime(), for `time` in seconds since the epoch. Return None if
+/// the time can't be represented.
+pub fn wstrftime(format: &wstr, time: i64, utc: bool) -> Option<WString> {
+    let tm = to_tm(time, utc)?;
+    // strftime returns 0 both if the buffer is too small and if the result is empty, so add a
+    // character to tell them apart.
+    let mut format = format.to_owned();
+    format.push(' ');
+    let format = wcs2zstring(&format);
+    let mut size = 256;
+    loop {
+        let mut buf = vec![0_u8; size];
+        let len = unsafe { libc::strftime(buf.as_mut_ptr().cast(), size, format.as_ptr(), &tm) };
+        if len != 0 {
+            // Remove the extra character.
+            return Some(bytes2wcstring(&buf[..len - 1]));
+        }
+        if size >= 64 * 1024 {
+            return None;
+        }
+        size *= 4;
+    }
+}
+
+/// Convert broken-down time to seconds since the epoch. If `offset` is given, `tm` is that many
+/// seconds east of UTC, otherwise it is in UTC if `utc` is set or else in local time.
+fn tm_to_epoch(mut tm: libc::tm, offset: Option<i64>, utc: bool) -> i64 {
+    let time = if offset.is_some() || utc {
+        unsafe { libc::timegm(&mut tm) }
+    } else {
+        // Let mktime figure out whether daylight saving time is in effect.
+        tm.tm_isdst = -1;
+        unsafe { libc::mktime(&mut tm) }
+    };
+    #[allow(clippy::unnecessary_cast)]
+    let time = time as i64;
+    time - offset.unwrap_or(0)
+}
+
+/// A broken-down time for the first day of 1900, which is what fields missing from a parsed time
+/// default to.
+fn empty_tm() -> libc::tm {
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    tm.tm_mday = 1;
+    tm
+}
+
+/// Wide character version of strptime(), returning seconds since the epoch. The whole string must
+/// match `format`. Times are in local time unless `utc` is set or the format contains `%z`.
+pub fn wstrptime(s: &wstr, format: &wstr, utc: bool) -> Option<i64> {
+    let narrow = wcs2zstring(s);
+    let narrow_format = wcs2zstring(format);
+    let mut tm = empty_tm();
+    let end = unsafe { libc::strptime(narrow.as_ptr(), narrow_format.as_ptr(), &mut tm) };
+    if end.is_null() || unsafe { *end } != 0 {
+        return None;
+    }
+    // strptime stores the offset of %z in tm_gmtoff.
+    let has_offset = format.as_char_slice().windows(2).any(|w| w == ['%', 'z']);
+    let offset = has_offset.then_some(i64::from(tm.tm_gmtoff));
+    Some(tm_to_epoch(tm, offset, utc))
+}
+
+/// A position in a string being parsed as an ISO-8601 time.
+struct Cursor<'a> {
+    chars: &'a [char],
+    pos: usize,
+}
+
+impl Cursor<'_> {
+    /// Consume `c` if it is next.
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.chars.get(self.pos) == Some(&c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    /// Consume exactly `width` digits.
+    fn number(&mut self, width: usize) -> Option<i32> {
+        let digits = self.chars.get(self.pos..self.pos + width)?;
+        let mut result = 0;
+        for c in digits {
+            result = result * 10 + i32::try_from(c.to_digit(10)?).unwrap();
+        }
+        self.pos += width;
+        Some(result)
+    }
+
+    /// Consume any number of digits, returning how many there were.
+    fn digits(&mut self) -> usize {
+        let start = self.pos;
+        while self.chars.get(self.pos).is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+}
+
+/// Parse a time in ISO-8601 format, like `2024-05-01`, `2024-05-01T12:30`, `2024-05-01 12:30:15.5`
+/// or `2024-05-01T12:30:15+02:00`, returning seconds since the epoch. Fractional seconds are
+/// ignored. Times without an offset are in local time unless `utc` is set.
+pub fn parse_iso8601(s: &wstr, utc: bool) -> Option<i64> {
+    let mut cursor = Cursor {
+        chars: s.as_char_slice(),
+        pos: 0,
+    };
+    let mut tm = empty_tm();
+    tm.tm_year = cursor.number(4)? - 1900;
+    if !cursor.eat('-') {
+        return None;
+    }
+    tm.tm_mon = cursor.number(2)? - 1;
+    if !cursor.eat('-') {
+        return None;
+    }
+    tm.tm_mday = cursor.number(2)?;
+
+    if cursor.eat('T') || cursor.eat(' ') {
+        tm.tm_hour = cursor.number(2)?;
+        if !cursor.eat(':') {
+            return None;
+        }
+        tm.tm_min = cursor.number(2)?;
+        if cursor.eat(':') {
+            tm.tm_sec = cursor.number(2)?;
+            if (cursor.eat('.') || cursor.eat(',')) && cursor.digits() == 0 {
+                return None;
+            }
+        }
+    }
+
+    let mut offset = None;
+    if cursor.eat('Z') {
+        offset = Some(0);
+    } else if cursor.eat('+') || cursor.eat('-') {
+        let sign = if cursor.chars[cursor.pos - 1] == '-' {
+            -1
+        } else {
+            1
+        };
+        let hours = cursor.number(2)?;
+        cursor.eat(':');
+        let minutes = cursor.number(2)?;
+        if hours > 23 || minutes > 59 {
+            return None;
+        }
+        offset = Some(sign * i64::from(hours * 3600 + minutes * 60));
+    }
+    if cursor.pos != cursor.chars.len() {
+        return None;
+    }
+
+    // Allow a leap second, like strptime does.
+    if !(0..12).contains(&tm.tm_mon)
+        || !(1..=31).contains(&tm.tm_mday)
+        || tm.tm_hour > 23
+        || tm.tm_min > 59
+        || tm.tm_sec > 60
+    {
+        return None;
+    }
+    Some(tm_to_epoch(tm, offset, utc))
+}
@@ -119,6 +119,8 @@ time { :; }
 # CHECKERR: Executed in {{.*}}
 # CHECKERR:  usr time {{.*}}
 # CHECKERR:  sys time {{.*}}
+# CHECKERR: page faults {{.*}}
+# CHECKERR: switches {{.*}}
 
 true & { echo background; }
 # CHECK: background
@@ -217,3 +219,47 @@ $fish -c 'begin; }'
 # CHECKERR: fish: Unexpected '}' for unopened brace
 # CHECKERR: begin; }
 # CHECKERR:        ^
+
+# Ranges
+echo {1..5}
+# CHECK: 1 2 3 4 5
+echo {5..1}
+# CHECK: 5 4 3 2 1
+echo {-2..2}
+# CHECK: -2 -1 0 1 2
+echo {01..10..3}
+# CHECK: 01 04 07 10
+echo {-05..5..5}
+# CHECK: -05 000 005
+echo {10..1..-4}
+# CHECK: 10 6 2
+echo {a..e} {E..A..2}
+# CHECK: a b c d e E C A
+echo x{1..3}y
+# CHECK: x1y x2y x3y
+echo {a,{1..2}} {1..2}{a..b}
+# CHECK: a 1 2 1a 1b 2a 2b
+echo {1..} {..3} {1..2..3..4} {1..b} {ab..c}
+# CHECK: {1..} {..3} {1..2..3..4} {1..b} {ab..c}
+echo '{1..3}' \{1..3\}
+# CHECK: {1..3} {1..3}
+
+$fish -c 'echo {1..1000000}; echo $status'
+# CHECKERR: fish: Brace range '1..1000000' makes the expansion produce 1000000 items, more than the limit of 100000
+# CHECKERR: echo {1..1000000}; echo $status
+# CHECKERR: {{.*}}
+# CHECK: 121
+
+# The limit applies to the whole expansion, not each range.
+$fish -c 'echo {1..1000}{1..1000}; echo $status'
+# CHECKERR: fish: Brace range '1..1000' makes the expansion produce 1000000 items, more than the limit of 100000
+# CHECKERR: echo {1..1000}{1..1000}; echo $status
+# CHECKERR: {{.*}}
+# CHECK: 121
+$fish -c 'echo {a,b}{1..60000}; echo $status'
+# CHECKERR: fish: Brace range '1..60000' makes the expansion produce 120000 items, more than the limit of 100000
+# CHECKERR: echo {a,b}{1..60000}; echo $status
+# CHECKERR: {{.*}}
+# CHECK: 121
+count {1..300}{1..300}
+# CHECK: 90000
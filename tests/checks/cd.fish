@@ -309,3 +309,39 @@ else
     chmod -R +rx $tmp # we must be able to list the directory to delete its children
     rm -rf $tmp
 end
+
+# cd --jump picks from the directories visited in interactive sessions.
+begin
+    set -l oldpwd (pwd)
+    set -l tmp (mktemp -d)
+    mkdir -p $tmp/projects/fish-shell $tmp/projects/other $tmp/music
+    $fish -i -c "cd $tmp/projects/fish-shell; cd $tmp/music; cd $tmp/projects/fish-shell; cd $tmp/projects/other"
+
+    cd $tmp
+    cd --jump fish
+    string replace -- $tmp '' $PWD
+    # CHECK: /projects/fish-shell
+
+    # The current directory is left out, the rest is ordered by rank.
+    cd --jump --list | string replace -- $tmp ''
+    # CHECK: /music
+    # CHECK: /projects/other
+
+    cd --jump projects
+    # CHECKERR: cd: No directory matching 'projects' was found in the jump database
+    echo $status
+    # CHECK: 1
+
+    cd --jump PROJ other
+    # CHECKERR: cd: No directory matching 'PROJ other' was found in the jump database
+
+    cd --jump proj other
+    string replace -- $tmp '' $PWD
+    # CHECK: /projects/other
+
+    cd --list
+    # CHECKERR: cd: invalid option combination, --list requires --jump
+
+    cd $oldpwd
+    rm -rf $tmp
+end
@@ -17,6 +17,14 @@ $fish -C 'function fish_command_not_found; echo command-not-found $argv; end' -c
 #CHECKERR: nonexistent-command-abcd foo bar baz
 #CHECKERR: ^~~~~~~~~~~~~~~~~~~~~~~^
 
+# Interactive shells suggest similar names.
+$fish -C 'function frobnicate; end' -ic "frobnicat 1 2"
+#CHECKERR: fish: Unknown command: frobnicat
+#CHECKERR: Did you mean: frobnicate?
+#CHECKERR: fish:
+#CHECKERR: frobnicat 1 2
+#CHECKERR: ^~~~~~~~^
+
 $fish -C 'functions --erase fish_command_not_found' -c 'nonexistent-command apple friday'
 #CHECKERR: fish: Unknown command: nonexistent-command
 #CHECKERR: nonexistent-command apple friday
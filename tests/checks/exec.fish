@@ -17,6 +17,58 @@ echo "neg failed: $status"
 $fish --no-config -c 'foo=bar exec env' | grep '^foo=bar$'
 # CHECK: foo=bar
 
+# A redirection-only exec keeps the fd open for later commands.
+set -l tmpdir (mktemp -d)
+exec 3>$tmpdir/log
+echo "exec status: $status"
+#CHECK: exec status: 0
+echo first >&3
+begin
+    echo second
+end >&3
+sh -c 'echo from child >&3'
+exec 3>&-
+cat $tmpdir/log
+#CHECK: first
+#CHECK: second
+#CHECK: from child
+
+# {name}> allocates an fd above 10 and stores it in the variable.
+exec {logfd}>$tmpdir/named
+test $logfd -ge 10
+and echo allocated
+#CHECK: allocated
+echo hello >&$logfd
+sh -c "echo from child >&$logfd"
+exec {logfd}>&-
+cat $tmpdir/named
+#CHECK: hello
+#CHECK: from child
+
+# Other commands close their redirections when they finish, so they can't store fds.
+$fish --no-config -c "echo hi {fd}>$tmpdir/other; set -q fd; or echo unset"
+#CHECKERR: fish: A file descriptor can only be stored in 'fd' by 'exec' without a command
+#CHECKERR: echo hi {fd}>{{.*}}/other; set -q fd; or echo unset
+#CHECKERR:         ^{{~+}}^
+#CHECK: unset
+$fish --no-config -c "begin; end {fd}>$tmpdir/other"
+#CHECKERR: fish: A file descriptor can only be stored in 'fd' by 'exec' without a command
+#CHECKERR: begin; end {fd}>{{.*}}/other
+#CHECKERR:            ^{{~+}}^
+
+exec 4<$tmpdir/nonexistent
+#CHECKERR: warning: An error occurred while redirecting file '{{.*}}/nonexistent'
+#CHECKERR: warning: Path '{{.*}}/nonexistent' does not exist
+echo "failed: $status"
+#CHECK: failed: 1
+
+$fish --no-config -c 'exec {nofd}>&-'
+#CHECKERR: fish: Variable 'nofd' does not hold a file descriptor
+#CHECKERR: exec {nofd}>&-
+#CHECKERR:      ^~~~~~~~^
+
+command rm -r $tmpdir
+
 # This needs to be last, because it actually runs exec.
 exec cat </dev/null
 echo "not reached"
@@ -5,11 +5,9 @@ fg (math 2 ^ 31 - 1)
 
 fg (math 2 ^ 31)
 # CHECKERR: fg: '2147483648' is not a valid process ID
-# CHECKERR: {{.*}}config.fish (line {{\d+}}):
-# CHECKERR:     and builtin fg $args[-1]
-# CHECKERR:         ^
-# CHECKERR: in function 'fg' with arguments '2147483648'
-# CHECKERR: {{\t}}called on line {{\d+}} of file {{.*}}/fg.fish
+# CHECKERR: {{.*}}checks/fg.fish (line {{\d+}}):
+# CHECKERR: fg (math 2 ^ 31)
+# CHECKERR: ^
 # CHECKERR: (Type 'help fg' for related documentation)
 
 fg 0 2>| string match --max-matches=1 '*' >&2
@@ -23,3 +21,9 @@ builtin fg -- -1 2>| string match --max-matches=1 '*' >&2
 
 builtin fg -- -(math 2 ^ 31) 2>| string match --max-matches=1 '*' >&2
 # CHECKERR: fg: '-2147483648' is not a valid process ID
+
+fg %3
+# CHECKERR: fg: No suitable job: %3
+
+fg %1x
+# CHECKERR: fg: '%1x' is not a valid job ID
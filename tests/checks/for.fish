@@ -1,4 +1,4 @@
-# RUN: %fish %s
+# RUN: fish=%fish %fish %s
 
 # A for-loop-variable is a local variable in the enclosing scope.
 set -g i global
@@ -53,3 +53,49 @@ end
 # CHECK: foo set
 # CHECK: foo value is bar
 # CHECK: foo value is 3
+
+# Parallel loops run every iteration in a separate process, but print the output in order.
+function greet
+    echo hello $argv
+end
+set -g greeting_suffix !
+for --parallel 3 delay in 0.3 0.2 0.1
+    sleep $delay
+    greet $delay$greeting_suffix
+end
+# CHECK: hello 0.3!
+# CHECK: hello 0.2!
+# CHECK: hello 0.1!
+
+for --parallel 2 x in 1 2 3
+    test $x -ne 2
+end
+echo $status
+# CHECK: 1
+
+set -g outer before
+for --parallel 2 x in 1 2 3
+    test $x = 2 && continue
+    set -g outer $x
+    echo iteration $x
+end
+echo $outer
+# CHECK: iteration 1
+# CHECK: iteration 3
+# CHECK: before
+
+for --parallel 2 x in 1 2
+    echo out $x
+    echo err $x >&2
+end 2>&1 | string join ' '
+# CHECK: out 1 err 1 out 2 err 2
+
+$fish -c 'for --parallel 0 x in 1; end'
+# CHECKERR: fish: for: 0: --parallel expects a positive integer
+# CHECKERR: for --parallel 0 x in 1; end
+# CHECKERR:                ^
+
+$fish -c 'for --serial 2 x in 1; end'
+# CHECKERR: fish: for: --serial: unknown option
+# CHECKERR: for --serial 2 x in 1; end
+# CHECKERR:     ^~~~~~~^
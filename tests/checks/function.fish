@@ -244,4 +244,37 @@ for flag in --on-process-exit --on-job-exit
     # CHECKERR:     ^~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~^
 end
 
+function tick --on-timeout 0.1
+    echo timeout fired $argv
+end
+functions --handlers-type timeout
+# CHECK: Event timeout
+# CHECK: 0.1 tick
+command sleep 0.3
+# CHECK: timeout fired TIMER 0.1
+echo after
+# CHECK: after
+# A timeout only fires once.
+functions --handlers-type timeout
+command sleep 0.2
+
+set -g ticks 0
+function tock --on-timer 50ms
+    set -g ticks (math $ticks + 1)
+end
+command sleep 0.2
+test $ticks -ge 1 && echo ticked
+# CHECK: ticked
+functions -e tock
+set -l before $ticks
+command sleep 0.2
+test $ticks = $before && echo stopped
+# CHECK: stopped
+
+function bad --on-timer 0s
+end
+# CHECKERR: {{.*}}/function.fish (line {{\d+}}): function: 0s: invalid duration
+# CHECKERR: function bad --on-timer 0s
+# CHECKERR: ^~~~~~~~~~~~~~~~~~~~~~~~^
+
 exit 0
@@ -85,6 +85,99 @@ string join \n **/bar | sort
 # CHECK: bar
 # CHECK: foo/bar
 
+# Qualifiers select matches by type, size, age and owner.
+mkdir quals
+cd quals
+mkdir dir
+echo hello >small
+printf '%2000s' x >big
+touch empty
+chmod +x big
+ln -s small link
+touch -t 200001010000 old
+touch -t 201001010000 big
+echo *[file]
+# CHECK: big empty link old small
+echo *[dir] *[link]
+# CHECK: dir link
+echo *[exec] *[size0]
+# CHECK: big empty old
+echo *[file,size+1k] *[file,size-1k]
+# CHECK: big empty link old small
+echo *[dir,link]
+# CHECK: dir link
+echo *[mtime+365] *[file,mtime-1]
+# CHECK: big old empty link small
+echo **[file,size1k]
+# CHECK: big
+count *[user:(id -u)]
+# CHECK: 6
+echo *[file,rsort:size]
+# CHECK: big link small empty old
+echo *[mtime+1,sort:mtime] *[mtime+1,rsort:mtime]
+# CHECK: old big big old
+# Quoted or invalid qualifiers are literal.
+set -l none *'[file]' *[nonsense]
+count $none
+# CHECK: 0
+echo \*[file]
+# CHECK: *[file]
+set -l list ig x
+echo *$list[1]
+# CHECK: big
+echo *[user:no_such_user_hopefully]
+# CHECKERR: {{.*}}glob.fish (line {{\d+}}): Unknown user or group in wildcard qualifier 'user:no_such_user_hopefully'
+# CHECKERR: echo *[user:no_such_user_hopefully]
+# CHECKERR: ^
+cd ..
+rm -Rf quals
+
+# Ignore files and depth limits for recursive wildcards.
+mkdir -p repo/.git repo/src/gen repo/node_modules/pkg repo/a/b/c
+cd repo
+touch src/main.rs src/gen/out.rs node_modules/pkg/index.rs a/b/c/deep.rs a/top.rs
+echo node_modules/ >.gitignore
+echo 'gen' >src/.ignore
+string join \n **.rs | sort
+# CHECK: a/b/c/deep.rs
+# CHECK: a/top.rs
+# CHECK: node_modules/pkg/index.rs
+# CHECK: src/gen/out.rs
+# CHECK: src/main.rs
+string join \n **.rs[gitignore] | sort
+# CHECK: a/b/c/deep.rs
+# CHECK: a/top.rs
+# CHECK: src/main.rs
+echo **.rs[depth:1]
+# CHECK: a/top.rs src/main.rs
+count **.rs[depth:0]
+# CHECK: 0
+echo src/**.rs[depth:0]
+# CHECK: src/main.rs
+# The ignore files of the repository apply in subdirectories too.
+cd src
+echo **.rs[gitignore]
+# CHECK: main.rs
+cd ..
+ln -s ../a src/link
+string join \n src/**.rs[gitignore] | sort
+# CHECK: src/main.rs
+string join \n src/**.rs[gitignore,follow] | sort
+# CHECK: src/link/b/c/deep.rs
+# CHECK: src/link/top.rs
+# CHECK: src/main.rs
+begin
+    set -l fish_glob_ignore node_modules 'ge*'
+    string join \n **.rs | sort
+end
+# CHECK: a/b/c/deep.rs
+# CHECK: a/top.rs
+# CHECK: src/link/b/c/deep.rs
+# CHECK: src/link/top.rs
+# CHECK: src/main.rs
+cd ..
+rm -Rf repo
+
 # Clean up.
 cd $oldpwd
 rm -Rf $tmpdir
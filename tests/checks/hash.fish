@@ -0,0 +1,57 @@
+#RUN: %fish %s
+
+set -l tmp (mktemp -d)
+printf '#!/bin/sh\necho one\n' >$tmp/hashtest-cmd
+chmod +x $tmp/hashtest-cmd
+set -gx PATH $tmp $PATH
+
+hash -r
+hash | string match -e hashtest
+echo $status
+# CHECK: 1
+
+hashtest-cmd
+# CHECK: one
+hash | string match -e hashtest | string replace -- $tmp TMP
+# CHECK: hashtest-cmd TMP/hashtest-cmd
+
+# Changing $PATH clears the table.
+set -l tmp2 (mktemp -d)
+printf '#!/bin/sh\necho two\n' >$tmp2/hashtest-cmd
+chmod +x $tmp2/hashtest-cmd
+set -gx PATH $tmp2 $PATH
+hash | string match -e hashtest
+hashtest-cmd
+# CHECK: two
+
+hash -d hashtest-cmd
+hash -d hashtest-cmd
+# CHECKERR: hash: hashtest-cmd: not found
+echo $status
+# CHECK: 1
+
+hash hashtest-cmd
+hash | string match -e hashtest | string replace -- $tmp2 TMP2
+# CHECK: hashtest-cmd TMP2/hashtest-cmd
+
+hash nonexistent-command-xyz
+# CHECKERR: hash: nonexistent-command-xyz: not found
+echo $status
+# CHECK: 1
+
+# Commands that were not found are looked up again, so a command can be used right after it
+# was installed.
+command -q hashtest-new
+or echo not found
+# CHECK: not found
+printf '#!/bin/sh\necho new\n' >$tmp2/hashtest-new
+chmod +x $tmp2/hashtest-new
+hashtest-new
+# CHECK: new
+
+hash -r hashtest-cmd
+# CHECKERR: hash: expected 0 arguments; got 1
+hash -r -d
+# CHECKERR: hash: --reset --delete: options cannot be used together
+
+rm -rf $tmp $tmp2
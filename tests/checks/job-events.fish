@@ -0,0 +1,34 @@
+# RUN: %fish %s
+
+function on_start --on-job-start
+    echo start $argv[1] $argv[4]
+end
+command true
+# CHECK: start JOB_START command true
+# Jobs without external processes do not count.
+begin
+    echo builtin
+end
+# CHECK: builtin
+functions -e on_start
+
+function on_state --on-job-stop --on-job-continue
+    echo $argv[1] (test "$argv[3]" = "$last_pid" && echo pgid) $argv[4]
+end
+functions --handlers-type job-stop
+# CHECK: Event job-stop
+# CHECK: job-stop on_state
+
+status job-control full
+sleep 5 &
+set -l pid $last_pid
+command kill -STOP $pid
+command sleep 0.2
+# CHECK: JOB_STOP pgid sleep 5 &
+command kill -CONT $pid
+command sleep 0.2
+# CHECK: JOB_CONTINUE pgid sleep 5 &
+
+status job-control interactive
+command kill -9 $pid
+wait $pid 2>/dev/null
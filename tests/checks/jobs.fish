@@ -29,12 +29,42 @@ disown $last_pid
 jobs -q
 echo $status
 #CHECK: 1
+jobs --json
+echo $status
+#CHECK: []
+#CHECK: 1
 sleep 5 &
 sleep 5 &
 jobs -c
 #CHECK: Command
 #CHECK: sleep
 #CHECK: sleep
+jobs --json | string replace -ra '\b[0-9]+(\.[0-9]+)?\b' N | string replace -a '}, {"id"' '}'\n'{"id"'
+#CHECK: [{"id": N, "internal_id": N, "pgid": N, "foreground": false, "state": "running", "start_time": N, "command": "sleep N &", "processes": [{"pid": N, "argv": ["sleep", "N"], "type": "external", "state": "running", "status": null}]}
+#CHECK: {"id": N, "internal_id": N, "pgid": N, "foreground": false, "state": "running", "start_time": N, "command": "sleep N &", "processes": [{"pid": N, "argv": ["sleep", "N"], "type": "external", "state": "running", "status": null}]}]
+jobs --json --last | string match -q '[{"id": *}]'
+and echo valid
+#CHECK: valid
+# The array is closed even if a later argument is invalid.
+jobs --json %1 %9 | string match -q '[{"id": *}]'
+and echo closed
+#CHECKERR: jobs: No suitable job: %9
+#CHECK: closed
+sleep 6 &
+test (jobs -p %%) = $last_pid
+and echo current job
+#CHECK: current job
+test (jobs -p '%?sleep 6') = $last_pid
+and echo substring
+#CHECK: substring
+jobs -p %sleep
+#CHECKERR: jobs: Ambiguous job specification '%sleep' matches 3 jobs
+test (jobs -p %-) != $last_pid
+and echo previous job
+#CHECK: previous job
+disown '%?sleep 6'
+jobs -p '%?sleep 6'
+#CHECKERR: jobs: No suitable job: %?sleep 6
 jobs -q
 echo $status
 #CHECK: 0
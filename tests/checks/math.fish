@@ -428,3 +428,184 @@ math -s 6 --scale-mode=ceiling "1 / 3 - 1"
 # CHECK: -0.666666
 math -s 6 --scale-mode=ceiling "2 / 3 - 1"
 # CHECK: -0.333333
+
+# Variables and assignments
+math -v x=3 'x^2 + 1'
+# CHECK: 10
+math -v a=3 -v b=4 'sqrt(a^2 + b^2)'
+# CHECK: 5
+math -v x=2 'x * 3'
+# CHECK: 6
+math -v n=2^10 -v m='n / 2' m
+# CHECK: 512
+math 'a = 2; b = a * 3; a + b'
+# CHECK: 8
+math 'a = 5;'
+# CHECK: 5
+not math 'pi = 3'
+# CHECKERR: math: Error: Name is already used by a function or constant
+# CHECKERR: 'pi = 3'
+# CHECKERR:  ^^
+math -v pi=3 pi
+echo $status
+# CHECKERR: math: pi: invalid variable name
+# CHECK: 2
+math -v foo 1
+echo $status
+# CHECKERR: math: foo: invalid variable, expected NAME=VALUE
+# CHECK: 2
+
+# User-defined functions
+math --define 'hyp(a, b) = sqrt(a^2 + b^2)'
+echo $status
+# CHECK: 0
+math 'hyp(3, 4)'
+# CHECK: 5
+math -v x=5 'hyp(x, 12)'
+# CHECK: 13
+math --define 'sq(x) = x * x' 'sq(hyp(3, 4))'
+# CHECK: 25
+math --define 'answer() = 42' 'answer + 1'
+# CHECK: 43
+not math --define 'inv(x) = 1 / x' 'inv(0)'
+# CHECKERR: math: Error: Division by zero
+# CHECKERR: 'inv(0)'
+# CHECKERR:  ^~^
+not math --define 'f(x) 2'
+# CHECKERR: math: Error: Invalid definition, expected NAME(PARAMETERS) = EXPRESSION
+# CHECKERR: 'f(x) 2'
+# CHECKERR:       ^
+not math --define 'sin(x) = x'
+# CHECKERR: math: Error: Name is already used by a function or constant
+# CHECKERR: 'sin(x) = x'
+# CHECKERR:  ^~^
+not math --define 'g(x) = x + y'
+# CHECKERR: math: Error: Unknown function
+# CHECKERR: 'g(x) = x + y'
+# CHECKERR:             ^
+
+# A function can't call itself, not even through another one.
+math --define 'f(x) = x' --define 'g(x) = f(x) + 1'
+not math --define 'f(x) = g(x)'
+# CHECKERR: math: Error: Function would call itself
+# CHECKERR: 'f(x) = g(x)'
+# CHECKERR:         ^
+not math --define 'h(x) = 1 + h(x - 1)'
+# CHECKERR: math: Error: Function would call itself
+# CHECKERR: 'h(x) = 1 + h(x - 1)'
+# CHECKERR:             ^
+math 'g(2)'
+# CHECK: 3
+
+# Aggregate functions
+set -l durations 12 15 9 30 7
+math 'sum(' $durations ')'
+# CHECK: 73
+math 'mean(' $durations ')'
+# CHECK: 14.6
+math 'median(' $durations ')'
+# CHECK: 12
+math 'median(4, 1, 3, 2)'
+# CHECK: 2.5
+math 'max(' (string join , -- $durations) ')'
+# CHECK: 30
+# Only the aggregate functions take lists separated by whitespace.
+math 'max(5 -3)'
+# CHECK: 2
+math 'percentile(90,' $durations ')'
+# CHECK: 24
+math 'percentile(50, 1 2 3 4)'
+# CHECK: 2.5
+math 'stddev(2, 4, 4, 4, 5, 5, 7, 9)'
+# CHECK: 2
+math 'sum(1, 2, 3.5)'
+# CHECK: 6.5
+math 'sum(9007199254740991, 2, -9007199254740991)'
+# CHECK: 2
+math 'mean(10 -2)'
+# CHECK: 4
+math 'mean(10 - 2)'
+# CHECK: 8
+math 'sum(2 (3 4))'
+# CHECKERR: math: Error: Missing operator
+# CHECKERR: 'sum(2 (3 4))'
+# CHECKERR:          ^
+math 'clamp(15, 0, 10)'
+# CHECK: 10
+math 'clamp(-5, 0, 10)'
+# CHECK: 0
+math 'percentile(101, 1, 2)'
+# CHECKERR: math: Error: Result is not a number
+# CHECKERR: 'percentile(101, 1, 2)'
+
+# Integer mode
+math --integer '2^64 + 1'
+# CHECK: 18446744073709551617
+math -i 9007199254740993 + 0
+# CHECK: 9007199254740993
+math -i '7 / 2'
+# CHECK: 3
+math -i '-7 / 2'
+# CHECK: -3
+math -i '-7 % 3'
+# CHECK: -1
+math -i '1 << 100'
+# CHECK: 1267650600228229401496703205376
+math -i '0xFF >> 4'
+# CHECK: 15
+math -i '1 << 2 + 1'
+# CHECK: 8
+math -i '0b1010 + 0o17 + 0x_ff + 1_000'
+# CHECK: 1280
+math -i 'bitxor(0xF0, 0xFF)'
+# CHECK: 15
+math -i 'max(3, 9, 4)'
+# CHECK: 9
+math -i 'sum(' 2^100 1 -1 ')'
+# CHECK: 1267650600228229401496703205376
+math --integer --define 'f(x) = x * 2' 'f(3)'
+# CHECK: 6
+math -i 'f(2^100) + 1'
+# CHECK: 2535301200456458802993406410753
+not math -i 'hyp(3, 4)'
+# CHECKERR: math: Error: Only integers are supported with --integer
+# CHECKERR: 'hyp(3, 4)'
+# CHECKERR:  ^~^
+math -i abs -5
+# CHECK: 5
+math -i -v n=10 'n * 3'
+# CHECK: 30
+math -i 'a = 2^70; a / 2^69'
+# CHECK: 2
+math -i --base=hex '2^100'
+# CHECK: 0x10000000000000000000000000
+math -i -b 2 5
+# CHECK: 0b101
+math -i -b 8 -- -8
+# CHECK: -010
+math --base binary 10
+# CHECK: 0b1010
+not math -i '1.5 + 1'
+# CHECKERR: math: Error: Only integers are supported with --integer
+# CHECKERR: '1.5 + 1'
+# CHECKERR:  ^~^
+not math -i '2^127'
+# CHECKERR: math: Error: Integer overflow
+# CHECKERR: '2^127'
+# CHECKERR:   ^
+not math -i 'sqrt(4)'
+# CHECKERR: math: Error: Only integers are supported with --integer
+# CHECKERR: 'sqrt(4)'
+# CHECKERR:  ^~~^
+not math -i '1 << 128'
+# CHECKERR: math: Error: Shift amount must be between 0 and 127
+# CHECKERR: '1 << 128'
+# CHECKERR:    ^^
+not math -i '5 / 0'
+# CHECKERR: math: Error: Division by zero
+# CHECKERR: '5 / 0'
+# CHECKERR:    ^
+math -i -s 2 1
+echo $status
+# CHECKERR: math: invalid option combination, non-zero scale value not valid with --integer
+# CHECK: 2
@@ -182,3 +182,67 @@ printf '\uf641' | display_bytes
 printf '%s' \xef\x99\x81 | display_bytes
 # CHECK: 0000000 357 231 201
 # CHECK: 0000003
+
+# Times and durations
+begin
+    set -lx TZ UTC
+    printf '%(%Y-%m-%d %H:%M:%S)T\n' 0
+    # CHECK: 1970-01-01 00:00:00
+    printf '[%10(%H:%M)T] [%-10(%H:%M)T] [%.2(%H:%M)T]\n' 86399 86399 86399
+    # CHECK: [     23:59] [23:59     ] [23]
+    printf '%(%s)T\n' 1714566600
+    # CHECK: 1714566600
+    set -lx TZ EST5
+    printf '%(%H:%M %Z)T\n' 0
+    # CHECK: 19:00 EST
+end
+
+# -1 and a missing argument are the current time.
+set -l before (date +%s)
+set -l now (printf '%(%s)T' -1) (printf '%(%s)T')
+set -l after (date +%s)
+test $before -le $now[1] -a $now[1] -le $after -a $before -le $now[2] -a $now[2] -le $after
+and echo now
+# CHECK: now
+
+printf '%(%H:%M:%S)D %()D\n' 3725 3725
+# CHECK: 01:02:05 01:02:05
+printf '%(%H:%M)D\n' 259200
+# CHECK: 72:00
+printf '%(%dd %Hh %Mm %Ss)D\n' 90061
+# CHECK: 1d 01h 01m 01s
+printf '%(%M:%S)D|%(%s seconds)D|%8(%S)D\n' -125 90 7
+# CHECK: -02:05|90 seconds|      07
+
+printf '%(%H' 1
+# CHECKERR: %(%H: missing closing parenthesis
+printf '%(%H)d\n' 1
+# CHECKERR: %(%H)d: invalid conversion specification
+printf '%(%q)D\n' 1
+# CHECKERR: %q: invalid duration format (unknown directive '%q')
+printf '%(%H)T\n' foo
+# CHECKERR: foo: expected a numeric value
+
+# Positional arguments
+printf '%2$s %1$s\n' world hello
+# CHECK: hello world
+printf '%2$s=%1$s\n' 1 a 2 b
+# CHECK: a=1
+# CHECK: b=2
+printf '%1$s-%1$s\n' a b
+# CHECK: a-a
+# CHECK: b-b
+printf '[%3$*1$s] [%4$-*1$.*2$f]\n' 8 2 x 1.23456
+# CHECK: [       x] [1.23    ]
+printf '%2$b|%1$d\n' 3 'a\x41'
+# CHECK: aA|3
+printf '%1$s|%s\n' a b
+echo
+# CHECK: a|
+# CHECKERR: %s: numbered and unnumbered arguments can't be mixed
+printf '%0$s\n' a
+# CHECKERR: %0$: invalid conversion specification
+
+# Grouping uses the LC_NUMERIC locale, and the C locale has no thousands separator.
+printf "%'d %'.7g\n" 1234567 1234567
+# CHECK: 1234567 1234567
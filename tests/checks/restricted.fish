@@ -0,0 +1,80 @@
+#RUN: fish=%fish %fish %s
+
+set -l tmp (mktemp -d)
+echo 'echo sourced' >$tmp/script.fish
+
+# The init commands run before the restrictions apply.
+$fish --restricted -C 'set -g PATH $PATH' -c 'echo ok'
+# CHECK: ok
+
+$fish --restricted -c 'cd /; echo $status'
+# CHECKERR: cd: Not allowed in restricted mode
+# CHECK: 1
+
+$fish --restricted -c 'set PATH /tmp; echo $status; set -e SHELL; echo $status'
+# CHECKERR: set: Cannot change the variable 'PATH' in restricted mode
+# CHECKERR: set: Cannot change the variable 'SHELL' in restricted mode
+# CHECK: 1
+# CHECK: 1
+
+# Neither through an assignment before a command, nor through function arguments.
+$fish --restricted -c 'PATH=/tmp/evil ls'
+# CHECKERR: fish: Cannot change the variable 'PATH' in restricted mode
+# CHECKERR: PATH=/tmp/evil ls
+# CHECKERR: ^~~~~~~~~~~~~^
+
+$fish --restricted -c 'LD_PRELOAD=/x.so true'
+# CHECKERR: fish: Cannot change the variable 'LD_PRELOAD' in restricted mode
+# CHECKERR: LD_PRELOAD=/x.so true
+# CHECKERR: ^~~~~~~~~~~~~~~^
+
+$fish --restricted -c 'function f -a PATH; end'
+# CHECKERR: {{.*}}function: Cannot change the variable 'PATH' in restricted mode
+# CHECKERR: function f -a PATH; end
+# CHECKERR: ^{{~*}}^
+
+$fish --restricted -c 'foo=bar echo $foo'
+# CHECK: bar
+
+$fish --restricted -c 'set -g foo bar; echo $foo'
+# CHECK: bar
+
+$fish --restricted -c 'echo hello >/dev/null; echo $status'
+# CHECK: 0
+
+$fish --restricted -c "echo hello >$tmp/out"
+# CHECKERR: fish: {{.*}}/out: Redirecting output to a file is not allowed in restricted mode
+# CHECKERR: echo hello >{{.*}}/out
+# CHECKERR: {{ *}}^{{~*}}^
+test -e $tmp/out
+or echo not written
+# CHECK: not written
+
+$fish --restricted -c 'exec true'
+# CHECKERR: fish: exec: Not allowed in restricted mode
+# CHECKERR: exec true
+# CHECKERR: {{ *}}^~~^
+
+$fish --restricted -c '/bin/sh -c true'
+# CHECKERR: fish: /bin/sh: Commands containing '/' are not allowed in restricted mode
+# CHECKERR: /bin/sh -c true
+# CHECKERR: ^~~~~^
+
+# The iterations of a parallel loop would run in an unrestricted fish.
+$fish --restricted -c 'for --parallel 2 i in 1; /bin/sh -c true; end'
+# CHECKERR: fish: for: --parallel is not allowed in restricted mode
+# CHECKERR: for --parallel 2 i in 1; /bin/sh -c true; end
+# CHECKERR:     ^~~~~~~~~^
+
+$fish --restricted -c "source $tmp/script.fish"
+# CHECKERR: source: Cannot source '{{.*}}/script.fish' in restricted mode, it is not in $fish_restricted_source_path
+
+$fish --restricted -C "set -g fish_restricted_source_path $tmp" -c "source $tmp/script.fish"
+# CHECK: sourced
+
+# Attempts fire an event.
+$fish --restricted -C 'function log --on-event fish_restricted; echo logged $argv; end' -c 'cd /tmp'
+# CHECKERR: cd: Not allowed in restricted mode
+# CHECK: logged cd /tmp
+
+rm -r $tmp
@@ -1015,6 +1015,136 @@ set line[0] ""
 # CHECKERR: ^
 # CHECKERR: (Type 'help set' for related documentation)
 
+# Maps
+set -l --map colors red ff0000 green 00ff00
+echo $colors[green]
+# CHECK: 00ff00
+echo $colors
+# CHECK: ff0000 00ff00
+set --keys colors
+# CHECK: red
+# CHECK: green
+set colors[blue] 0000ff
+set colors[red] f00
+set -S colors
+# CHECK: $colors: set in local scope, unexported, a map with 3 entries
+# CHECK: $colors[red]: |f00|
+# CHECK: $colors[green]: |00ff00|
+# CHECK: $colors[blue]: |0000ff|
+set -l | string match 'colors *'
+# CHECK: colors [red]=f00 [green]=00ff00 [blue]=0000ff
+
+set -q colors[blue]; and echo has blue
+# CHECK: has blue
+set -e colors[blue]
+set -q colors[blue]; or echo no blue
+# CHECK: no blue
+count $colors[blue]
+# CHECK: 0
+
+set -l key "light green"
+set colors[$key] 90ee90
+echo "$colors[$key]"
+# CHECK: 90ee90
+set --map --append colors black 000
+
+# Keys end at the first closing bracket, both when setting and expanding.
+set colors[a]b] x
+# CHECKERR: set: Invalid index starting at 'a]b]'
+# CHECKERR: {{.*}}checks/set.fish (line {{\d+}}):
+# CHECKERR: set colors[a]b] x
+# CHECKERR: ^
+# CHECKERR: (Type 'help set' for related documentation)
+set --map --append colors 'a]b' x
+# CHECKERR: set: a]b: map keys cannot contain ']'
+# CHECKERR: {{.*}}checks/set.fish (line {{\d+}}):
+# CHECKERR: set --map --append colors 'a]b' x
+# CHECKERR: ^
+# CHECKERR: (Type 'help set' for related documentation)
+echo $colors[green]]
+# CHECK: 00ff00]
+set --keys colors
+# CHECK: red
+# CHECK: green
+# CHECK: light green
+# CHECK: black
+
+set -a colors white
+# CHECKERR: set: colors: is a map, use `set --map --append` to add entries
+# CHECKERR: {{.*}}set.fish (line {{\d+}}):
+# CHECKERR: set -a colors white
+# CHECKERR: ^
+# CHECKERR: (Type 'help set' for related documentation)
+
+set --map colors white
+# CHECKERR: set: --map expects a value for every key
+# CHECKERR: {{.*}}set.fish (line {{\d+}}):
+# CHECKERR: set --map colors white
+# CHECKERR: ^
+# CHECKERR: (Type 'help set' for related documentation)
+
+set -l plain 1 2
+set --keys plain
+# CHECKERR: set: plain: not a map
+echo $status
+# CHECK: 1
+
+# Setting plain values turns a map back into a list.
+set colors a b
+set -S colors
+# CHECK: $colors: set in local scope, unexported, with 2 elements
+# CHECK: $colors[1]: |a|
+# CHECK: $colors[2]: |b|
+
+env XDG_CONFIG_HOME= HOME=$PWD/empty $FISH -c 'set -U --map __fish_test_map a 1 b 2'
+env XDG_CONFIG_HOME= HOME=$PWD/empty $FISH -c 'set -S __fish_test_map; echo $__fish_test_map[b]; set -eU __fish_test_map'
+# CHECK: $__fish_test_map: set in universal scope, unexported, a map with 2 entries
+# CHECK: $__fish_test_map[a]: |1|
+# CHECK: $__fish_test_map[b]: |2|
+# CHECK: 2
+
+# Secret variables are redacted in listings, but still expand and export.
+set -g --secret -x __fish_test_secret hunter2
+set -S __fish_test_secret
+# CHECK: $__fish_test_secret: set in global scope, exported, with 1 elements (secret)
+# CHECK: $__fish_test_secret[1]: |<redacted>|
+set -g | string match -e __fish_test_secret
+# CHECK: __fish_test_secret <redacted>
+echo $__fish_test_secret
+# CHECK: hunter2
+env | string match -e __fish_test_secret
+# CHECK: __fish_test_secret=hunter2
+
+# Setting it again keeps it secret.
+set __fish_test_secret swordfish
+set -g | string match -e __fish_test_secret
+# CHECK: __fish_test_secret <redacted>
+set -e __fish_test_secret
+
+set --secret
+# CHECKERR: set: --secret: option requires an argument
+# CHECKERR: {{.*}}set.fish (line {{\d+}}):
+# CHECKERR: set --secret
+# CHECKERR: ^
+# CHECKERR: (Type 'help set' for related documentation)
+
+set --secret -e foo
+# CHECKERR: set: invalid option combination
+# CHECKERR: {{.*}}set.fish (line {{\d+}}):
+# CHECKERR: set --secret -e foo
+# CHECKERR: ^
+# CHECKERR: (Type 'help set' for related documentation)
+
+# Secrets only become universal when asked to.
+env XDG_CONFIG_HOME= HOME=$PWD/empty $FISH -c 'set -U __fish_test_secret plain; set --secret __fish_test_secret hidden; set -S __fish_test_secret'
+# CHECK: $__fish_test_secret: set in global scope, unexported, with 1 elements (secret)
+# CHECK: $__fish_test_secret[1]: |<redacted>|
+# CHECK: $__fish_test_secret: set in universal scope, unexported, with 1 elements
+# CHECK: $__fish_test_secret[1]: |plain|
+env XDG_CONFIG_HOME= HOME=$PWD/empty $FISH -c 'set -U --secret __fish_test_secret hidden'
+env XDG_CONFIG_HOME= HOME=$PWD/empty $FISH -c 'set -S __fish_test_secret; set -eU __fish_test_secret'
+# CHECK: $__fish_test_secret: set in universal scope, unexported, with 1 elements (secret)
+# CHECK: $__fish_test_secret[1]: |<redacted>|
 
 echo Still here
 # CHECK: Still here
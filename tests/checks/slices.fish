@@ -54,10 +54,39 @@ echo $test[1..2..]
 #CHECKERR: {{.*}}: Invalid index value
 #CHECKERR: echo $test[1..2..]
 #CHECKERR:                ^
-echo $test[..1..2]
+echo $test[1..2..x]
 #CHECKERR: {{.*}}: Invalid index value
-#CHECKERR: echo $test[..1..2]
-#CHECKERR:               ^
+#CHECKERR: echo $test[1..2..x]
+#CHECKERR:                ^
+echo $test[1..2..0]
+#CHECKERR: {{.*}}: Invalid index value
+#CHECKERR: echo $test[1..2..0]
+#CHECKERR:                ^
+
+# Ranges with a step
+echo $test[1..-1..2]
+# CHECK: 1 3 5 7 9
+echo $test[-1..1..3]
+# CHECK: 10 7 4 1
+echo $test[..5..2] $test[2..20..5] $test[ 9 .. 1 .. 4 ]
+# CHECK: 1 3 5 2 7 9 5 1
+echo (seq 10)[2..-1..4]
+# CHECK: 2 6 10
+echo $test[2..-1..9223372036854775807] $test[-1..1..9223372036854775807]
+# CHECK: 2 10
+set -l stepped a b c d e f
+set stepped[1..-1..2] A C E
+echo $stepped
+# CHECK: A b C d E f
+set -e stepped[-1..1..2]
+echo $stepped
+# CHECK: A C E
+set stepped[1..2..0] x
+# CHECKERR: set: Invalid index starting at '..0]'
+# CHECKERR: {{.*}}slices.fish (line {{\d+}}):
+# CHECKERR: set stepped[1..2..0] x
+# CHECKERR: ^
+# CHECKERR: (Type 'help set' for related documentation)
 
 set -l empty
 echo $test[ $empty..]
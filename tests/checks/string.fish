@@ -128,6 +128,39 @@ string pad -c_ --width 5 --center --right longer-than-width-param x
 # CHECK: longer-than-width-param
 # CHECK: ___________x___________
 
+string parse-time 1970-01-02T00:00:00Z
+# CHECK: 86400
+string parse-time --utc "2024-05-01 12:30" 2024-05-01 2024-05-01T12:30:59
+# CHECK: 1714566600
+# CHECK: 1714521600
+# CHECK: 1714566659
+# Fractional seconds are ignored, offsets may or may not have a colon.
+string parse-time 2024-05-01T12:30:15.250+02:00 2024-05-01T12:30-0500
+# CHECK: 1714559415
+# CHECK: 1714584600
+string parse-time -u -f '%d.%m.%Y %H:%M' '01.05.2024 12:30'
+# CHECK: 1714566600
+string parse-time -f '%Y-%m-%d %H:%M %z' '2024-05-01 12:30 +0200'
+# CHECK: 1714559400
+begin
+    set -lx TZ EST5
+    string parse-time 2024-05-01T12:30 2024-05-01T12:30Z
+    # CHECK: 1714584600
+    # CHECK: 1714566600
+end
+
+string parse-time -u 2024-05-01 2024-13-01 2024-05-01T25:00 yesterday; echo $status
+# CHECK: 1714521600
+# CHECKERR: string parse-time: Cannot parse time '2024-13-01'
+# CHECKERR: string parse-time: Cannot parse time '2024-05-01T25:00'
+# CHECKERR: string parse-time: Cannot parse time 'yesterday'
+# CHECK: 1
+string parse-time -f %d.%m.%Y 01.05.2024x; echo $status
+# CHECKERR: string parse-time: Cannot parse time '01.05.2024x'
+# CHECK: 1
+string parse-time -q 2024-05-01 nonsense; echo $status
+# CHECK: 1
+
 # Current behavior is that only a single padding character is supported.
 # We can support longer strings in future without breaking compatibility.
 string pad -c ab -w4 .
@@ -825,6 +858,36 @@ count (string split / /)
 count (echo -ne '\x00\x00\x00' | string split0)
 # CHECK: 3
 
+# string split --csv and string join --csv
+string split --csv , 'fish,"1,2,3","say ""hi"""'
+# CHECK: fish
+# CHECK: 1,2,3
+# CHECK: say "hi"
+# A quoted field can continue on the next line.
+printf '%s\n' 'a,"multi' 'line",b' 'c,d' | string split --csv -f2 ,
+# CHECK: multi
+# CHECK: line
+# CHECK: d
+printf 'name\tcount\nfish\t3\n' | string split --csv -f2 \t
+# CHECK: count
+# CHECK: 3
+string split --csv , 'a,"b'
+echo $status
+# CHECKERR: string split: Unterminated quoted field
+# CHECK: 1
+string split --csv -m1 , a,b
+# CHECKERR: string split: invalid option combination, --max and --right can't be used with --csv
+string split --csv ', ' a,b
+# CHECKERR: string split: The separator must be a single character with --csv
+string join --csv , fish 1,2,3 'say "hi"' 'two'\n'lines'
+# CHECK: fish,"1,2,3","say ""hi""","two
+# CHECK: lines"
+string join --csv --quote "'" \; "it's" 'a;b'
+# CHECK: 'it''s';'a;b'
+string split --csv , (string join --csv , 'x,y' 'z"')
+# CHECK: x,y
+# CHECK: z"
+
 # string collect
 count (echo one\ntwo\nthree\nfour | string collect)
 count (echo one | string collect)
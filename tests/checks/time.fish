@@ -7,6 +7,8 @@ time sleep 0
 #CHECKERR: Executed in {{[\d,.\s]*}} {{millis|micros|secs}} {{\s*}}fish {{\s*}}external
 #CHECKERR: usr time {{[\d,.\s]*}} {{millis|micros|secs}} {{[\d,.\s]*}} {{millis|micros|secs}} {{[\d,.\s]*}} {{millis|micros|secs}}
 #CHECKERR: sys time {{[\d,.\s]*}} {{millis|micros|secs}} {{[\d,.\s]*}} {{millis|micros|secs}} {{[\d,.\s]*}} {{millis|micros|secs}}
+#CHECKERR: page faults {{\s*\d+}} major {{\d+}} minor
+#CHECKERR: switches {{\s*\d+}} voluntary {{\d+}} involuntary
 time for i in (seq 1 2)
     echo banana
 end
@@ -17,6 +19,8 @@ end
 #CHECKERR: Executed in {{[\d,.\s]*}} {{millis|micros|secs}} {{\s*}}fish {{\s*}}external
 #CHECKERR: usr time {{[\d,.\s]*}} {{millis|micros|secs}} {{[\d,.\s]*}} {{millis|micros|secs}} {{[\d,.\s]*}} {{millis|micros|secs}}
 #CHECKERR: sys time {{[\d,.\s]*}} {{millis|micros|secs}} {{[\d,.\s]*}} {{millis|micros|secs}} {{[\d,.\s]*}} {{millis|micros|secs}}
+#CHECKERR: page faults {{\s*\d+}} major {{\d+}} minor
+#CHECKERR: switches {{\s*\d+}} voluntary {{\d+}} involuntary
 
 # Make sure we're not double-parsing
 time echo 'foo -s   bar'
@@ -25,24 +29,32 @@ time echo 'foo -s   bar'
 #CHECKERR: Executed in {{[\d,.\s]*}} {{millis|micros|secs}} {{\s*}}fish {{\s*}}external
 #CHECKERR: usr time {{[\d,.\s]*}} {{millis|micros|secs}} {{[\d,.\s]*}} {{millis|micros|secs}} {{[\d,.\s]*}} {{millis|micros|secs}}
 #CHECKERR: sys time {{[\d,.\s]*}} {{millis|micros|secs}} {{[\d,.\s]*}} {{millis|micros|secs}} {{[\d,.\s]*}} {{millis|micros|secs}}
+#CHECKERR: page faults {{\s*\d+}} major {{\d+}} minor
+#CHECKERR: switches {{\s*\d+}} voluntary {{\d+}} involuntary
 
 true && time a=b not builtin true | true
 #CHECKERR: ___{{.*}}
 #CHECKERR: {{.*}}
 #CHECKERR: {{.*}}
 #CHECKERR: {{.*}}
+#CHECKERR: {{.*}}
+#CHECKERR: {{.*}}
 
 not time true
 #CHECKERR: ___{{.*}}
 #CHECKERR: {{.*}}
 #CHECKERR: {{.*}}
 #CHECKERR: {{.*}}
+#CHECKERR: {{.*}}
+#CHECKERR: {{.*}}
 
 not time a=b true
 #CHECKERR: ___{{.*}}
 #CHECKERR: {{.*}}
 #CHECKERR: {{.*}}
 #CHECKERR: {{.*}}
+#CHECKERR: {{.*}}
+#CHECKERR: {{.*}}
 
 # Currently illegal syntax. Same in zsh. POSIX shells call the external command "time" here.
 a=b time true
@@ -74,3 +86,38 @@ begin
     # CHECK: No this is Patrick
 end
 rm time
+
+set -g fish_time_format 'took %es, %M KiB, %%e'
+time true
+#CHECKERR: took {{\d+\.\d\d}}s, {{\d+|-}} KiB, %e
+set -g fish_time_format ''
+time true
+set -e fish_time_format
+
+time --repeat 3 --warmup 1 echo hello
+#CHECK: hello
+#CHECK: hello
+#CHECK: hello
+#CHECK: hello
+#CHECKERR: ________________________________________________________
+#CHECKERR: Executed 3 times after 1 warmup runs
+#CHECKERR: mean {{[\d.\s]*}} {{millis|micros|secs}} ± {{[\d.]*}} {{millis|micros|secs}}
+#CHECKERR: min {{[\d.\s]*}} {{millis|micros|secs}}
+#CHECKERR: max {{[\d.\s]*}} {{millis|micros|secs}}
+#CHECKERR: usr time {{[\d.\s]*}} {{millis|micros|secs}} (mean)
+#CHECKERR: sys time {{[\d.\s]*}} {{millis|micros|secs}} (mean)
+
+time --repeat 2 --json echo a 2>| string replace -ra '[\d.]+' N
+#CHECK: a
+#CHECK: a
+#CHECK: {"runs": N, "warmup": N, "mean": N, "stddev": N, "min": N, "max": N, "user": N, "system": N, "max_rss_kib": {{N|null}}, "major_faults": N, "minor_faults": N, "voluntary_switches": N, "involuntary_switches": N, "times": [N, N]}
+
+# The arguments are not expanded again.
+set -l x 'a; echo injected'
+time --repeat 1 --json echo $x 2>/dev/null
+#CHECK: a; echo injected
+
+time --repeat 0 true
+#CHECKERR: time: 0: invalid integer
+time --repeat 2
+#CHECKERR: time: expected >= 1 arguments; got 0
@@ -70,3 +70,13 @@ set -e fish_trace
 
 echo untraced
 # CHECK: untraced
+
+# Values of secret variables are redacted.
+set fish_trace 1
+set --secret token hunter2
+echo $token "user:$token"
+set -e fish_trace
+# CHECK: hunter2 user:hunter2
+# CHECKERR: > set --secret token <redacted>
+# CHECKERR: > echo '<redacted>' 'user:<redacted>'
+# CHECKERR: > set -e fish_trace
@@ -140,3 +140,18 @@ chmod +x ./test
 
 PATH=.:$PATH type -P test
 # CHECK: ./test
+
+function frobnicate
+end
+function frobnicator
+end
+type --suggest frobnicaet
+# CHECK: frobnicate
+type --suggest frobnicatr
+# CHECK: frobnicate
+# CHECK: frobnicator
+type --suggest zzzzzzzzzzzzzzzz
+echo $status
+# CHECK: 1
+type --suggest --query frobnicate
+# CHECKERR: type: invalid option combination
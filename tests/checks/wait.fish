@@ -76,3 +76,35 @@ wait -- -1
 # CHECKERR: wait: Could not find child processes with the name '-1'
 wait -- -(math 2 ^ 31)
 # CHECKERR: wait: Could not find child processes with the name '-2147483648'
+
+# Timeouts.
+sleep 5 &
+set -l p $last_pid
+wait --timeout 0.1 $p
+echo $status
+# CHECK: 124
+jobs -q
+and echo still running
+# CHECK: still running
+wait --timeout 100ms --signal TERM $p
+echo $status
+# CHECK: 124
+jobs -q
+or echo gone
+# CHECK: gone
+
+command true &
+wait --timeout 5 $last_pid
+echo $status
+# CHECK: 0
+
+wait --timeout 5 --kill-after 0.1
+echo $status
+# CHECK: 0
+
+wait --timeout soon
+# CHECKERR: wait: soon: invalid duration
+wait --timeout 1 --signal SIGFOO
+# CHECKERR: wait: Unknown signal 'SIGFOO'
+wait --signal TERM
+# CHECKERR: wait: invalid option combination, --signal and --kill-after require --timeout
@@ -0,0 +1,42 @@
+#!/usr/bin/env python3
+from pexpect_helper import SpawnedProc
+import os
+
+env = os.environ.copy()
+env["TERM"] = "not-dumb"
+# Without an XTVERSION response, support for notifications is detected from TERM_PROGRAM.
+env["TERM_PROGRAM"] = "WezTerm"
+
+sp = SpawnedProc(env=env)
+sendline, expect_prompt, expect_re = sp.sendline, sp.expect_prompt, sp.expect_re
+expect_prompt()
+
+NOTIFICATION = "\x1b]9;"
+
+
+def expect_notification(body):
+    expect_re(r"\x1b\]9;([^\x07]*)\x07")
+    assert sp.spawn.match.group(1) == body, sp.spawn.match.group(1)
+
+
+# Nothing is sent while fish_notify_duration is unset.
+sendline("sleep 0.6")
+expect_prompt()
+assert NOTIFICATION not in sp.spawn.before
+
+sendline("set -g fish_notify_duration 500")
+expect_prompt()
+
+# Commands that take less time don't send a notification either.
+sendline("echo fast")
+expect_prompt("fast")
+assert NOTIFICATION not in sp.spawn.before
+
+sendline("sleep 0.6")
+expect_notification("'sleep 0.6' has finished")
+expect_prompt()
+
+# Semicolons are replaced since OSC 777 uses them to separate fields.
+sendline("sleep 0.6; false")
+expect_notification("'sleep 0.6, false' has failed with status 1")
+expect_prompt()
@@ -85,6 +85,18 @@ tab_expand_glob(r"echo ben*/somed", r"echo ben*/somedir", len("/somed"))
 # No glob in "current path component," offer completions.
 tab_expand_glob(r"echo {benchmarks/*/,benchm}a", r"echo {benchmarks/*/,benchm}arks/")
 
+# Recursive globs skip the names in $fish_glob_ignore.
+sendline(
+    r"mkdir -p tree/node_modules tree/src && touch tree/node_modules/x.rs tree/src/y.rs tree/src/gen.rs && set -g fish_glob_ignore node_modules gen.rs"
+)
+expect_prompt()
+tab_expand_glob(r"echo tree/**.rs", r"echo tree/src/y.rs")
+sendline(r"set -e fish_glob_ignore")
+expect_prompt()
+tab_expand_glob(
+    r"echo tree/**.rs", r"echo tree/node_modules/x.rs tree/src/gen.rs tree/src/y.rs"
+)
+
 # Test undo and redo.
 # "<" and ">" to undo and redo respectively.
 sendline(r"bind \< undo; bind \> redo")